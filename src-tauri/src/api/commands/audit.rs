@@ -0,0 +1,18 @@
+use crate::api::commands::experimental::get_experimental_features_enabled;
+use crate::core::audit_log::{AuditEvent, AuditLog};
+
+/// Журнал аудита доступен только при включённых экспериментальных функциях.
+#[tauri::command]
+pub async fn get_audit_log(
+    app: tauri::AppHandle,
+    limit: u32,
+    since: Option<String>,
+) -> Result<Vec<AuditEvent>, String> {
+    if !get_experimental_features_enabled(app.clone())? {
+        return Err("Audit log is available only with experimental features enabled".to_string());
+    }
+    let log = AuditLog::new(&app)?;
+    tauri::async_runtime::spawn_blocking(move || log.read(limit as usize, since.as_deref()))
+        .await
+        .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,81 @@
+//! Пользовательские сопоставления архитектур со встроенными бекендами.
+
+use crate::api::scan_cache::{ModelScanCache, SCAN_CACHE_FILE};
+use crate::core::state::ModelState;
+use crate::models::registry::{self, ArchKind, UserDefinedBackendConfig};
+use serde::Serialize;
+use tauri::AppHandle;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RegisteredBackends {
+    pub built_in: Vec<String>,
+    pub custom: Vec<UserDefinedBackendConfig>,
+}
+
+fn same_rule(
+    existing: &UserDefinedBackendConfig,
+    architecture_match: &str,
+    use_gguf: bool,
+) -> bool {
+    existing.use_gguf == use_gguf
+        && existing
+            .architecture_match
+            .trim()
+            .eq_ignore_ascii_case(architecture_match.trim())
+}
+
+/// Сохраняет правила и сбрасывает кэш сканирования: в нём закэширована
+/// архитектура, определённая по старым правилам.
+fn apply_custom_backends(
+    app: &AppHandle,
+    backends: Vec<UserDefinedBackendConfig>,
+) -> Result<Vec<UserDefinedBackendConfig>, String> {
+    ModelState::save_custom_backends(app, &backends)?;
+    registry::set_custom_backends(backends.clone());
+    ModelScanCache::clear(&ModelState::profile_dir(app)?.join(SCAN_CACHE_FILE))?;
+    Ok(backends)
+}
+
+/// Добавить или заменить правило для архитектуры. Правило с тем же
+/// `architecture_match` и форматом перезаписывается.
+#[tauri::command]
+pub fn register_custom_backend(
+    app: AppHandle,
+    config: UserDefinedBackendConfig,
+) -> Result<Vec<UserDefinedBackendConfig>, String> {
+    config.target_arch()?;
+    let mut backends = registry::custom_backends();
+    backends.retain(|existing| !same_rule(existing, &config.architecture_match, config.use_gguf));
+    backends.push(config);
+    apply_custom_backends(&app, backends)
+}
+
+/// Удалить правило для архитектуры и формата.
+#[tauri::command]
+pub fn unregister_custom_backend(
+    app: AppHandle,
+    architecture_match: String,
+    use_gguf: bool,
+) -> Result<Vec<UserDefinedBackendConfig>, String> {
+    let mut backends = registry::custom_backends();
+    let before = backends.len();
+    backends.retain(|existing| !same_rule(existing, &architecture_match, use_gguf));
+    if backends.len() == before {
+        return Err(format!(
+            "No custom backend registered for '{}'",
+            architecture_match.trim()
+        ));
+    }
+    apply_custom_backends(&app, backends)
+}
+
+#[tauri::command]
+pub fn list_registered_backends() -> RegisteredBackends {
+    RegisteredBackends {
+        built_in: ArchKind::ALL
+            .iter()
+            .map(|arch| arch.display_name().to_string())
+            .collect(),
+        custom: registry::custom_backends(),
+    }
+}
@@ -1,6 +1,10 @@
-use crate::core::device::device_label;
+use crate::core::device::{
+    CpuFeatures, IntelGpuInfo, detect_cpu_features, detect_intel_gpu, device_label,
+    query_nvidia_gpu,
+};
 use crate::core::state::SharedState;
 use crate::core::types::DevicePreference;
+use crate::models::SimdCapabilities;
 
 use serde::{Deserialize, Serialize};
 
@@ -22,6 +26,7 @@ pub struct DeviceInfoDto {
     pub neon: bool,
     pub simd128: bool,
     pub f16c: bool,
+    pub cpu_features: CpuFeatures,
 }
 
 #[tauri::command]
@@ -45,9 +50,16 @@ pub fn get_device_info(state: tauri::State<'_, SharedState>) -> Result<DeviceInf
         neon,
         simd128,
         f16c,
+        cpu_features: detect_cpu_features(),
     })
 }
 
+/// SIMD-расширения CPU, определённые во время выполнения.
+#[tauri::command]
+pub fn get_simd_capabilities() -> SimdCapabilities {
+    SimdCapabilities::detect()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProbeCudaDto {
     pub cuda_build: bool,
@@ -82,3 +94,28 @@ pub fn probe_cuda() -> Result<ProbeCudaDto, String> {
         })
     }
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemInfo {
+    pub cuda_version: Option<String>,
+    pub driver_version: Option<String>,
+    pub gpu_name: Option<String>,
+    pub total_vram_mb: Option<u64>,
+    pub intel_gpu: Option<IntelGpuInfo>,
+}
+
+#[tauri::command]
+pub async fn get_system_info() -> Result<SystemInfo, String> {
+    // nvidia-smi может отвечать заметное время, не блокируем async-рантайм
+    let (gpu, intel_gpu) =
+        tauri::async_runtime::spawn_blocking(|| (query_nvidia_gpu(), detect_intel_gpu()))
+            .await
+            .map_err(|e| format!("Failed to query GPU info: {e}"))?;
+    Ok(SystemInfo {
+        cuda_version: gpu.as_ref().and_then(|g| g.cuda_version.clone()),
+        driver_version: gpu.as_ref().map(|g| g.driver_version.clone()),
+        gpu_name: gpu.as_ref().map(|g| g.name.clone()),
+        total_vram_mb: gpu.map(|g| g.total_vram_mb),
+        intel_gpu,
+    })
+}
@@ -0,0 +1,65 @@
+use crate::core::diagnostics::{self, DiagnosticReport};
+use crate::core::state::SharedState;
+use std::path::PathBuf;
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+/// Собирает диагностический отчёт и сохраняет его в `profile_dir/diagnostics/`.
+#[tauri::command]
+pub async fn generate_diagnostic_report(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SharedState>,
+    include_model_list: bool,
+    include_settings: bool,
+) -> Result<DiagnosticReport, String> {
+    let (active_sessions, monitor) = {
+        let guard = state.lock().map_err(|e| e.to_string())?;
+        (
+            diagnostics::active_sessions(&guard),
+            guard.performance_monitor.clone(),
+        )
+    };
+    let regressions = monitor.get_regressions().await;
+    // nvidia-smi может отвечать заметное время, не блокируем async-рантайм
+    tauri::async_runtime::spawn_blocking(move || {
+        diagnostics::generate_report(
+            &app,
+            active_sessions,
+            regressions,
+            include_model_list,
+            include_settings,
+        )
+    })
+    .await
+    .map_err(|e| format!("Failed to generate diagnostic report: {e}"))?
+}
+
+/// Копирует отчёт в буфер обмена. Без `path` берётся самый свежий отчёт.
+#[tauri::command]
+pub fn copy_diagnostic_to_clipboard(
+    app: tauri::AppHandle,
+    path: Option<String>,
+) -> Result<PathBuf, String> {
+    let dir = diagnostics::diagnostics_dir(&app)?;
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => diagnostics::latest_report(&dir)
+            .ok_or_else(|| "No diagnostic reports found".to_string())?,
+    };
+    // Копируем только отчёты из каталога диагностики
+    let canonical = path
+        .canonicalize()
+        .map_err(|e| format!("Diagnostic report not found: {e}"))?;
+    let dir = dir
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve diagnostics directory: {e}"))?;
+    if !canonical.starts_with(&dir) {
+        return Err("Path is outside the diagnostics directory".to_string());
+    }
+
+    let report = std::fs::read_to_string(&canonical)
+        .map_err(|e| format!("Failed to read diagnostic report: {e}"))?;
+    app.clipboard()
+        .write_text(report)
+        .map_err(|e| format!("Failed to copy diagnostic report: {e}"))?;
+    Ok(canonical)
+}
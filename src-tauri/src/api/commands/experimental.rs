@@ -37,6 +37,7 @@ pub fn set_experimental_features_enabled(
     let profile_dir = dir.join("oxide-lab");
     create_dir_all(&profile_dir).map_err(|e| format!("Failed to create profile directory: {e}"))?;
     let path = profile_dir.join("experimental_features.json");
+    crate::core::settings_watcher::note_internal_write(&path);
 
     let mut file = File::create(&path)
         .map_err(|e| format!("Failed to create experimental features file: {e}"))?;
@@ -44,5 +45,10 @@ pub fn set_experimental_features_enabled(
         .map_err(|e| format!("Failed to serialize experimental features: {e}"))?;
     file.write_all(data.as_bytes())
         .map_err(|e| format!("Failed to write experimental features file: {e}"))?;
+    crate::core::audit_log::record(
+        &app,
+        "settings_changed",
+        serde_json::json!({ "setting": "experimental_features", "enabled": enabled }),
+    );
     Ok(())
 }
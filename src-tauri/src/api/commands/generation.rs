@@ -1,7 +1,10 @@
+use crate::core::prompt::is_coder_model;
 use crate::core::state::SharedState;
-use crate::core::types::GenerateRequest;
+use crate::core::types::{FimConfig, GenerateRequest};
 use crate::generate;
+use crate::generate::emit::{EmissionBackend, GenerationEvent};
 use crate::log_template;
+use std::sync::{Arc, Mutex};
 
 #[tauri::command]
 pub async fn generate_stream(
@@ -22,3 +25,102 @@ pub async fn generate_stream(
 pub fn cancel_generation() -> Result<(), String> {
     generate::cancel_generation_cmd()
 }
+
+/// Проверить JSON Schema для structured output до запуска генерации
+#[tauri::command]
+pub fn validate_output_schema(schema: serde_json::Value) -> Result<(), String> {
+    generate::grammar::validate_schema_definition(&schema)
+}
+
+/// Собирает текст ответа вместо отправки событий во frontend
+struct CollectTextBackend(Arc<Mutex<String>>);
+
+impl EmissionBackend for CollectTextBackend {
+    fn emit(&self, event: GenerationEvent) {
+        let text = match event {
+            GenerationEvent::Token(t) => t,
+            GenerationEvent::Message(msg) => msg.content,
+            _ => return,
+        };
+        if let Ok(mut out) = self.0.lock() {
+            out.push_str(&text);
+        }
+    }
+}
+
+/// Дополнить код между `prefix` и `suffix` (FIM) загруженной Coder-моделью
+#[tauri::command]
+pub async fn code_completion(
+    state: tauri::State<'_, SharedState>,
+    model_id: String,
+    prefix: String,
+    suffix: String,
+    max_tokens: u32,
+) -> Result<String, String> {
+    let shared = state.inner().clone();
+    {
+        let guard = shared.lock().map_err(|e| e.to_string())?;
+        if !guard.scheduler.has_model() {
+            return Err("Model is not loaded".to_string());
+        }
+        let loaded = guard
+            .model_path
+            .as_deref()
+            .or(guard.hub_repo_id.as_deref())
+            .unwrap_or_default();
+        if !loaded.to_lowercase().contains(&model_id.to_lowercase()) {
+            return Err(format!(
+                "Model {} is not loaded (current: {})",
+                model_id, loaded
+            ));
+        }
+        let has_fim_tokens = guard
+            .tokenizer
+            .as_ref()
+            .is_some_and(|t| t.token_to_id("<|fim_prefix|>").is_some());
+        if !is_coder_model(loaded) || !has_fim_tokens {
+            return Err(format!(
+                "Code completion requires a Coder model, got {}",
+                loaded
+            ));
+        }
+    }
+
+    let req = GenerateRequest {
+        prompt: String::new(),
+        messages: None,
+        temperature: None,
+        top_p: None,
+        max_new_tokens: Some(max_tokens as usize),
+        tools: None,
+        top_k: None,
+        min_p: None,
+        repeat_penalty: None,
+        repeat_last_n: 64,
+        seed: None,
+        use_custom_params: false,
+        tracing: None,
+        verbose_prompt: None,
+        split_prompt: None,
+        attachments: None,
+        edit_index: None,
+        format: None,
+        stop_sequences: None,
+        tool_choice: None,
+        disable_template_substitution: true,
+        suppress_thinking: false,
+        thinking_budget_tokens: None,
+        fim_mode: Some(FimConfig { prefix, suffix }),
+    };
+
+    let output = Arc::new(Mutex::new(String::new()));
+    let backend = Box::new(CollectTextBackend(output.clone()));
+    tauri::async_runtime::spawn_blocking(move || {
+        generate::stream::generate_stream_with_backend(shared, req, backend)
+    })
+    .await
+    .map_err(|e| format!("Code completion task failed: {}", e))??;
+
+    let text = output.lock().map_err(|e| e.to_string())?.clone();
+    Ok(text)
+}
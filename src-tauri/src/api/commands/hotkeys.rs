@@ -0,0 +1,42 @@
+use crate::core::hotkeys::{
+    self, GlobalHotkeyBinding, HotkeyAction, HotkeyRegistration, parse_hotkey,
+};
+use tauri_plugin_global_shortcut::GlobalShortcutExt;
+
+#[tauri::command]
+pub fn list_hotkeys(app: tauri::AppHandle) -> Result<Vec<GlobalHotkeyBinding>, String> {
+    hotkeys::load_bindings(&app)
+}
+
+/// Сохраняет привязку и регистрирует её. Занятое другим приложением
+/// сочетание возвращается с предупреждением, а не ошибкой.
+#[tauri::command]
+pub fn register_hotkey(
+    app: tauri::AppHandle,
+    hotkey: String,
+    action: HotkeyAction,
+) -> Result<HotkeyRegistration, String> {
+    let shortcut = parse_hotkey(&hotkey)?;
+    let mut bindings = hotkeys::load_bindings(&app)?;
+    bindings.retain(|b| !parse_hotkey(&b.hotkey).is_ok_and(|s| s.id() == shortcut.id()));
+    let binding = GlobalHotkeyBinding { hotkey, action };
+    bindings.push(binding.clone());
+    hotkeys::save_bindings(&app, &bindings)?;
+    hotkeys::register_binding(&app, binding)
+}
+
+#[tauri::command]
+pub fn unregister_hotkey(app: tauri::AppHandle, hotkey: String) -> Result<(), String> {
+    let shortcut = parse_hotkey(&hotkey)?;
+    let mut bindings = hotkeys::load_bindings(&app)?;
+    bindings.retain(|b| !parse_hotkey(&b.hotkey).is_ok_and(|s| s.id() == shortcut.id()));
+    hotkeys::save_bindings(&app, &bindings)?;
+
+    let global_shortcut = app.global_shortcut();
+    if global_shortcut.is_registered(shortcut) {
+        global_shortcut
+            .unregister(shortcut)
+            .map_err(|e| format!("Failed to unregister hotkey {hotkey}: {e}"))?;
+    }
+    Ok(())
+}
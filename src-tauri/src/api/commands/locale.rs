@@ -3,7 +3,9 @@
  *
  * Команды для управления локалью из frontend.
  */
+use crate::core::state::ModelState;
 use crate::i18n::{self, Locale};
+use tauri::AppHandle;
 
 /// Получить текущую локаль
 #[tauri::command]
@@ -11,10 +13,25 @@ pub fn get_locale() -> String {
     i18n::get_locale().as_str().to_string()
 }
 
-/// Установить локаль
+/// Установить локаль и сохранить выбор в профиле
 #[tauri::command]
-pub fn set_locale(locale: String) -> Result<(), String> {
-    let locale_enum: Locale = locale.parse().map_err(|_| "Invalid locale".to_string())?;
+pub fn set_locale(app: AppHandle, locale: String) -> Result<(), String> {
+    let locale_enum: Locale = locale.parse().map_err(|_| {
+        let supported: Vec<&str> = Locale::SUPPORTED.iter().map(Locale::as_str).collect();
+        format!(
+            "Invalid locale: {locale} (supported: {})",
+            supported.join(", ")
+        )
+    })?;
     i18n::set_locale(locale_enum);
-    Ok(())
+    ModelState::save_locale(&app, locale_enum.as_str())
+}
+
+/// Определить системную локаль; `en`, если для неё нет перевода
+#[tauri::command]
+pub fn detect_system_locale() -> String {
+    i18n::detect_system_locale()
+        .unwrap_or_default()
+        .as_str()
+        .to_string()
 }
@@ -0,0 +1,38 @@
+//! Команды логирования: уровни компонентов, последние записи и файлы лога.
+
+use crate::core::log::{self, LogEntry};
+use crate::core::state::ModelState;
+use tauri::AppHandle;
+use tauri_plugin_opener::OpenerExt;
+
+/// Изменить уровень логирования компонента без перезапуска.
+/// Уровень `default` возвращает компонент к общему фильтру.
+#[tauri::command]
+pub fn set_log_level(app: AppHandle, component: String, level: String) -> Result<(), String> {
+    log::set_component_level(&component, &level)?;
+    ModelState::save_log_levels(&app, &log::component_levels())
+}
+
+/// Последние записи лога, от старых к новым.
+#[tauri::command]
+pub fn get_recent_logs(component: Option<String>, limit: u32) -> Vec<LogEntry> {
+    log::recent_entries(component.as_deref(), limit as usize)
+}
+
+/// Путь к текущему файлу лога
+#[tauri::command]
+pub fn get_log_file_path() -> Result<String, String> {
+    log::log_file_path()
+        .map(|path| path.to_string_lossy().to_string())
+        .ok_or_else(|| "Log file is not configured".to_string())
+}
+
+/// Открыть каталог логов в файловом менеджере
+#[tauri::command]
+pub fn open_log_folder(app: AppHandle) -> Result<(), String> {
+    let dir = ModelState::ensure_profile_dir(&app)?.join(log::LOGS_DIR);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create logs directory: {}", e))?;
+    app.opener()
+        .open_path(dir.to_string_lossy().to_string(), None::<&str>)
+        .map_err(|e| format!("Failed to open logs folder: {}", e))
+}
@@ -1,23 +1,37 @@
+pub mod audit;
+pub mod backends;
 pub mod device;
+pub mod diagnostics;
 pub mod experimental;
 pub mod general;
 pub mod generation;
+pub mod hotkeys;
 pub mod locale;
+pub mod logs;
 pub mod metadata;
 pub mod model;
 pub mod precision;
 pub mod prompts;
+pub mod settings_bundle;
 pub mod stt;
+pub mod system_prompts;
 pub mod threads;
 
+pub use audit::*;
+pub use backends::*;
 pub use device::*;
+pub use diagnostics::*;
 pub use experimental::*;
 pub use general::*;
 pub use generation::*;
+pub use hotkeys::*;
 pub use locale::*;
+pub use logs::*;
 pub use metadata::*;
 pub use model::*;
 pub use precision::*;
 pub use prompts::*;
+pub use settings_bundle::*;
 pub use stt::*;
+pub use system_prompts::*;
 pub use threads::*;
@@ -1,4 +1,8 @@
+use crate::api::model_loading::retry::{RetryPolicy, describe_history};
 use crate::core::background_mode::BackgroundModeGuard;
+use crate::core::session_store::{
+    PersistedSession, RecoveredSessions, SessionPersistenceStore, SessionRecoveredEvent,
+};
 use crate::core::state::{ModelState, SharedState};
 use crate::core::types::LoadRequest;
 use crate::generate::cancel::{CANCEL_LOADING, cancel_model_loading_cmd};
@@ -6,6 +10,7 @@ use crate::log_load;
 use crate::log_load_warn;
 
 use std::sync::Arc;
+use tauri::Emitter;
 
 pub fn clone_state_arc(state: &tauri::State<'_, SharedState>) -> SharedState {
     state.inner().clone()
@@ -27,6 +32,110 @@ fn snapshot_for_loading(
     )
 }
 
+/// Записать сведения о загруженной модели для восстановления после сбоя.
+fn persist_session(app: &tauri::AppHandle, state: &ModelState, req: LoadRequest) {
+    let Some(model_id) = state.scheduler.get_model_id() else {
+        return;
+    };
+    let result = SessionPersistenceStore::new(app).and_then(|store| {
+        // В памяти одновременно держится одна модель
+        store.clear()?;
+        store.save(&PersistedSession::new(model_id, req))
+    });
+    if let Err(e) = result {
+        log_load_warn!("failed to persist session: {}", e);
+    }
+}
+
+/// Удалить сохранённые сессии после штатной выгрузки модели.
+pub(crate) fn clear_persisted_sessions(app: &tauri::AppHandle) {
+    if let Err(e) = SessionPersistenceStore::new(app).and_then(|store| store.clear()) {
+        log_load_warn!("failed to clear persisted sessions: {}", e);
+    }
+}
+
+/// Загрузка модели по запросу в подготовленное состояние.
+fn load_into_state(
+    app: &tauri::AppHandle,
+    state: &mut ModelState,
+    req: LoadRequest,
+) -> Result<(), String> {
+    match req {
+        LoadRequest::Gguf {
+            model_path,
+            tokenizer_path: _,
+            context_length,
+            device,
+        } => crate::api::model_loading::gguf::load_gguf_model(
+            app,
+            state,
+            model_path,
+            context_length,
+            device,
+        ),
+        LoadRequest::HubGguf {
+            repo_id,
+            revision,
+            filename,
+            context_length,
+            device,
+        } => crate::api::model_loading::hub_gguf::load_hub_gguf_model(
+            app,
+            state,
+            repo_id,
+            revision,
+            filename,
+            context_length,
+            device,
+        ),
+        LoadRequest::HubSafetensors {
+            repo_id,
+            revision,
+            context_length,
+            device,
+        } => crate::api::model_loading::safetensors::load_hub_safetensors_model(
+            app,
+            state,
+            repo_id,
+            revision,
+            context_length,
+            device,
+        ),
+        LoadRequest::LocalSafetensors {
+            model_path,
+            context_length,
+            device,
+        } => crate::api::model_loading::safetensors::load_local_safetensors_model(
+            app,
+            state,
+            model_path,
+            context_length,
+            device,
+        ),
+    }
+}
+
+/// Сессии, восстановленные после аварийного завершения (отдаются один раз).
+/// При старте окна ещё нет, поэтому `session_recovered` эмитится здесь,
+/// когда frontend уже подписан на события.
+#[tauri::command]
+pub fn take_recovered_sessions(
+    app: tauri::AppHandle,
+    recovered: tauri::State<'_, RecoveredSessions>,
+) -> Vec<PersistedSession> {
+    let sessions = recovered.take();
+    for session in &sessions {
+        let _ = app.emit(
+            "session_recovered",
+            SessionRecoveredEvent {
+                model_id: session.model_id.clone(),
+                request: session.request.clone(),
+            },
+        );
+    }
+    sessions
+}
+
 #[tauri::command]
 pub async fn load_model(
     app: tauri::AppHandle,
@@ -73,87 +182,82 @@ pub async fn load_model(
                 snapshot_for_loading(&guard)
             };
 
-            let mut next_state = ModelState::new(device);
-            next_state.precision_policy = precision_policy;
-            next_state.rayon_thread_limit = rayon_thread_limit;
-            next_state.performance_monitor = performance_monitor;
-
-            let res: Result<(), String> = match req {
-                LoadRequest::Gguf {
-                    model_path,
-                    tokenizer_path: _,
-                    context_length,
-                    device,
-                } => crate::api::model_loading::gguf::load_gguf_model(
-                    &app_for_blocking,
-                    &mut next_state,
-                    model_path,
-                    context_length,
-                    device,
-                ),
-                LoadRequest::HubGguf {
-                    repo_id,
-                    revision,
-                    filename,
-                    context_length,
-                    device,
-                } => crate::api::model_loading::hub_gguf::load_hub_gguf_model(
-                    &app_for_blocking,
-                    &mut next_state,
-                    repo_id,
-                    revision,
-                    filename,
-                    context_length,
-                    device,
-                ),
-                LoadRequest::HubSafetensors {
-                    repo_id,
-                    revision,
-                    context_length,
-                    device,
-                } => crate::api::model_loading::safetensors::load_hub_safetensors_model(
-                    &app_for_blocking,
-                    &mut next_state,
-                    repo_id,
-                    revision,
-                    context_length,
-                    device,
-                ),
-                LoadRequest::LocalSafetensors {
-                    model_path,
-                    context_length,
-                    device,
-                } => crate::api::model_loading::safetensors::load_local_safetensors_model(
-                    &app_for_blocking,
-                    &mut next_state,
-                    model_path,
-                    context_length,
-                    device,
-                ),
-            };
+            let persisted_req = req.clone();
+            // Временные ошибки (файл занят антивирусом, таймаут чтения) повторяем с паузой
+            let policy = RetryPolicy::default();
+            let res = policy.run(
+                |attempt| {
+                    if attempt > 1 {
+                        log_load!("retrying model load, attempt {}", attempt);
+                    }
+                    let mut next_state = ModelState::new(device.clone());
+                    next_state.precision_policy = precision_policy.clone();
+                    next_state.rayon_thread_limit = rayon_thread_limit;
+                    next_state.performance_monitor = performance_monitor.clone();
+                    load_into_state(&app_for_blocking, &mut next_state, req.clone())
+                        .map(|()| next_state)
+                },
+                |retry| {
+                    log_load_warn!(
+                        "model load attempt {} failed: {}",
+                        retry.attempt,
+                        retry.error
+                    );
+                    crate::api::model_loading::emit_load_progress(
+                        &app_for_blocking,
+                        "retry",
+                        0,
+                        Some(&format!(
+                            "Attempt {} failed, retrying in {} ms: {}",
+                            retry.attempt,
+                            retry.delay_ms.unwrap_or_default(),
+                            retry.error
+                        )),
+                        false,
+                        None,
+                    );
+                },
+                || CANCEL_LOADING.load(std::sync::atomic::Ordering::SeqCst),
+            );
 
-            if res.is_ok() {
-                match state_arc.lock() {
-                    Ok(mut guard) => {
-                        *guard = next_state;
+            match res {
+                Ok(next_state) => {
+                    persist_session(&app_for_blocking, &next_state, persisted_req.clone());
+                    let model_id = next_state.scheduler.get_model_id();
+                    // После перезагрузки скорость может измениться законно (другие параметры)
+                    if let Some(model_id) = &model_id {
+                        tauri::async_runtime::block_on(
+                            performance_monitor.clear_speed_baseline(Some(model_id)),
+                        );
                     }
-                    Err(e) => {
-                        log_load_warn!("failed to commit loaded model state: {}", e);
+                    match state_arc.lock() {
+                        Ok(mut guard) => {
+                            *guard = next_state;
+                        }
+                        Err(e) => {
+                            log_load_warn!("failed to commit loaded model state: {}", e);
+                        }
                     }
+                    crate::core::audit_log::record(
+                        &app_for_blocking,
+                        "model_load",
+                        serde_json::json!({ "model_id": model_id, "request": persisted_req }),
+                    );
+                    Ok(())
+                }
+                Err((e, history)) => {
+                    let details = (history.len() > 1).then(|| describe_history(&history));
+                    crate::api::model_loading::emit_load_progress(
+                        &app_for_blocking,
+                        "error",
+                        0,
+                        details.as_deref(),
+                        true,
+                        Some(&e),
+                    );
+                    Err(e)
                 }
             }
-
-            if let Err(ref e) = res {
-                crate::api::model_loading::emit_load_progress(
-                    &app_for_blocking,
-                    "error",
-                    0,
-                    None,
-                    true,
-                    Some(e),
-                );
-            }
-            res
         })
         .await;
 
@@ -238,6 +342,8 @@ pub async fn unload_model(
             None,
         );
         log_load!("hard reset: freed model/tokenizer and reset state (preserved device)");
+        clear_persisted_sessions(&app_clone);
+        crate::core::audit_log::record(&app_clone, "model_unload", serde_json::json!({}));
         Ok(())
     })
     .await
@@ -0,0 +1,50 @@
+use crate::core::settings_bundle::{self, ExportResult, ImportResult};
+use std::path::PathBuf;
+
+/// Экспорт настроек профиля в ZIP-архив с контрольной суммой.
+#[tauri::command]
+pub async fn export_settings_bundle(
+    app: tauri::AppHandle,
+    dest_path: String,
+) -> Result<ExportResult, String> {
+    let handle = app.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        settings_bundle::export_bundle(&handle, &PathBuf::from(dest_path))
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    crate::core::audit_log::record(
+        &app,
+        "settings_exported",
+        serde_json::json!({ "path": result.path, "files": result.files }),
+    );
+    Ok(result)
+}
+
+/// Импорт настроек из архива. Без `overwrite` существующие настройки дополняются.
+#[tauri::command]
+pub async fn import_settings_bundle(
+    app: tauri::AppHandle,
+    zip_path: String,
+    overwrite: bool,
+) -> Result<ImportResult, String> {
+    let handle = app.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        settings_bundle::import_bundle(&handle, &PathBuf::from(zip_path), overwrite)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    crate::core::audit_log::record(
+        &app,
+        "settings_changed",
+        serde_json::json!({
+            "setting": "bundle_import",
+            "overwrite": overwrite,
+            "restored": result.restored,
+            "merged": result.merged,
+        }),
+    );
+    Ok(result)
+}
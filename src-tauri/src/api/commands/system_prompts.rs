@@ -0,0 +1,54 @@
+use crate::core::system_prompts::{NamedSystemPrompt, SystemPromptStore, SystemPromptUpdate};
+
+#[tauri::command]
+pub fn list_system_prompts(app: tauri::AppHandle) -> Result<Vec<NamedSystemPrompt>, String> {
+    Ok(SystemPromptStore::new(&app)?.load()?.prompts)
+}
+
+#[tauri::command]
+pub fn add_system_prompt(
+    app: tauri::AppHandle,
+    name: String,
+    content: String,
+    tags: Option<Vec<String>>,
+) -> Result<NamedSystemPrompt, String> {
+    if name.trim().is_empty() {
+        return Err("System prompt name cannot be empty".to_string());
+    }
+    let store = SystemPromptStore::new(&app)?;
+    let mut library = store.load()?;
+    let prompt = library.add(name, content, tags.unwrap_or_default());
+    store.save(&library)?;
+    Ok(prompt)
+}
+
+/// Обновляет промпт; предыдущая версия сохраняется в истории.
+#[tauri::command]
+pub fn update_system_prompt(
+    app: tauri::AppHandle,
+    id: String,
+    update: SystemPromptUpdate,
+) -> Result<NamedSystemPrompt, String> {
+    let store = SystemPromptStore::new(&app)?;
+    let mut library = store.load()?;
+    let (previous, updated) = library.update(&id, update)?;
+    store.append_history(previous)?;
+    store.save(&library)?;
+    Ok(updated)
+}
+
+#[tauri::command]
+pub fn delete_system_prompt(app: tauri::AppHandle, id: String) -> Result<(), String> {
+    let store = SystemPromptStore::new(&app)?;
+    let mut library = store.load()?;
+    library.remove(&id)?;
+    store.save(&library)
+}
+
+#[tauri::command]
+pub fn get_system_prompt_history(
+    app: tauri::AppHandle,
+    id: String,
+) -> Result<Vec<NamedSystemPrompt>, String> {
+    SystemPromptStore::new(&app)?.history(&id)
+}
@@ -1,7 +1,10 @@
+use crate::core::rayon_pool::{self, rebuild_inference_pool};
 use crate::core::state::{ModelState, SharedState};
+use crate::core::thread_priority::ThreadPriority;
 
+use serde::Serialize;
 use std::env;
-use tauri::AppHandle;
+use tauri::{AppHandle, Emitter};
 
 const RAYON_ENV_VAR: &str = "RAYON_NUM_THREADS";
 
@@ -37,3 +40,49 @@ pub fn set_rayon_thread_limit(
     guard.rayon_thread_limit = limit;
     ModelState::save_thread_limit(&app, limit).map_err(|e| e.to_string())
 }
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ThreadPoolReconfigured {
+    pub old_count: usize,
+    pub new_count: usize,
+}
+
+/// Пересоздать пул потоков инференса без перезапуска приложения.
+/// `None` — все ядра, кроме одного (оставляем его UI). Сохранённый ручной лимит
+/// потоков Rayon ограничивает итоговое значение сверху.
+#[tauri::command]
+pub fn configure_inference_thread_pool(
+    app: AppHandle,
+    thread_count: Option<usize>,
+) -> Result<usize, String> {
+    let requested = thread_count
+        .unwrap_or_else(default_rayon_thread_limit)
+        .max(1);
+    let new_count = match ModelState::load_thread_limit(&app)? {
+        Some(limit) => requested.min(limit.max(1)),
+        None => requested,
+    };
+
+    let old_count = rebuild_inference_pool(new_count)?;
+    let _ = app.emit(
+        "thread_pool_reconfigured",
+        ThreadPoolReconfigured {
+            old_count,
+            new_count,
+        },
+    );
+    Ok(new_count)
+}
+
+/// Приоритет потоков инференса: `"low"`, `"normal"` или `"high"`.
+#[tauri::command]
+pub fn get_inference_thread_priority(app: AppHandle) -> Result<String, String> {
+    ModelState::load_inference_priority(&app).map(|p| p.as_str().to_string())
+}
+
+#[tauri::command]
+pub fn set_inference_thread_priority(app: AppHandle, priority: String) -> Result<(), String> {
+    let priority = ThreadPriority::parse(&priority)?;
+    rayon_pool::set_inference_thread_priority(priority)?;
+    ModelState::save_inference_priority(&app, priority)
+}
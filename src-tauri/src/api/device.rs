@@ -8,12 +8,31 @@ use crate::core::tokenizer::{
 use crate::models::registry::detect_arch;
 use crate::models::registry::detect_arch_from_config;
 use crate::models::registry::get_model_factory;
+use crate::models::registry::remap_custom_gguf_metadata;
+use crate::models::{OptimizationConfig, SimdCapabilities};
 use crate::{log_device, log_device_error, log_load};
 use candle::quantized::gguf_file;
 use candle::utils::{cuda_is_available, metal_is_available};
 use std::fs::File;
 use std::path::PathBuf;
 
+/// Записывает в лог SIMD-расширения CPU и те из них, которые сборка не использует.
+pub fn log_simd_capabilities() {
+    let detected = SimdCapabilities::detect();
+    log_device!(
+        "simd: {} (best path: {})",
+        detected.description(),
+        detected.best_path()
+    );
+    let unused = OptimizationConfig::simd_info().unused_by_build(&detected);
+    if !unused.is_empty() {
+        log_device!(
+            "CPU supports {} but this build does not use it",
+            unused.join(", ")
+        );
+    }
+}
+
 pub fn set_device(
     guard: &mut ModelState,
     pref: crate::core::types::DevicePreference,
@@ -78,12 +97,16 @@ pub fn set_device(
         let kcfg = crate::core::precision::GpuKernelConfig::default();
         kcfg.apply_for_device(&guard.device);
     }
+    let cpu = crate::core::device::detect_cpu_features();
     log_device!(
-        "hw caps: avx={}, neon={}, simd128={}, f16c={}",
+        "hw caps: avx={}, neon={}, simd128={}, f16c={}, arm64={}, sve={}, apple_silicon={}",
         candle::utils::with_avx(),
         candle::utils::with_neon(),
         candle::utils::with_simd128(),
-        candle::utils::with_f16c()
+        candle::utils::with_f16c(),
+        cpu.is_arm64,
+        cpu.has_sve,
+        cpu.is_apple_silicon
     );
     // Если модель загружена — перезагрузим её под выбранное устройство
     // TODO: Поддержка перезагрузки safetensors моделей
@@ -98,7 +121,7 @@ pub fn set_device(
         if model_path.ends_with(".gguf") {
             let ctx_len = guard.context_length.max(1);
             let mut file = File::open(&model_path).map_err(|e| e.to_string())?;
-            let content = gguf_file::Content::read(&mut file)
+            let mut content = gguf_file::Content::read(&mut file)
                 .map_err(|e| format!("{}", e.with_path(PathBuf::from(model_path.clone()))))?;
 
             // Токенизатор и шаблон чата
@@ -110,6 +133,7 @@ pub fn set_device(
             // Архитектура
             let arch = detect_arch(&content.metadata)
                 .ok_or_else(|| "Unsupported GGUF architecture".to_string())?;
+            remap_custom_gguf_metadata(&mut content.metadata, arch);
 
             // Универсальное создание модели через фабрику (под выбранное устройство)
             let model_backend = get_model_factory()
@@ -2,15 +2,18 @@
 //! The manager exposes a set of Tauri commands consumed by the Svelte frontend.
 
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet, VecDeque},
     fs,
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{
+        Arc,
+        atomic::{AtomicU32, Ordering},
+    },
     time::{Duration, Instant},
 };
 
 use chrono::{DateTime, Utc};
-use futures_util::StreamExt;
+use futures_util::{StreamExt, future::BoxFuture};
 use once_cell::sync::Lazy;
 use reqwest::header::{HeaderMap, HeaderValue, RANGE};
 use serde::{Deserialize, Serialize};
@@ -18,15 +21,33 @@ use tauri::{AppHandle, Emitter, Manager};
 use tokio::{
     fs::OpenOptions,
     io::{AsyncSeekExt, AsyncWriteExt},
-    sync::{RwLock, mpsc},
+    sync::{Mutex, RwLock, mpsc},
     task::JoinHandle,
 };
 
 use super::local_models::build_http_client;
+use crate::core::settings_watcher;
+use crate::core::state::ModelState;
+use crate::core::types::DownloadSettings;
 
 /// Event sent to the frontend whenever the downloads state changes.
 pub const DOWNLOAD_EVENT: &str = "download-manager-updated";
 
+/// Event sent after stale partial downloads were removed on startup.
+pub const DOWNLOAD_CLEANUP_EVENT: &str = "download_cleanup";
+
+/// History is deduplicated on load once it grows past this many entries.
+const HISTORY_AUTO_DEDUP_THRESHOLD: usize = 1000;
+
+/// Partial files untouched for longer than this are treated as orphaned.
+const STALE_PARTIAL_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Payload of [`DOWNLOAD_CLEANUP_EVENT`].
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadCleanupPayload {
+    pub count: u32,
+}
+
 /// Describes the status of a download job.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -39,6 +60,16 @@ pub enum DownloadStatus {
     Cancelled,
 }
 
+/// Order in which queued downloads are started.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum DownloadPriority {
+    High,
+    #[default]
+    Normal,
+    Low,
+}
+
 /// Immutable information for a download job exposed to the UI.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DownloadJob {
@@ -61,6 +92,11 @@ pub struct DownloadJob {
     pub group_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub display_name: Option<String>,
+    /// Коммит репозитория (`x-repo-commit`), с которого начата загрузка
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit_sha: Option<String>,
+    #[serde(default)]
+    pub priority: DownloadPriority,
 }
 
 /// Download job persisted to history once finished.
@@ -90,6 +126,54 @@ pub struct DownloadManagerState {
     history_loaded: bool,
 }
 
+/// Which history entries survive `deduplicate_history` for one `(repo_id, filename)`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryKeepPolicy {
+    /// Only the most recently finished entry
+    KeepLatest,
+    /// Drop failed and cancelled attempts once the file was downloaded successfully
+    KeepSuccessful,
+    KeepAll,
+}
+
+/// Removes duplicate history entries according to `keep`; returns how many were removed.
+fn deduplicate_entries(history: &mut Vec<DownloadHistoryEntry>, keep: HistoryKeepPolicy) -> u32 {
+    let before = history.len();
+    match keep {
+        HistoryKeepPolicy::KeepAll => {}
+        HistoryKeepPolicy::KeepLatest => {
+            let mut latest: HashMap<(String, String), DateTime<Utc>> = HashMap::new();
+            for entry in history.iter() {
+                let finished = latest
+                    .entry((entry.repo_id.clone(), entry.filename.clone()))
+                    .or_insert(entry.finished_at);
+                *finished = (*finished).max(entry.finished_at);
+            }
+            let mut kept = HashSet::new();
+            // При равном времени оставляем первую запись
+            history.retain(|entry| {
+                let key = (entry.repo_id.clone(), entry.filename.clone());
+                latest.get(&key) == Some(&entry.finished_at) && kept.insert(key)
+            });
+        }
+        HistoryKeepPolicy::KeepSuccessful => {
+            let completed: HashSet<(String, String)> = history
+                .iter()
+                .filter(|entry| entry.status == DownloadStatus::Completed)
+                .map(|entry| (entry.repo_id.clone(), entry.filename.clone()))
+                .collect();
+            history.retain(|entry| {
+                !matches!(
+                    entry.status,
+                    DownloadStatus::Error | DownloadStatus::Cancelled
+                ) || !completed.contains(&(entry.repo_id.clone(), entry.filename.clone()))
+            });
+        }
+    }
+    (before - history.len()) as u32
+}
+
 /// Control message sent to a running download task.
 enum DownloadControl {
     Pause,
@@ -113,14 +197,57 @@ impl DownloadTaskChannels {
     }
 }
 
+/// Jobs waiting for a free download slot: by priority, FIFO within one priority.
+#[derive(Debug, Default)]
+pub struct DownloadQueue {
+    jobs: VecDeque<DownloadJob>,
+}
+
+impl DownloadQueue {
+    pub fn push(&mut self, mut job: DownloadJob, priority: DownloadPriority) {
+        self.remove(&job.id);
+        job.priority = priority;
+        let index = self
+            .jobs
+            .iter()
+            .position(|queued| queued.priority > priority)
+            .unwrap_or(self.jobs.len());
+        self.jobs.insert(index, job);
+    }
+
+    pub fn pop(&mut self) -> Option<DownloadJob> {
+        self.jobs.pop_front()
+    }
+
+    pub fn remove(&mut self, job_id: &str) -> Option<DownloadJob> {
+        let index = self.jobs.iter().position(|job| job.id == job_id)?;
+        self.jobs.remove(index)
+    }
+
+    /// Moves a queued job according to its new priority. Returns false if it is not queued.
+    pub fn set_priority(&mut self, job_id: &str, priority: DownloadPriority) -> bool {
+        match self.remove(job_id) {
+            Some(job) => {
+                self.push(job, priority);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
 struct DownloadManager {
     state: RwLock<DownloadManagerState>,
     tasks: RwLock<HashMap<String, DownloadTaskHandle>>,
+    queue: Mutex<DownloadQueue>,
+    max_concurrent: AtomicU32,
 }
 
 static MANAGER: Lazy<DownloadManager> = Lazy::new(|| DownloadManager {
     state: RwLock::new(DownloadManagerState::default()),
     tasks: RwLock::new(HashMap::new()),
+    queue: Mutex::new(DownloadQueue::default()),
+    max_concurrent: AtomicU32::new(DownloadSettings::default().max_concurrent_downloads),
 });
 
 #[derive(Debug)]
@@ -134,6 +261,7 @@ struct DownloadContext {
     sha256: Option<String>,
     group_id: Option<String>,
     display_name: Option<String>,
+    commit_sha: Option<String>,
 }
 
 #[derive(Debug)]
@@ -159,6 +287,8 @@ pub struct StartDownloadRequest {
     pub group_id: Option<String>,
     #[serde(default)]
     pub display_name: Option<String>,
+    #[serde(default)]
+    pub priority: DownloadPriority,
 }
 
 /// Snapshot emitted to the frontend.
@@ -181,19 +311,31 @@ impl DownloadManager {
     }
 
     async fn ensure_history_loaded(&self, app: &AppHandle) -> Result<(), String> {
-        let mut guard = self.state.write().await;
-        if guard.history_loaded {
-            return Ok(());
-        }
+        let removed = {
+            let mut guard = self.state.write().await;
+            if guard.history_loaded {
+                return Ok(());
+            }
 
-        let path = Self::history_path(app)?;
-        if path.exists() {
-            let data =
-                fs::read(&path).map_err(|e| format!("Failed to read download history: {e}"))?;
-            guard.history = serde_json::from_slice(&data)
-                .map_err(|e| format!("Failed to parse download history: {e}"))?;
+            let path = Self::history_path(app)?;
+            if path.exists() {
+                let data =
+                    fs::read(&path).map_err(|e| format!("Failed to read download history: {e}"))?;
+                guard.history = serde_json::from_slice(&data)
+                    .map_err(|e| format!("Failed to parse download history: {e}"))?;
+            }
+            guard.history_loaded = true;
+
+            if guard.history.len() > HISTORY_AUTO_DEDUP_THRESHOLD {
+                deduplicate_entries(&mut guard.history, HistoryKeepPolicy::KeepLatest)
+            } else {
+                0
+            }
+        };
+        if removed > 0 {
+            log::info!("Removed {removed} duplicate download history entries");
+            self.persist_history(app).await?;
         }
-        guard.history_loaded = true;
         Ok(())
     }
 
@@ -205,6 +347,7 @@ impl DownloadManager {
         let path = Self::history_path(app)?;
         let payload = serde_json::to_vec_pretty(&guard.history)
             .map_err(|e| format!("Failed to serialize download history: {e}"))?;
+        settings_watcher::note_internal_write(&path);
         fs::write(path, payload).map_err(|e| format!("Failed to write download history: {e}"))
     }
 
@@ -221,11 +364,6 @@ impl DownloadManager {
         let _ = app.emit(DOWNLOAD_EVENT, snapshot);
     }
 
-    async fn register_task(&self, job_id: String, handle: DownloadTaskHandle) {
-        let mut guard = self.tasks.write().await;
-        guard.insert(job_id, handle);
-    }
-
     async fn unregister_task(&self, job_id: &str) {
         let mut guard = self.tasks.write().await;
         guard.remove(job_id);
@@ -360,6 +498,7 @@ async fn run_download_loop(
         sha256,
         group_id,
         display_name,
+        commit_sha,
     } = ctx;
     let mut total_bytes = total_bytes;
 
@@ -422,6 +561,28 @@ async fn run_download_loop(
         ));
     }
 
+    // Докачка с другого коммита склеила бы части разных файлов
+    let remote_commit = response
+        .headers()
+        .get("x-repo-commit")
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    if let Err(err) = check_resume_commit(
+        commit_sha.as_deref(),
+        remote_commit.as_deref(),
+        downloaded_bytes,
+    ) {
+        return DownloadLoopOutcome::Error(err);
+    }
+    if remote_commit.is_some() && remote_commit != commit_sha {
+        let manager = &*MANAGER;
+        manager
+            .update_job(&job_id, |job| {
+                job.commit_sha = remote_commit.clone();
+            })
+            .await;
+    }
+
     if total_bytes.is_none() {
         let content_len = response
             .headers()
@@ -598,9 +759,25 @@ async fn init_job(request: &StartDownloadRequest, job_id: &str) -> Result<Downlo
         sha256: request.sha256.clone(),
         group_id: request.group_id.clone(),
         display_name: request.display_name.clone(),
+        commit_sha: None,
+        priority: request.priority,
     })
 }
 
+/// Проверяет, что докачиваемый файл не изменился в репозитории с начала загрузки.
+fn check_resume_commit(
+    stored: Option<&str>,
+    remote: Option<&str>,
+    downloaded_bytes: u64,
+) -> Result<(), String> {
+    match (stored, remote) {
+        (Some(stored), Some(remote)) if downloaded_bytes > 0 && stored != remote => Err(format!(
+            "Repository changed since the download started (commit {stored} -> {remote}); cancel and restart the download"
+        )),
+        _ => Ok(()),
+    }
+}
+
 async fn start_task(app: AppHandle, job: DownloadJob) -> Result<(), String> {
     let job_id = job.id.clone();
     let job_id_for_task = job_id.clone();
@@ -614,6 +791,7 @@ async fn start_task(app: AppHandle, job: DownloadJob) -> Result<(), String> {
         sha256: job.sha256.clone(),
         group_id: job.group_id.clone(),
         display_name: job.display_name.clone(),
+        commit_sha: job.commit_sha.clone(),
     };
 
     {
@@ -633,6 +811,9 @@ async fn start_task(app: AppHandle, job: DownloadJob) -> Result<(), String> {
     let DownloadTaskChannels { tx, rx } = DownloadTaskChannels::new();
     let app_clone = app.clone();
 
+    // Задача снимает себя с учёта по завершении, поэтому регистрируем её под
+    // блокировкой: иначе быстрая ошибка успела бы отработать до регистрации
+    let mut tasks = MANAGER.tasks.write().await;
     let handle = tokio::spawn(async move {
         let outcome = run_download_loop(app_clone.clone(), ctx, rx).await;
         match outcome {
@@ -723,21 +904,131 @@ async fn start_task(app: AppHandle, job: DownloadJob) -> Result<(), String> {
         }
 
         MANAGER.unregister_task(&job_id_for_task).await;
+        start_queued_downloads(app_clone).await;
     });
 
-    MANAGER
-        .register_task(
-            job_id.clone(),
-            DownloadTaskHandle {
-                control: tx,
-                join: handle,
-            },
-        )
-        .await;
+    tasks.insert(
+        job_id,
+        DownloadTaskHandle {
+            control: tx,
+            join: handle,
+        },
+    );
 
     Ok(())
 }
 
+/// Puts a job into the queue and starts queued jobs while there are free slots.
+async fn enqueue_download(app: &AppHandle, job: DownloadJob) {
+    let priority = job.priority;
+    MANAGER.queue.lock().await.push(job, priority);
+    start_queued_downloads(app.clone()).await;
+}
+
+/// Starts queued jobs until `max_concurrent_downloads` tasks are running.
+/// Boxed because it is also called from the tail of a download task spawned by `start_task`.
+fn start_queued_downloads(app: AppHandle) -> BoxFuture<'static, ()> {
+    Box::pin(async move {
+        // Очередь заблокирована до регистрации задачи, чтобы два вызова не заняли один слот
+        let mut queue = MANAGER.queue.lock().await;
+        let limit = MANAGER.max_concurrent.load(Ordering::SeqCst).max(1) as usize;
+        while MANAGER.tasks.read().await.len() < limit {
+            let Some(job) = queue.pop() else {
+                break;
+            };
+            // Задача могла быть отменена, пока стояла в очереди
+            let current = MANAGER.state.read().await.active.get(&job.id).cloned();
+            let Some(current) = current else {
+                continue;
+            };
+            if let Err(err) = start_task(app.clone(), current).await {
+                log::error!("Failed to start queued download {}: {err}", job.id);
+            }
+        }
+    })
+}
+
+/// Apply download settings to the running manager.
+pub async fn apply_download_settings(app: &AppHandle, settings: &DownloadSettings) {
+    MANAGER
+        .max_concurrent
+        .store(settings.max_concurrent_downloads.max(1), Ordering::SeqCst);
+    start_queued_downloads(app.clone()).await;
+}
+
+fn remove_stale_partials(
+    dirs: &HashSet<PathBuf>,
+    keep: &HashSet<PathBuf>,
+    max_age: Duration,
+) -> u32 {
+    let mut removed = 0;
+    for dir in dirs {
+        let Ok(entries) = fs::read_dir(dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("part") || keep.contains(&path)
+            {
+                continue;
+            }
+            let is_stale = entry
+                .metadata()
+                .and_then(|meta| meta.modified())
+                .ok()
+                .and_then(|modified| modified.elapsed().ok())
+                .is_some_and(|age| age > max_age);
+            if !is_stale {
+                continue;
+            }
+            match fs::remove_file(&path) {
+                Ok(()) => {
+                    log::info!("Removed stale partial download {}", path.display());
+                    removed += 1;
+                }
+                Err(err) => log::warn!("Failed to remove {}: {err}", path.display()),
+            }
+        }
+    }
+    removed
+}
+
+/// Remove `.part` files older than 24 hours from directories used by previous downloads.
+///
+/// Active jobs are not persisted across restarts, so such files can never be resumed.
+pub async fn cleanup_stale_partial_files(app: &AppHandle) -> Result<u32, String> {
+    MANAGER.ensure_history_loaded(app).await?;
+
+    let (dirs, keep) = {
+        let guard = MANAGER.state.read().await;
+        let dirs: HashSet<PathBuf> = guard
+            .history
+            .iter()
+            .filter_map(|entry| entry.destination_path.parent().map(Path::to_path_buf))
+            .chain(guard.active.values().map(|job| job.destination_dir.clone()))
+            .collect();
+        let keep: HashSet<PathBuf> = guard
+            .active
+            .values()
+            .map(|job| resolve_partial_path(&job.destination_dir, &job.filename))
+            .collect();
+        (dirs, keep)
+    };
+
+    let removed =
+        tokio::task::spawn_blocking(move || remove_stale_partials(&dirs, &keep, STALE_PARTIAL_AGE))
+            .await
+            .map_err(|e| format!("Failed to join partial cleanup task: {e}"))?;
+
+    if removed > 0 {
+        let _ = app.emit(
+            DOWNLOAD_CLEANUP_EVENT,
+            DownloadCleanupPayload { count: removed },
+        );
+    }
+    Ok(removed)
+}
+
 /// Start a download job and return the queued job information.
 #[tauri::command]
 pub async fn start_model_download(
@@ -775,7 +1066,7 @@ pub async fn start_model_download(
         manager.emit_update(&app).await;
     }
 
-    start_task(app.clone(), job.clone()).await?;
+    enqueue_download(&app, job.clone()).await;
 
     Ok(job)
 }
@@ -821,7 +1112,14 @@ pub async fn resume_download(app: AppHandle, job_id: String) -> Result<(), Strin
         return Err("Only paused or error downloads can be resumed".to_string());
     }
 
-    start_task(app, job).await?;
+    MANAGER
+        .update_job(&job_id, |job| {
+            job.status = DownloadStatus::Queued;
+            job.updated_at = Some(Utc::now());
+        })
+        .await;
+    MANAGER.emit_update(&app).await;
+    enqueue_download(&app, job).await;
     Ok(())
 }
 
@@ -829,6 +1127,7 @@ pub async fn resume_download(app: AppHandle, job_id: String) -> Result<(), Strin
 #[tauri::command]
 pub async fn cancel_download(app: AppHandle, job_id: String) -> Result<(), String> {
     MANAGER.ensure_history_loaded(&app).await?;
+    MANAGER.queue.lock().await.remove(&job_id);
     let mut cancelled_job = None;
     match MANAGER.get_task_control(&job_id).await {
         Some(control) => {
@@ -917,3 +1216,195 @@ pub async fn clear_download_history(app: AppHandle) -> Result<(), String> {
     MANAGER.emit_update(&app).await;
     Ok(())
 }
+
+/// Remove repeated history entries for the same file. Returns the number removed.
+#[tauri::command]
+pub async fn deduplicate_history(app: AppHandle, keep: HistoryKeepPolicy) -> Result<u32, String> {
+    MANAGER.ensure_history_loaded(&app).await?;
+    let removed = {
+        let mut guard = MANAGER.state.write().await;
+        deduplicate_entries(&mut guard.history, keep)
+    };
+    if removed > 0 {
+        MANAGER.persist_history(&app).await?;
+        MANAGER.emit_update(&app).await;
+    }
+    Ok(removed)
+}
+
+/// Change the start order of a queued download. Running downloads keep their slot.
+#[tauri::command]
+pub async fn set_download_priority(
+    app: AppHandle,
+    job_id: String,
+    priority: DownloadPriority,
+) -> Result<(), String> {
+    MANAGER
+        .update_job(&job_id, |job| job.priority = priority)
+        .await
+        .ok_or_else(|| "Download not found".to_string())?;
+    MANAGER.queue.lock().await.set_priority(&job_id, priority);
+    MANAGER.emit_update(&app).await;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_download_settings(app: AppHandle) -> Result<DownloadSettings, String> {
+    ModelState::load_download_settings(&app)
+}
+
+#[tauri::command]
+pub async fn set_download_settings(
+    app: AppHandle,
+    settings: DownloadSettings,
+) -> Result<(), String> {
+    if settings.max_concurrent_downloads == 0 {
+        return Err("max_concurrent_downloads must be at least 1".to_string());
+    }
+    ModelState::save_download_settings(&app, &settings)?;
+    apply_download_settings(&app, &settings).await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(id: &str) -> DownloadJob {
+        DownloadJob {
+            id: id.to_string(),
+            repo_id: "Qwen/Qwen3-4B-GGUF".to_string(),
+            filename: format!("{id}.gguf"),
+            download_url: String::new(),
+            destination_dir: PathBuf::new(),
+            total_bytes: None,
+            downloaded_bytes: 0,
+            status: DownloadStatus::Queued,
+            speed_bytes_per_sec: None,
+            eta_seconds: None,
+            started_at: None,
+            updated_at: None,
+            finished_at: None,
+            error: None,
+            sha256: None,
+            group_id: None,
+            display_name: None,
+            commit_sha: None,
+            priority: DownloadPriority::Normal,
+        }
+    }
+
+    fn drain(queue: &mut DownloadQueue) -> Vec<String> {
+        std::iter::from_fn(|| queue.pop())
+            .map(|job| job.id)
+            .collect()
+    }
+
+    #[test]
+    fn queue_orders_by_priority_then_arrival() {
+        let mut queue = DownloadQueue::default();
+        queue.push(job("a"), DownloadPriority::Normal);
+        queue.push(job("b"), DownloadPriority::Low);
+        queue.push(job("c"), DownloadPriority::High);
+        queue.push(job("d"), DownloadPriority::Normal);
+        assert_eq!(drain(&mut queue), ["c", "a", "d", "b"]);
+
+        queue.push(job("a"), DownloadPriority::Normal);
+        queue.push(job("b"), DownloadPriority::Normal);
+        assert!(queue.set_priority("b", DownloadPriority::High));
+        assert!(!queue.set_priority("missing", DownloadPriority::High));
+        assert_eq!(drain(&mut queue), ["b", "a"]);
+    }
+
+    fn history_entry(filename: &str, status: DownloadStatus, minute: u32) -> DownloadHistoryEntry {
+        DownloadHistoryEntry {
+            id: format!("{filename}-{minute}"),
+            repo_id: "Qwen/Qwen3-4B-GGUF".to_string(),
+            filename: filename.to_string(),
+            destination_path: PathBuf::from(filename),
+            status,
+            total_bytes: None,
+            downloaded_bytes: 0,
+            finished_at: DateTime::parse_from_rfc3339(&format!("2025-01-01T00:{minute:02}:00Z"))
+                .unwrap()
+                .with_timezone(&Utc),
+            error: None,
+            sha256: None,
+            group_id: None,
+            display_name: None,
+        }
+    }
+
+    #[test]
+    fn deduplicates_history_by_policy() {
+        let history = vec![
+            history_entry("a.gguf", DownloadStatus::Error, 1),
+            history_entry("a.gguf", DownloadStatus::Completed, 2),
+            history_entry("a.gguf", DownloadStatus::Cancelled, 3),
+            history_entry("b.gguf", DownloadStatus::Error, 4),
+        ];
+        let ids = |entries: &[DownloadHistoryEntry]| {
+            entries.iter().map(|e| e.id.clone()).collect::<Vec<_>>()
+        };
+
+        let mut latest = history.clone();
+        assert_eq!(
+            deduplicate_entries(&mut latest, HistoryKeepPolicy::KeepLatest),
+            2
+        );
+        assert_eq!(ids(&latest), ["a.gguf-3", "b.gguf-4"]);
+
+        let mut successful = history.clone();
+        assert_eq!(
+            deduplicate_entries(&mut successful, HistoryKeepPolicy::KeepSuccessful),
+            2
+        );
+        assert_eq!(ids(&successful), ["a.gguf-2", "b.gguf-4"]);
+
+        let mut all = history;
+        assert_eq!(deduplicate_entries(&mut all, HistoryKeepPolicy::KeepAll), 0);
+    }
+
+    #[test]
+    fn resume_is_refused_when_commit_changed() {
+        assert!(check_resume_commit(Some("abc"), Some("def"), 10).is_err());
+        assert!(check_resume_commit(Some("abc"), Some("def"), 0).is_ok());
+        assert!(check_resume_commit(Some("abc"), None, 10).is_ok());
+    }
+
+    #[test]
+    fn removes_only_stale_partials_outside_keep() {
+        let dir = std::env::temp_dir().join(format!("oxide-stale-parts-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let write_part = |name: &str, age: Duration| {
+            let path = dir.join(name);
+            fs::write(&path, b"partial").unwrap();
+            let modified = std::time::SystemTime::now() - age;
+            fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(modified)
+                .unwrap();
+            path
+        };
+        let two_days = Duration::from_secs(2 * 24 * 60 * 60);
+        let stale = write_part("stale.gguf.part", two_days);
+        let fresh = write_part("fresh.gguf.part", Duration::ZERO);
+        let active = write_part("active.gguf.part", two_days);
+        let finished = write_part("done.gguf", two_days);
+
+        let dirs = HashSet::from([dir.clone()]);
+        let keep = HashSet::from([active.clone()]);
+        let removed = remove_stale_partials(&dirs, &keep, Duration::from_secs(24 * 60 * 60));
+
+        assert_eq!(removed, 1);
+        assert!(!stale.exists());
+        assert!(fresh.exists());
+        assert!(active.exists());
+        assert!(finished.exists());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
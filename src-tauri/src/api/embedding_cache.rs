@@ -0,0 +1,185 @@
+//! Short-lived cache for `/v1/embeddings`.
+//!
+//! Clients often embed the same text several times in a row (e.g. the same
+//! user message in multiple pipeline calls). Results are keyed by the SHA-256
+//! of model, pooling and input, and expire after
+//! `LocalRagSettings::cache_ttl_secs`.
+
+use crate::core::state::ModelState;
+use crate::core::types::LocalRagSettings;
+use once_cell::sync::Lazy;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Upper bound on cached entries; the oldest are evicted first.
+const MAX_ENTRIES: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachedEmbedding {
+    pub embedding: Vec<f32>,
+    pub prompt_tokens: usize,
+}
+
+pub struct EmbeddingCache {
+    entries: Mutex<HashMap<String, (CachedEmbedding, Instant)>>,
+    ttl_ms: AtomicU64,
+}
+
+pub static EMBEDDING_CACHE: Lazy<EmbeddingCache> = Lazy::new(|| {
+    EmbeddingCache::new(Duration::from_secs(
+        LocalRagSettings::default().cache_ttl_secs,
+    ))
+});
+
+/// Apply saved RAG settings to the shared cache.
+pub fn apply_local_rag_settings(settings: &LocalRagSettings) {
+    EMBEDDING_CACHE.set_ttl(Duration::from_secs(settings.cache_ttl_secs));
+}
+
+#[tauri::command]
+pub fn get_local_rag_settings(app: tauri::AppHandle) -> Result<LocalRagSettings, String> {
+    ModelState::load_local_rag_settings(&app)
+}
+
+#[tauri::command]
+pub fn set_local_rag_settings(
+    app: tauri::AppHandle,
+    settings: LocalRagSettings,
+) -> Result<(), String> {
+    ModelState::save_local_rag_settings(&app, &settings)?;
+    crate::core::audit_log::record(
+        &app,
+        "settings_changed",
+        serde_json::json!({
+            "setting": "local_rag",
+            "cache_ttl_secs": settings.cache_ttl_secs,
+        }),
+    );
+    apply_local_rag_settings(&settings);
+    Ok(())
+}
+
+impl EmbeddingCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            ttl_ms: AtomicU64::new(ttl.as_millis() as u64),
+        }
+    }
+
+    fn ttl(&self) -> Duration {
+        Duration::from_millis(self.ttl_ms.load(Ordering::Relaxed))
+    }
+
+    /// Change the lifetime of cached entries; `Duration::ZERO` disables the cache.
+    pub fn set_ttl(&self, ttl: Duration) {
+        self.ttl_ms.store(ttl.as_millis() as u64, Ordering::Relaxed);
+        if ttl.is_zero()
+            && let Ok(mut entries) = self.entries.lock()
+        {
+            entries.clear();
+        }
+    }
+
+    pub fn key(model_id: &str, pooling: &str, input: &str) -> String {
+        let mut hasher = Sha256::new();
+        for part in [model_id, pooling, input] {
+            hasher.update(part.as_bytes());
+            hasher.update([0u8]);
+        }
+        hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+
+    pub fn get(&self, key: &str) -> Option<CachedEmbedding> {
+        let ttl = self.ttl();
+        if ttl.is_zero() {
+            return None;
+        }
+        let mut entries = self.entries.lock().ok()?;
+        match entries.get(key) {
+            Some((value, stored_at)) if stored_at.elapsed() < ttl => Some(value.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn insert(&self, key: String, value: CachedEmbedding) {
+        let ttl = self.ttl();
+        if ttl.is_zero() {
+            return;
+        }
+        let Ok(mut entries) = self.entries.lock() else {
+            return;
+        };
+        if entries.len() >= MAX_ENTRIES {
+            entries.retain(|_, (_, stored_at)| stored_at.elapsed() < ttl);
+        }
+        if entries.len() >= MAX_ENTRIES
+            && let Some(oldest) = entries
+                .iter()
+                .min_by_key(|(_, (_, stored_at))| *stored_at)
+                .map(|(k, _)| k.clone())
+        {
+            entries.remove(&oldest);
+        }
+        entries.insert(key, (value, Instant::now()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cached(value: f32) -> CachedEmbedding {
+        CachedEmbedding {
+            embedding: vec![value],
+            prompt_tokens: 1,
+        }
+    }
+
+    #[test]
+    fn hits_until_ttl_expires() {
+        let cache = EmbeddingCache::new(Duration::from_millis(50));
+        let key = EmbeddingCache::key("model.gguf", "mean", "hello");
+        assert_ne!(key, EmbeddingCache::key("model.gguf", "cls", "hello"));
+
+        assert_eq!(cache.get(&key), None);
+        cache.insert(key.clone(), cached(0.5));
+        assert_eq!(cache.get(&key), Some(cached(0.5)));
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(cache.get(&key), None);
+    }
+
+    #[test]
+    fn zero_ttl_disables_cache() {
+        let cache = EmbeddingCache::new(Duration::ZERO);
+        cache.insert("k".to_string(), cached(1.0));
+        assert_eq!(cache.get("k"), None);
+    }
+
+    #[test]
+    fn set_ttl_applies_to_existing_cache() {
+        let cache = EmbeddingCache::new(Duration::from_secs(60));
+        cache.insert("k".to_string(), cached(1.0));
+        assert_eq!(cache.get("k"), Some(cached(1.0)));
+
+        cache.set_ttl(Duration::ZERO);
+        assert_eq!(cache.get("k"), None);
+
+        cache.set_ttl(Duration::from_secs(60));
+        assert_eq!(cache.get("k"), None);
+        cache.insert("k".to_string(), cached(2.0));
+        assert_eq!(cache.get("k"), Some(cached(2.0)));
+    }
+}
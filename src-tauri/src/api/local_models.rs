@@ -7,8 +7,12 @@
 //! * Download helper with progress events bridged to the Svelte frontend
 
 use crate::api::model_manager::manifest::{
-    DownloadManifest, infer_quantization_from_label, load_manifest, save_manifest,
+    CURRENT_MANIFEST_VERSION, DownloadManifest, infer_quantization_from_label, load_manifest,
+    save_manifest,
 };
+use crate::api::scan_cache::{ModelScanCache, SCAN_CACHE_FILE, ScanCompleteEvent};
+use crate::core::state::ModelState;
+use crate::core::types::{FolderPath, ModelPath, ModelsStorageSettings, ProxySettings, RepoId};
 use crate::core::weights::local_list_safetensors;
 use crate::models::registry::{ArchKind, detect_arch, detect_arch_from_config};
 use candle::quantized::gguf_file::{self, Content, Value as GgufValue, VersionedMagic};
@@ -27,6 +31,7 @@ use std::sync::{
     Arc,
     atomic::{AtomicU64, Ordering},
 };
+use std::time::Duration;
 use tauri::{AppHandle, Emitter, async_runtime};
 use tokio::sync::RwLock;
 
@@ -138,6 +143,9 @@ pub struct ModelInfo {
     pub source_repo_name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub source_quantization: Option<String>,
+    /// Number of files for split GGUF models (`model-00001-of-00003.gguf`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shard_count: Option<u8>,
     /// Indicates that Candle can instantiate the detected architecture and that
     /// validation did not fail. Use this flag together with `validation_status`
     /// to determine whether the registry can support the model.
@@ -145,6 +153,19 @@ pub struct ModelInfo {
     pub validation_status: ValidationStatus,
     pub created_at: DateTime<Utc>,
     pub metadata: GGUFMetadata,
+    /// Inferred without loading the model; see `detect_capabilities`.
+    #[serde(default)]
+    pub capabilities: ModelCapabilities,
+}
+
+/// What a local model can do, guessed from its name, metadata and sibling files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelCapabilities {
+    pub supports_thinking: bool,
+    pub supports_vision: bool,
+    pub supports_code: bool,
+    pub supports_function_calling: bool,
+    pub embedding_only: bool,
 }
 
 /// Remote GGUF file descriptor from Hugging Face.
@@ -231,6 +252,36 @@ pub struct ModelFilters {
     pub offset: Option<u32>,
 }
 
+/// Sorting options for local scan results.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SortField {
+    #[default]
+    Name,
+    Size,
+    Date,
+    ContextLength,
+}
+
+/// Filters applied to local scan results on the backend.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ModelScanFilters {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub architecture: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_context_length: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_file_size_gb: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quantization: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub has_thinking: Option<bool>,
+    #[serde(default)]
+    pub sort_by: SortField,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sort_order: Option<SortOrder>,
+}
+
 /// Download outcome returned to the frontend after copying the cached artifact.
 #[derive(Debug, Clone, Serialize)]
 pub struct DownloadedFileInfo {
@@ -240,6 +291,34 @@ pub struct DownloadedFileInfo {
     pub size: u64,
 }
 
+/// License gating of a Hugging Face repository.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct ModelGatingStatus {
+    pub gated: bool,
+    /// `auto` or `manual` approval
+    pub gating_type: Option<String>,
+    pub terms_url: Option<String>,
+    /// Whether the configured Hugging Face token can download the files
+    pub accessible_with_token: bool,
+}
+
+/// One commit from a repository's history on Hugging Face.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct ModelCommit {
+    pub sha: String,
+    pub message: String,
+    pub created_at: String,
+    pub author: Option<String>,
+}
+
+/// Suggested quantization for the available VRAM.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct QuantizationRecommendation {
+    pub recommended: String,
+    /// `(quantization, estimated_vram_gb, description)`, smallest first
+    pub alternatives: Vec<(String, f32, String)>,
+}
+
 /// Download stage for progress events.
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "snake_case")]
@@ -262,6 +341,15 @@ pub struct DownloadProgressPayload {
 static README_CACHE: Lazy<RwLock<HashMap<String, String>>> =
     Lazy::new(|| RwLock::new(HashMap::new()));
 
+/// TCP connect timeout for all outbound Hugging Face requests.
+const HTTP_CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+/// Keepalive for pooled connections reused between requests.
+const HTTP_TCP_KEEPALIVE: Duration = Duration::from_secs(30);
+
+/// Overall timeout for Hugging Face API/metadata requests.
+/// Not applied to file downloads, which may legitimately run for hours.
+const HF_API_TIMEOUT: Duration = Duration::from_secs(60);
+
 const README_FALLBACK_MESSAGE: &str =
     "README.md не найден или недоступен для этой модели на Hugging Face.";
 
@@ -319,8 +407,8 @@ impl HubProgress for HubProgressEmitter {
 
 /// Command: parse a GGUF metadata section from a file.
 #[tauri::command]
-pub async fn parse_gguf_metadata(file_path: String) -> Result<GGUFMetadata, String> {
-    let path = PathBuf::from(&file_path);
+pub async fn parse_gguf_metadata(file_path: ModelPath) -> Result<GGUFMetadata, String> {
+    let path = PathBuf::from(file_path);
     async_runtime::spawn_blocking(move || {
         let envelope = read_gguf_metadata(&path, true)?;
         Ok(envelope.metadata)
@@ -330,25 +418,73 @@ pub async fn parse_gguf_metadata(file_path: String) -> Result<GGUFMetadata, Stri
 }
 
 /// Command: scan a folder recursively for GGUF models.
+///
+/// Unchanged GGUF files are served from the scan cache unless `force_rescan` is set.
 #[tauri::command]
-pub async fn scan_models_folder(folder_path: String) -> Result<Vec<ModelInfo>, String> {
-    let path = PathBuf::from(&folder_path);
-    async_runtime::spawn_blocking(move || scan_directory(&path))
-        .await
-        .map_err(|e| e.to_string())?
+pub async fn scan_models_folder(
+    app: AppHandle,
+    folder_path: FolderPath,
+    force_rescan: Option<bool>,
+) -> Result<Vec<ModelInfo>, String> {
+    let path = PathBuf::from(folder_path);
+    async_runtime::spawn_blocking(move || {
+        scan_with_cache(&app, force_rescan.unwrap_or(false), |gguf_info| {
+            scan_directory(&path, gguf_info)
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Command: scan several folders and merge the results.
+#[tauri::command]
+pub async fn scan_models_folders(
+    app: AppHandle,
+    folder_paths: Vec<String>,
+    force_rescan: Option<bool>,
+) -> Result<Vec<ModelInfo>, String> {
+    async_runtime::spawn_blocking(move || {
+        let dirs: Vec<&str> = folder_paths.iter().map(String::as_str).collect();
+        scan_with_cache(&app, force_rescan.unwrap_or(false), |gguf_info| {
+            scan_directories(&dirs, gguf_info)
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
 /// Backwards-compatible alias for legacy frontend code.
 #[tauri::command]
-pub async fn scan_local_models_folder(folder_path: String) -> Result<Vec<ModelInfo>, String> {
-    scan_models_folder(folder_path).await
+pub async fn scan_local_models_folder(
+    app: AppHandle,
+    folder_path: FolderPath,
+) -> Result<Vec<ModelInfo>, String> {
+    scan_models_folder(app, folder_path, None).await
+}
+
+/// Command: scan a folder and return only models matching `filters`.
+#[tauri::command]
+pub async fn scan_models_with_filters(
+    app: AppHandle,
+    folder_path: FolderPath,
+    filters: ModelScanFilters,
+) -> Result<Vec<ModelInfo>, String> {
+    let models = scan_models_folder(app, folder_path, None).await?;
+    Ok(apply_scan_filters(models, &filters))
 }
 
 /// Command: delete a local model file.
 #[tauri::command]
-pub async fn delete_local_model(model_path: String) -> Result<(), String> {
-    let path = PathBuf::from(model_path);
-    async_runtime::spawn_blocking(move || {
+pub async fn delete_local_model(app: AppHandle, model_path: String) -> Result<(), String> {
+    let path = PathBuf::from(&model_path);
+    let storage = ModelState::load_models_storage_settings(&app)?;
+    if !is_inside_any_dir(&path, &storage.models_dirs) {
+        return Err(format!(
+            "Refusing to delete a model outside the configured models directories: {}",
+            path.display()
+        ));
+    }
+    let result = async_runtime::spawn_blocking(move || {
         if !path.exists() {
             return Err(format!("File does not exist: {}", path.display()));
         }
@@ -364,7 +500,16 @@ pub async fn delete_local_model(model_path: String) -> Result<(), String> {
         }
     })
     .await
-    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())?;
+
+    if result.is_ok() {
+        crate::core::audit_log::record(
+            &app,
+            "data_cleared",
+            serde_json::json!({ "kind": "local_model", "path": model_path }),
+        );
+    }
+    result
 }
 
 /// Command: search Hugging Face Hub for GGUF models.
@@ -395,6 +540,7 @@ pub async fn search_huggingface_gguf(
     let response = client
         .get("https://huggingface.co/api/models")
         .query(&params)
+        .timeout(HF_API_TIMEOUT)
         .send()
         .await
         .map_err(|e| format!("Failed to query Hugging Face: {e}"))?
@@ -430,29 +576,206 @@ pub async fn search_huggingface_gguf(
     Ok(results)
 }
 
+/// Токен Hugging Face: `HF_TOKEN` или сохранённый `huggingface-cli login`.
+fn hf_token() -> Option<String> {
+    std::env::var("HF_TOKEN")
+        .ok()
+        .filter(|token| !token.trim().is_empty())
+        .or_else(|| hf_hub::Cache::from_env().token())
+}
+
+/// Значение `gated` из API: `false`, `"auto"` или `"manual"`.
+fn parse_gating_type(gated: Option<&JsonValue>) -> Option<String> {
+    match gated? {
+        JsonValue::String(kind) => Some(kind.clone()),
+        JsonValue::Bool(true) => Some("auto".to_string()),
+        _ => None,
+    }
+}
+
+/// Command: check whether a repository requires accepting its license first.
+#[tauri::command]
+pub async fn check_model_gating(repo_id: String) -> Result<ModelGatingStatus, String> {
+    let repo_id = repo_id.trim();
+    if repo_id.is_empty() {
+        return Err("Repository id cannot be empty".to_string());
+    }
+    let client = build_http_client()?;
+    let detail = fetch_model_detail(&client, repo_id).await?;
+    let gating_type = parse_gating_type(detail.gated.as_ref());
+    if gating_type.is_none() {
+        return Ok(ModelGatingStatus {
+            gated: false,
+            gating_type: None,
+            terms_url: None,
+            accessible_with_token: true,
+        });
+    }
+
+    // Доступ проверяем запросом к любому файлу репозитория от имени токена
+    let accessible_with_token = match (hf_token(), detail.siblings.first()) {
+        (Some(token), Some(file)) => client
+            .head(format!(
+                "https://huggingface.co/{repo_id}/resolve/main/{}",
+                file.rfilename
+            ))
+            .bearer_auth(token)
+            .timeout(HF_API_TIMEOUT)
+            .send()
+            .await
+            .is_ok_and(|response| response.status().is_success()),
+        _ => false,
+    };
+
+    Ok(ModelGatingStatus {
+        gated: true,
+        gating_type,
+        terms_url: Some(format!("https://huggingface.co/{repo_id}")),
+        accessible_with_token,
+    })
+}
+
+/// Command: accept a repository's license terms.
+/// The Hugging Face API has no endpoint for this yet; terms are accepted on the website.
+#[tauri::command]
+pub async fn accept_model_gating(repo_id: String, hf_token: String) -> Result<(), String> {
+    let _ = hf_token;
+    Err(format!(
+        "Accepting model terms through the API is not supported; accept them at https://huggingface.co/{} and retry",
+        repo_id.trim()
+    ))
+}
+
+/// Разбирает ответ `/api/models/{repo}/commits/{rev}`: `id`, `title`, `date`, `authors[].user`.
+fn parse_model_commits(items: &[JsonValue]) -> Vec<ModelCommit> {
+    items
+        .iter()
+        .filter_map(|item| {
+            let sha = item.get("id")?.as_str()?.to_string();
+            Some(ModelCommit {
+                sha,
+                message: item
+                    .get("title")
+                    .and_then(JsonValue::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+                created_at: item
+                    .get("date")
+                    .and_then(JsonValue::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+                author: item
+                    .get("authors")
+                    .and_then(JsonValue::as_array)
+                    .and_then(|authors| authors.first())
+                    .and_then(|author| author.get("user"))
+                    .and_then(JsonValue::as_str)
+                    .map(str::to_string),
+            })
+        })
+        .collect()
+}
+
+/// Command: list recent commits of a repository's `main` branch, newest first.
+#[tauri::command]
+pub async fn get_model_commits(
+    repo_id: String,
+    limit: Option<usize>,
+) -> Result<Vec<ModelCommit>, String> {
+    let repo_id = repo_id.trim();
+    if repo_id.is_empty() {
+        return Err("Repository id cannot be empty".to_string());
+    }
+    let limit = limit.unwrap_or(20).clamp(1, 100);
+    let client = build_http_client()?;
+    let mut request = client
+        .get(format!(
+            "https://huggingface.co/api/models/{repo_id}/commits/main"
+        ))
+        .timeout(HF_API_TIMEOUT);
+    if let Some(token) = hf_token() {
+        request = request.bearer_auth(token);
+    }
+    let items: Vec<JsonValue> = request
+        .send()
+        .await
+        .map_err(|e| format!("Failed to query Hugging Face: {e}"))?
+        .error_for_status()
+        .map_err(|e| format!("Hugging Face request failed: {e}"))?
+        .json()
+        .await
+        .map_err(|e| format!("Failed to decode Hugging Face response: {e}"))?;
+
+    let mut commits = parse_model_commits(&items);
+    commits.truncate(limit);
+    Ok(commits)
+}
+
+/// SHA коммита из пути в кеше hf-hub: `.../snapshots/<sha>/<file>`.
+fn snapshot_commit_sha(pointer_path: &Path) -> Option<String> {
+    let mut components = pointer_path.components().rev().map(|c| c.as_os_str());
+    while let Some(component) = components.next() {
+        if components.clone().next() == Some(std::ffi::OsStr::new("snapshots")) {
+            return component.to_str().map(str::to_string);
+        }
+    }
+    None
+}
+
 /// Command: download a GGUF file using hf-hub and emit progress events.
 #[tauri::command]
 pub async fn download_hf_model_file(
     app: AppHandle,
-    repo_id: String,
+    repo_id: RepoId,
     filename: String,
     destination_dir: String,
+    revision: Option<String>,
 ) -> Result<DownloadedFileInfo, String> {
     use crate::api::model_manager::manifest::{DownloadManifest, infer_quantization_from_label};
 
+    let repo_id = String::from(repo_id);
+    // Понятная ошибка вместо 403 от hf-hub; сбой самой проверки не блокирует загрузку
+    match check_model_gating(repo_id.clone()).await {
+        Ok(ModelGatingStatus {
+            gated: true,
+            accessible_with_token: false,
+            terms_url,
+            ..
+        }) => {
+            return Err(format!(
+                "{repo_id} is gated: accept its license at {} and log in with a Hugging Face token that has access",
+                terms_url.unwrap_or_else(|| format!("https://huggingface.co/{repo_id}"))
+            ));
+        }
+        Ok(_) => {}
+        Err(err) => log::warn!("Gating check for {} failed: {}", repo_id, err),
+    }
+
     let download_id = format!("{}::{}", repo_id, filename);
     let api = ApiBuilder::new()
         .with_progress(false)
         .build()
         .map_err(|e| format!("Failed to initialize hf-hub API: {e}"))?;
 
-    let repo = api.model(repo_id.clone());
+    // Конкретный коммит (SHA) или ветка; по умолчанию `main`
+    let revision = revision
+        .map(|rev| rev.trim().to_string())
+        .filter(|rev| !rev.is_empty());
+    let repo = match &revision {
+        Some(rev) => api.repo(hf_hub::Repo::with_revision(
+            repo_id.clone(),
+            hf_hub::RepoType::Model,
+            rev.clone(),
+        )),
+        None => api.model(repo_id.clone()),
+    };
     let progress = HubProgressEmitter::new(app.clone(), download_id.clone(), filename.clone());
 
     let pointer_path = repo
         .download_with_progress(&filename, progress.clone())
         .await
         .map_err(|e| format!("Download failed: {e}"))?;
+    let commit_sha = snapshot_commit_sha(&pointer_path);
 
     let dest_dir = PathBuf::from(&destination_dir);
     let dest_file = dest_dir.join(&filename);
@@ -485,7 +808,7 @@ pub async fn download_hf_model_file(
     let quantization = extract_quantization_from_filename(&filename);
 
     let manifest = DownloadManifest {
-        version: 1,
+        version: CURRENT_MANIFEST_VERSION,
         repo_id: repo_id.clone(),
         repo_name: repo_name.clone(),
         publisher: publisher.clone(),
@@ -493,6 +816,11 @@ pub async fn download_hf_model_file(
         quantization: quantization.or_else(|| infer_quantization_from_label(&filename)),
         card_id: None,
         card_name: None,
+        sha256: None,
+        file_size: Some(size),
+        tensor_count: None,
+        revision,
+        commit_sha,
         downloaded_at: chrono::Utc::now().to_rfc3339(),
     };
 
@@ -555,6 +883,7 @@ pub async fn get_model_readme(repo_id: String) -> Result<String, String> {
         let fallback_url = format!("https://huggingface.co/{}/raw/main/README.md", trimmed);
         let response = client
             .get(&fallback_url)
+            .timeout(HF_API_TIMEOUT)
             .send()
             .await
             .map_err(|e| format!("Failed to request README: {e}"))?;
@@ -706,7 +1035,70 @@ fn read_gguf_metadata(path: &Path, include_tokens: bool) -> Result<MetadataEnvel
     })
 }
 
-fn scan_directory(dir: &Path) -> Result<Vec<ModelInfo>, String> {
+/// Builds `ModelInfo` for a single GGUF file during a scan.
+type GgufInfoFn<'a> = dyn FnMut(&Path) -> Result<Option<ModelInfo>, String> + 'a;
+
+/// Runs `scan` with GGUF metadata served from `profile_dir/scan_cache.json`,
+/// then persists the cache and emits `scan_complete`.
+fn scan_with_cache(
+    app: &AppHandle,
+    force_rescan: bool,
+    scan: impl FnOnce(&mut GgufInfoFn<'_>) -> Result<Vec<ModelInfo>, String>,
+) -> Result<Vec<ModelInfo>, String> {
+    let cache_path = ModelState::ensure_profile_dir(app)?.join(SCAN_CACHE_FILE);
+    let mut cache = ModelScanCache::load(&cache_path);
+    let mut stats = ScanCompleteEvent::default();
+
+    let models = scan(&mut |path: &Path| {
+        cache.get_or_build(path, force_rescan, &mut stats, build_model_info)
+    })?;
+
+    cache.prune_missing();
+    if let Err(err) = cache.save(&cache_path) {
+        log::warn!("{err}");
+    }
+    let _ = app.emit("scan_complete", &stats);
+    Ok(models)
+}
+
+/// Scans several folders, skipping missing ones and models reachable from more
+/// than one folder (compared by canonical path).
+fn scan_directories(
+    dirs: &[&str],
+    gguf_info: &mut GgufInfoFn<'_>,
+) -> Result<Vec<ModelInfo>, String> {
+    let mut visited: HashSet<PathBuf> = HashSet::new();
+    let mut models = Vec::new();
+
+    for dir in dirs {
+        let dir = Path::new(dir);
+        if !dir.is_dir() {
+            log::warn!("Skipping missing models directory: {}", dir.display());
+            continue;
+        }
+        for info in scan_directory(dir, gguf_info)? {
+            let key = fs::canonicalize(&info.path).unwrap_or_else(|_| info.path.clone());
+            if visited.insert(key) {
+                models.push(info);
+            }
+        }
+    }
+
+    models.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(models)
+}
+
+/// True if `path` is located inside one of `dirs`.
+fn is_inside_any_dir(path: &Path, dirs: &[String]) -> bool {
+    let Ok(path) = fs::canonicalize(path) else {
+        return false;
+    };
+    dirs.iter()
+        .filter_map(|dir| fs::canonicalize(dir).ok())
+        .any(|dir| path.starts_with(dir))
+}
+
+fn scan_directory(dir: &Path, gguf_info: &mut GgufInfoFn<'_>) -> Result<Vec<ModelInfo>, String> {
     if !dir.exists() {
         return Err(format!("Path does not exist: {}", dir.display()));
     }
@@ -749,7 +1141,10 @@ fn scan_directory(dir: &Path) -> Result<Vec<ModelInfo>, String> {
                 .map(|ext| ext.eq_ignore_ascii_case("gguf"))
                 .unwrap_or(false)
             {
-                match build_model_info(&path) {
+                if is_secondary_gguf_shard(&path) {
+                    continue;
+                }
+                match gguf_info(&path) {
                     Ok(Some(info)) => models.push(info),
                     Ok(None) => {
                         log::info!(
@@ -770,17 +1165,42 @@ fn scan_directory(dir: &Path) -> Result<Vec<ModelInfo>, String> {
     Ok(models)
 }
 
-fn build_model_info(path: &Path) -> Result<Option<ModelInfo>, String> {
+pub(crate) fn build_model_info(path: &Path) -> Result<Option<ModelInfo>, String> {
     use crate::api::model_manager::manifest::load_manifest;
 
     let envelope = read_gguf_metadata(path, false)?;
-    let file_name = path
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap_or("unknown")
-        .to_string();
+    let shard = parse_gguf_shard(path);
+    let file_name = match &shard {
+        Some(shard) => shard.prefix.clone(),
+        None => path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("unknown")
+            .to_string(),
+    };
 
     let metadata_fs = fs::metadata(path).map_err(|e| format!("Failed to read metadata: {e}"))?;
+    let mut file_size = metadata_fs.len();
+    let mut shard_count = None;
+    let mut candle_compatible = envelope.detected_arch.is_some();
+    let mut validation_status = envelope.validation;
+    if let Some(shard) = &shard {
+        let shards = find_gguf_shards(path);
+        file_size = shards
+            .iter()
+            .filter_map(|p| fs::metadata(p).ok())
+            .map(|m| m.len())
+            .sum();
+        shard_count = Some(u8::try_from(shard.total).unwrap_or(u8::MAX));
+        // Candle читает GGUF из одного файла; split-модели пока не загружаются
+        candle_compatible = false;
+        validation_status.level = ValidationLevel::Error;
+        validation_status.messages.push(format!(
+            "Split GGUF model ({} of {} shards found) is not supported by the Candle loader",
+            shards.len(),
+            shard.total
+        ));
+    }
     let created_at = metadata_fs
         .created()
         .or_else(|_| metadata_fs.modified())
@@ -846,10 +1266,10 @@ fn build_model_info(path: &Path) -> Result<Option<ModelInfo>, String> {
         return Ok(None);
     }
 
-    Ok(Some(ModelInfo {
+    let mut info = ModelInfo {
         name: file_name,
         path: path.to_path_buf(),
-        file_size: metadata_fs.len(),
+        file_size,
         format: ModelFormat::Gguf,
         architecture: envelope.metadata.architecture.clone(),
         detected_architecture,
@@ -863,11 +1283,15 @@ fn build_model_info(path: &Path) -> Result<Option<ModelInfo>, String> {
         source_repo_id,
         source_repo_name,
         source_quantization,
-        candle_compatible: envelope.detected_arch.is_some(),
-        validation_status: envelope.validation,
+        shard_count,
+        candle_compatible,
+        validation_status,
         created_at,
         metadata: envelope.metadata,
-    }))
+        capabilities: ModelCapabilities::default(),
+    };
+    info.capabilities = detect_capabilities(&info, &companion_files(path));
+    Ok(Some(info))
 }
 
 fn build_safetensors_model_info(dir: &Path) -> Result<Option<ModelInfo>, String> {
@@ -981,7 +1405,7 @@ fn build_safetensors_model_info(dir: &Path) -> Result<Option<ModelInfo>, String>
         custom_metadata: Vec::new(),
     };
 
-    Ok(Some(ModelInfo {
+    let mut info = ModelInfo {
         name: folder_name,
         path: dir.to_path_buf(),
         file_size: total_bytes,
@@ -1000,6 +1424,7 @@ fn build_safetensors_model_info(dir: &Path) -> Result<Option<ModelInfo>, String>
         source_repo_id,
         source_repo_name,
         source_quantization,
+        shard_count: None,
         candle_compatible: candle_ready,
         validation_status: ValidationStatus {
             level: ValidationLevel::Warning,
@@ -1009,24 +1434,176 @@ fn build_safetensors_model_info(dir: &Path) -> Result<Option<ModelInfo>, String>
         },
         created_at,
         metadata,
-    }))
+        capabilities: ModelCapabilities::default(),
+    };
+    info.capabilities = detect_capabilities(&info, &dir_file_names(dir));
+    Ok(Some(info))
+}
+
+/// Прокси из настроек пользователя и номер их версии; загружается при старте
+/// и меняется командой.
+static PROXY_SETTINGS: Lazy<std::sync::RwLock<(u64, ProxySettings)>> =
+    Lazy::new(|| std::sync::RwLock::new((0, ProxySettings::default())));
+
+/// Общий клиент с пулом соединений и версия настроек, из которых он собран.
+static SHARED_HTTP_CLIENT: Lazy<std::sync::RwLock<Option<(u64, Client)>>> =
+    Lazy::new(|| std::sync::RwLock::new(None));
+
+const HTTP_PROXY_ENV_VARS: &[&str] = &["HTTP_PROXY", "http_proxy"];
+const HTTPS_PROXY_ENV_VARS: &[&str] = &["HTTPS_PROXY", "https_proxy"];
+
+/// Прокси передаётся только в наш `reqwest::Client` (см. `new_http_client`);
+/// окружение процесса после старта не меняется — `set_var` при работающих
+/// потоках tokio/rayon небезопасен. Клиенты hf-hub видят лишь прокси из
+/// окружения, с которым запущено приложение.
+pub(crate) fn apply_proxy_settings(settings: ProxySettings) {
+    // Клиент прежней версии перестаёт выдаваться сразу после смены версии
+    if let Ok(mut guard) = PROXY_SETTINGS.write() {
+        *guard = (guard.0 + 1, settings);
+    }
+}
+
+/// Настройка имеет приоритет над переменными окружения.
+fn resolve_proxy(configured: Option<&str>, env_vars: &[&str]) -> Option<String> {
+    configured
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .map(str::to_string)
+        .or_else(|| {
+            env_vars
+                .iter()
+                .filter_map(|name| std::env::var(name).ok())
+                .map(|url| url.trim().to_string())
+                .find(|url| !url.is_empty())
+        })
 }
 
+/// Возвращает общий клиент: `reqwest::Client` держит пул соединений, поэтому
+/// повторные запросы к Hub не тратят время на TCP/TLS-рукопожатие.
 pub(crate) fn build_http_client() -> Result<Client, String> {
-    Client::builder()
+    let (version, settings) = PROXY_SETTINGS
+        .read()
+        .map(|guard| guard.clone())
+        .unwrap_or_default();
+    if let Some(client) = SHARED_HTTP_CLIENT.read().ok().and_then(|guard| {
+        guard
+            .as_ref()
+            .filter(|(built_for, _)| *built_for == version)
+            .map(|(_, client)| client.clone())
+    }) {
+        return Ok(client);
+    }
+    let client = new_http_client(&settings)?;
+    // Клиент, собранный по устаревшим настройкам, не вытесняет более новый
+    if let Ok(mut guard) = SHARED_HTTP_CLIENT.write()
+        && guard.as_ref().is_none_or(|(cached, _)| *cached < version)
+    {
+        *guard = Some((version, client.clone()));
+    }
+    Ok(client)
+}
+
+fn new_http_client(settings: &ProxySettings) -> Result<Client, String> {
+    let mut builder = Client::builder()
         .user_agent(format!(
             "oxide-lab/{} (https://github.com/FerrisMind/Oxide-Lab)",
             env!("CARGO_PKG_VERSION")
         ))
+        .connect_timeout(HTTP_CONNECT_TIMEOUT)
+        .connection_verbose(false)
+        .pool_max_idle_per_host(4)
+        .tcp_keepalive(HTTP_TCP_KEEPALIVE);
+
+    // Явно заданный прокси отключает системные настройки reqwest, поэтому
+    // исключения из NO_PROXY передаём сами.
+    if let Some(url) = resolve_proxy(settings.http_proxy.as_deref(), HTTP_PROXY_ENV_VARS) {
+        let proxy = reqwest::Proxy::http(&url)
+            .map_err(|e| format!("Invalid HTTP proxy '{url}': {e}"))?
+            .no_proxy(reqwest::NoProxy::from_env());
+        builder = builder.proxy(proxy);
+    }
+    if let Some(url) = resolve_proxy(settings.https_proxy.as_deref(), HTTPS_PROXY_ENV_VARS) {
+        let proxy = reqwest::Proxy::https(&url)
+            .map_err(|e| format!("Invalid HTTPS proxy '{url}': {e}"))?
+            .no_proxy(reqwest::NoProxy::from_env());
+        builder = builder.proxy(proxy);
+    }
+
+    builder
         .build()
         .map_err(|e| format!("Failed to create HTTP client: {e}"))
 }
 
+#[tauri::command]
+pub fn get_models_storage_settings(app: AppHandle) -> Result<ModelsStorageSettings, String> {
+    ModelState::load_models_storage_settings(&app)
+}
+
+#[tauri::command]
+pub fn set_models_storage_settings(
+    app: AppHandle,
+    settings: ModelsStorageSettings,
+) -> Result<(), String> {
+    let settings = settings.normalized();
+    ModelState::save_models_storage_settings(&app, &settings)?;
+    crate::core::audit_log::record(
+        &app,
+        "settings_changed",
+        serde_json::json!({
+            "setting": "models_storage",
+            "models_dirs": settings.models_dirs,
+        }),
+    );
+    Ok(())
+}
+
+/// Command: register the folder the models page works with as a models directory.
+#[tauri::command]
+pub fn register_models_dir(app: AppHandle, dir: String) -> Result<(), String> {
+    let mut settings = ModelState::load_models_storage_settings(&app)?;
+    if settings.models_dirs.iter().any(|d| d.trim() == dir.trim()) {
+        return Ok(());
+    }
+    settings.models_dirs.insert(0, dir);
+    set_models_storage_settings(app, settings)
+}
+
+/// Command: count manifests per format version in the configured models directories.
+#[tauri::command]
+pub fn manifest_version_stats(app: AppHandle) -> Result<HashMap<u32, u32>, String> {
+    let storage = ModelState::load_models_storage_settings(&app)?;
+    let dirs: Vec<PathBuf> = storage.models_dirs.iter().map(PathBuf::from).collect();
+    Ok(crate::api::model_manager::manifest::manifest_version_counts(&dirs))
+}
+
+#[tauri::command]
+pub fn get_proxy_settings(app: AppHandle) -> Result<ProxySettings, String> {
+    ModelState::load_proxy_settings(&app)
+}
+
+#[tauri::command]
+pub fn set_proxy_settings(app: AppHandle, settings: ProxySettings) -> Result<(), String> {
+    settings.validate()?;
+    ModelState::save_proxy_settings(&app, &settings)?;
+    crate::core::audit_log::record(
+        &app,
+        "settings_changed",
+        serde_json::json!({
+            "setting": "proxy",
+            "http_proxy_set": settings.http_proxy.is_some(),
+            "https_proxy_set": settings.https_proxy.is_some(),
+        }),
+    );
+    apply_proxy_settings(settings);
+    Ok(())
+}
+
 async fn fetch_model_detail(client: &Client, repo_id: &str) -> Result<HFModelDetail, String> {
     let url = format!("https://huggingface.co/api/models/{repo_id}");
     client
         .get(url)
         .query(&[("blobs", "true"), ("config", "true"), ("cardData", "true")])
+        .timeout(HF_API_TIMEOUT)
         .send()
         .await
         .map_err(|e| format!("Failed to fetch model detail: {e}"))?
@@ -1172,6 +1749,197 @@ fn apply_search_sorting(
     }
 }
 
+fn apply_scan_filters(models: Vec<ModelInfo>, filters: &ModelScanFilters) -> Vec<ModelInfo> {
+    let eq_ignore_case = |expected: &Option<String>, values: [Option<&String>; 2]| match expected {
+        Some(expected) => values
+            .into_iter()
+            .flatten()
+            .any(|value| value.eq_ignore_ascii_case(expected)),
+        None => true,
+    };
+    let max_bytes = filters
+        .max_file_size_gb
+        .map(|gb| (f64::from(gb) * 1024.0 * 1024.0 * 1024.0) as u64);
+
+    let mut models: Vec<ModelInfo> = models
+        .into_iter()
+        .filter(|m| {
+            eq_ignore_case(
+                &filters.architecture,
+                [m.architecture.as_ref(), m.detected_architecture.as_ref()],
+            )
+        })
+        .filter(|m| {
+            eq_ignore_case(
+                &filters.quantization,
+                [m.quantization.as_ref(), m.source_quantization.as_ref()],
+            )
+        })
+        .filter(|m| {
+            filters
+                .min_context_length
+                .is_none_or(|min| m.context_length.is_some_and(|ctx| ctx >= min))
+        })
+        .filter(|m| max_bytes.is_none_or(|max| m.file_size <= max))
+        .filter(|m| {
+            filters
+                .has_thinking
+                .is_none_or(|wanted| is_thinking_model(m) == wanted)
+        })
+        .collect();
+
+    models.sort_by(|a, b| match filters.sort_by {
+        SortField::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+        SortField::Size => a.file_size.cmp(&b.file_size),
+        SortField::Date => a.created_at.cmp(&b.created_at),
+        SortField::ContextLength => a.context_length.cmp(&b.context_length),
+    });
+    if matches!(filters.sort_order, Some(SortOrder::Desc)) {
+        models.reverse();
+    }
+    models
+}
+
+/// Heuristic: reasoning models usually carry one of these words in their name.
+fn is_thinking_model(model: &ModelInfo) -> bool {
+    static REGEX: OnceCell<Regex> = OnceCell::new();
+    let regex = REGEX.get_or_init(|| {
+        Regex::new(r"\b(?:think|thinking|reason|reasoning|reasoner|r1|qwq)\b")
+            .expect("Failed to compile thinking model regex")
+    });
+    [
+        Some(&model.name),
+        model.model_name.as_ref(),
+        model.source_repo_id.as_ref(),
+        model.architecture.as_ref(),
+    ]
+    .into_iter()
+    .flatten()
+    .any(|value| regex.is_match(&value.to_lowercase()))
+}
+
+/// Имена файлов каталога (без подкаталогов).
+fn dir_file_names(dir: &Path) -> Vec<String> {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.file_type().is_ok_and(|t| t.is_file()))
+                .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Файлы рядом с GGUF-моделью (например, `mmproj-*.gguf` для vision).
+fn companion_files(path: &Path) -> Vec<String> {
+    let own_name = path.file_name().and_then(|n| n.to_str());
+    path.parent()
+        .map(dir_file_names)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|name| Some(name.as_str()) != own_name)
+        .collect()
+}
+
+/// Эвристика возможностей модели без её загрузки: ключевые слова в имени,
+/// архитектура GGUF, маркеры вызова инструментов в токенах или шаблоне чата
+/// и соседние файлы (проектор `mmproj` этой модели означает поддержку изображений).
+pub fn detect_capabilities(info: &ModelInfo, companion_files: &[String]) -> ModelCapabilities {
+    const VISION_MARKERS: &[&str] = &["vision", "-vl", "llava", "pixtral"];
+    const EMBED_MARKERS: &[&str] = &["embed", "bge-"];
+    const EMBED_ARCHITECTURES: &[&str] = &["bert", "nomic-bert", "jina-bert-v2", "t5encoder"];
+    const TOOL_MARKERS: &[&str] = &["<tool_call>", "<|python_tag|>", "[TOOL_CALLS]"];
+
+    let names: Vec<String> = [
+        Some(&info.name),
+        info.model_name.as_ref(),
+        info.source_repo_id.as_ref(),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|value| value.to_lowercase())
+    .collect();
+    let name_has = |markers: &[&str]| {
+        names
+            .iter()
+            .any(|name| markers.iter().any(|marker| name.contains(marker)))
+    };
+    let architecture = info
+        .metadata
+        .architecture
+        .as_deref()
+        .or(info.architecture.as_deref())
+        .unwrap_or_default()
+        .to_lowercase();
+
+    let chat_template =
+        custom_metadata_get_string(&info.metadata.custom_metadata, "tokenizer.chat_template")
+            .unwrap_or_default();
+    let has_token = |marker: &str| {
+        info.metadata
+            .tokenizer_tokens
+            .as_ref()
+            .is_some_and(|tokens| tokens.iter().any(|token| token == marker))
+            || chat_template.contains(marker)
+    };
+
+    let embedding_only =
+        name_has(EMBED_MARKERS) || EMBED_ARCHITECTURES.contains(&architecture.as_str());
+    // Проектор из того же репозитория или с тем же именем модели, а не любой
+    // `mmproj` в общей папке моделей
+    let has_mmproj = companion_files
+        .iter()
+        .filter_map(|name| projector_base_name(name))
+        .any(|projector| match projector.as_str() {
+            "" => info.source_repo_id.is_some(),
+            projector => names.iter().any(|name| {
+                let name = strip_quantization_suffix(name);
+                !name.is_empty() && (name.contains(projector) || projector.contains(&name))
+            }),
+        });
+    let is_code_model = {
+        static REGEX: OnceCell<Regex> = OnceCell::new();
+        let regex = REGEX.get_or_init(|| {
+            Regex::new(r"\b(?:coder|codellama|codestral|starcoder)\d*\b")
+                .expect("Failed to compile code model regex")
+        });
+        names.iter().any(|name| regex.is_match(name))
+    };
+
+    ModelCapabilities {
+        supports_thinking: !embedding_only && (is_thinking_model(info) || has_token("<think>")),
+        supports_vision: !embedding_only && (name_has(VISION_MARKERS) || has_mmproj),
+        supports_code: !embedding_only && is_code_model,
+        supports_function_calling: !embedding_only
+            && TOOL_MARKERS.iter().any(|marker| has_token(marker)),
+        embedding_only,
+    }
+}
+
+/// Имя модели, для которой собран проектор: `mmproj-qwen3-8b-f16.gguf` → `qwen3-8b`.
+/// Пустая строка — общий проектор репозитория (`mmproj-F16.gguf`); None — не проектор.
+fn projector_base_name(file_name: &str) -> Option<String> {
+    let lower = file_name.to_lowercase();
+    let stem = lower.strip_suffix(".gguf")?;
+    stem.contains("mmproj")
+        .then(|| strip_quantization_suffix(&stem.replace("mmproj", "")))
+}
+
+/// Убирает суффикс квантизации и разделители по краям: `qwen3-8b-q4_k_m` → `qwen3-8b`.
+fn strip_quantization_suffix(name: &str) -> String {
+    static REGEX: OnceCell<Regex> = OnceCell::new();
+    let regex = REGEX.get_or_init(|| {
+        Regex::new(r"(?i)(?:^|[-_.]+)(q\d+_\w+|q\d+\w*|f16|f32|fp\d+|int\d+|bf16)$")
+            .expect("Failed to compile quantization suffix regex")
+    });
+    let trimmed = name.trim_matches(|c| matches!(c, '-' | '_' | '.'));
+    regex
+        .replace(trimmed, "")
+        .trim_matches(|c| matches!(c, '-' | '_' | '.'))
+        .to_string()
+}
+
 fn validate_metadata(metadata: &GGUFMetadata, candle_ready: bool) -> ValidationStatus {
     let mut errors = Vec::new();
     let mut warnings = Vec::new();
@@ -1257,6 +2025,97 @@ fn format_parameter_count(count: u64) -> String {
     }
 }
 
+/// Parses labels such as `7B`, `7.6B`, `500M` or `8x7B` into billions of parameters.
+fn parse_parameter_count(raw: &str) -> Option<f32> {
+    let label = raw.trim().to_ascii_uppercase();
+    if let Some((experts, per_expert)) = label.split_once('X') {
+        let experts: f32 = experts.trim().parse().ok()?;
+        return Some(experts * parse_parameter_count(per_expert)?);
+    }
+    let (number, scale) = match label.chars().last()? {
+        'T' => (&label[..label.len() - 1], 1_000.0),
+        'B' => (&label[..label.len() - 1], 1.0),
+        'M' => (&label[..label.len() - 1], 0.001),
+        'K' => (&label[..label.len() - 1], 0.000_001),
+        _ => (label.as_str(), 1e-9),
+    };
+    let value: f32 = number.trim().parse().ok()?;
+    (value > 0.0).then_some(value * scale)
+}
+
+/// Quantizations considered by `recommend_quantization`, smallest first:
+/// `(name, bytes per parameter, description)`.
+const QUANTIZATION_PROFILES: &[(&str, f32, &str)] = &[
+    ("Q4_K_M", 0.6, "Smallest usable size, some quality loss"),
+    ("Q5_K_M", 0.7, "Good balance of size and quality"),
+    ("Q6_K", 0.82, "Close to Q8_0 quality at a smaller size"),
+    ("Q8_0", 1.0, "Nearly lossless"),
+    ("F16", 2.0, "Original weights, no quantization loss"),
+];
+
+/// Approximate f16 KV cache per token for one billion parameters
+/// (Llama-3-8B: 32 layers × 8 KV heads × 128 dims × K+V × 2 bytes ≈ 128 KiB per token).
+const KV_BYTES_PER_TOKEN_PER_BILLION: f32 = 16_384.0;
+/// Activations, CUDA context and other runtime buffers.
+const RUNTIME_OVERHEAD_GB: f32 = 0.5;
+const BYTES_PER_GB: f32 = 1024.0 * 1024.0 * 1024.0;
+
+fn estimate_quantization_options(
+    params_billion: f32,
+    context_length: u32,
+) -> Vec<(String, f32, String)> {
+    let kv_cache_gb =
+        context_length as f32 * params_billion * KV_BYTES_PER_TOKEN_PER_BILLION / BYTES_PER_GB;
+    QUANTIZATION_PROFILES
+        .iter()
+        .map(|(name, bytes_per_param, description)| {
+            let weights_gb = params_billion * 1e9 * bytes_per_param / BYTES_PER_GB;
+            let total = weights_gb + kv_cache_gb + RUNTIME_OVERHEAD_GB;
+            (
+                name.to_string(),
+                (total * 10.0).round() / 10.0,
+                description.to_string(),
+            )
+        })
+        .collect()
+}
+
+/// Command: pick the highest-quality quantization that fits into VRAM.
+/// `available_vram_gb <= 0` uses the total VRAM reported by nvidia-smi.
+#[tauri::command]
+pub async fn recommend_quantization(
+    repo_id: String,
+    parameter_count: String,
+    available_vram_gb: f32,
+    context_length: u32,
+) -> Result<QuantizationRecommendation, String> {
+    let params_billion = parse_parameter_count(&parameter_count)
+        .ok_or_else(|| format!("Unknown parameter count '{parameter_count}' for {repo_id}"))?;
+    let available_vram_gb = if available_vram_gb > 0.0 {
+        available_vram_gb
+    } else {
+        let gpu = async_runtime::spawn_blocking(crate::core::device::query_nvidia_gpu)
+            .await
+            .map_err(|e| format!("Failed to query GPU info: {e}"))?;
+        gpu.map(|g| g.total_vram_mb as f32 / 1024.0)
+            .ok_or_else(|| "Could not detect VRAM; pass available_vram_gb".to_string())?
+    };
+
+    let alternatives = estimate_quantization_options(params_billion, context_length);
+    // Если не влезает ни один вариант, советуем самый компактный: часть слоёв уйдёт на CPU
+    let recommended = alternatives
+        .iter()
+        .rev()
+        .find(|(_, vram_gb, _)| *vram_gb <= available_vram_gb)
+        .or_else(|| alternatives.first())
+        .map(|(name, _, _)| name.clone())
+        .unwrap_or_default();
+    Ok(QuantizationRecommendation {
+        recommended,
+        alternatives,
+    })
+}
+
 fn extract_quantization_from_filename(filename: &str) -> Option<String> {
     static REGEX: OnceCell<Regex> = OnceCell::new();
     let regex = REGEX.get_or_init(|| {
@@ -1268,6 +2127,70 @@ fn extract_quantization_from_filename(filename: &str) -> Option<String> {
         .map(|m| m.as_str().to_string())
 }
 
+/// Parsed `<prefix>-0000N-of-0000M.gguf` file name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct GgufShard {
+    prefix: String,
+    index: u32,
+    total: u32,
+}
+
+fn parse_gguf_shard(path: &Path) -> Option<GgufShard> {
+    static REGEX: OnceCell<Regex> = OnceCell::new();
+    let regex = REGEX.get_or_init(|| {
+        Regex::new(r"(?i)^(.+)-(\d{5})-of-(\d{5})\.gguf$")
+            .expect("Failed to compile GGUF shard regex")
+    });
+    let file_name = path.file_name()?.to_str()?;
+    let caps = regex.captures(file_name)?;
+    let index = caps[2].parse().ok()?;
+    let total = caps[3].parse().ok()?;
+    ((1..=total).contains(&index) && total > 1).then(|| GgufShard {
+        prefix: caps[1].to_string(),
+        index,
+        total,
+    })
+}
+
+/// Returns true for split GGUF files such as `model-00002-of-00003.gguf`.
+pub fn is_gguf_shard(path: &Path) -> bool {
+    parse_gguf_shard(path).is_some()
+}
+
+/// Identifies the split set a GGUF shard belongs to: `(prefix, total)`.
+pub fn gguf_shard_set(path: &Path) -> Option<(String, u32)> {
+    parse_gguf_shard(path).map(|shard| (shard.prefix, shard.total))
+}
+
+/// Non-primary shards are represented by the first shard when listing models.
+pub(crate) fn is_secondary_gguf_shard(path: &Path) -> bool {
+    parse_gguf_shard(path).is_some_and(|shard| shard.index != 1)
+}
+
+/// Locates all existing shards of a split GGUF model in the same directory,
+/// ordered by shard index. Non-split files yield just the path itself.
+pub fn find_gguf_shards(primary_path: &Path) -> Vec<PathBuf> {
+    let Some(primary) = parse_gguf_shard(primary_path) else {
+        return vec![primary_path.to_path_buf()];
+    };
+    let dir = primary_path.parent().unwrap_or_else(|| Path::new("."));
+    let Ok(entries) = fs::read_dir(dir) else {
+        return vec![primary_path.to_path_buf()];
+    };
+
+    let mut shards: Vec<(u32, PathBuf)> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter_map(|path| {
+            let shard = parse_gguf_shard(&path)?;
+            (shard.prefix == primary.prefix && shard.total == primary.total)
+                .then_some((shard.index, path))
+        })
+        .collect();
+    shards.sort_by_key(|(index, _)| *index);
+    shards.into_iter().map(|(_, path)| path).collect()
+}
+
 const ALLOWED_QUANTIZATIONS: &[&str] = &["Q4_K_M", "Q5_K_M", "Q6_K_M", "Q8_K_M"];
 
 fn canonicalize_quantization(raw: &str) -> String {
@@ -1307,7 +2230,7 @@ fn infer_manifest_from_gguf(path: &Path, metadata: &GGUFMetadata) -> DownloadMan
     let quantization = infer_quantization_from_label(&file_name);
 
     DownloadManifest {
-        version: 1,
+        version: CURRENT_MANIFEST_VERSION,
         repo_id,
         repo_name,
         publisher,
@@ -1315,6 +2238,11 @@ fn infer_manifest_from_gguf(path: &Path, metadata: &GGUFMetadata) -> DownloadMan
         quantization,
         card_id: None,
         card_name: None,
+        sha256: None,
+        file_size: fs::metadata(path).ok().map(|m| m.len()),
+        tensor_count: Some(metadata.tensor_count as u64),
+        revision: None,
+        commit_sha: None,
         downloaded_at: Utc::now().to_rfc3339(),
     }
 }
@@ -1350,7 +2278,7 @@ fn infer_manifest_from_safetensors(
         .or_else(|| infer_quantization_from_label(folder_name));
 
     DownloadManifest {
-        version: 1,
+        version: CURRENT_MANIFEST_VERSION,
         repo_id,
         repo_name,
         publisher,
@@ -1358,6 +2286,11 @@ fn infer_manifest_from_safetensors(
         quantization,
         card_id: None,
         card_name: None,
+        sha256: None,
+        file_size: None,
+        tensor_count: None,
+        revision: None,
+        commit_sha: None,
         downloaded_at: Utc::now().to_rfc3339(),
     }
 }
@@ -1375,7 +2308,7 @@ pub fn update_model_manifest(
     }
 
     let mut manifest = load_manifest(&path).unwrap_or_else(|| DownloadManifest {
-        version: 1,
+        version: CURRENT_MANIFEST_VERSION,
         repo_id: path
             .file_name()
             .and_then(|s| s.to_str())
@@ -1392,6 +2325,11 @@ pub fn update_model_manifest(
         quantization: None,
         card_id: None,
         card_name: None,
+        sha256: None,
+        file_size: None,
+        tensor_count: None,
+        revision: None,
+        commit_sha: None,
         downloaded_at: Utc::now().to_rfc3339(),
     });
 
@@ -1724,6 +2662,9 @@ struct HFModelDetail {
     siblings: Vec<HFSiblingDetail>,
     #[serde(default, rename = "modelId")]
     model_id: Option<String>,
+    /// `false`, `"auto"` or `"manual"`
+    #[serde(default)]
+    gated: Option<JsonValue>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -1747,3 +2688,248 @@ struct HFFileLfs {
     size: Option<u64>,
 }
 type TokenizerExtraction = (Option<Vec<String>>, Option<Vec<f32>>, Option<usize>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_gguf_shards() {
+        assert!(is_gguf_shard(Path::new("qwen-00001-of-00003.gguf")));
+        assert!(is_gguf_shard(Path::new(
+            "/m/Qwen-Q4_K_M-00003-of-00003.GGUF"
+        )));
+        assert!(!is_gguf_shard(Path::new("qwen-Q4_K_M.gguf")));
+        assert!(!is_gguf_shard(Path::new("qwen-00004-of-00003.gguf")));
+        assert!(!is_gguf_shard(Path::new("qwen-00001-of-00003.safetensors")));
+
+        let shard = parse_gguf_shard(Path::new("qwen-00002-of-00003.gguf")).unwrap();
+        assert_eq!(shard.prefix, "qwen");
+        assert_eq!((shard.index, shard.total), (2, 3));
+    }
+
+    #[test]
+    fn finds_sibling_shards_in_order() {
+        let dir = std::env::temp_dir().join(format!("oxide-gguf-shards-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for name in [
+            "m-00002-of-00002.gguf",
+            "m-00001-of-00002.gguf",
+            "other-00001-of-00002.gguf",
+        ] {
+            fs::write(dir.join(name), b"").unwrap();
+        }
+
+        let shards = find_gguf_shards(&dir.join("m-00001-of-00002.gguf"));
+        let names: Vec<_> = shards
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, ["m-00001-of-00002.gguf", "m-00002-of-00002.gguf"]);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    fn scanned_model(name: &str, size: u64, ctx: Option<u64>, quant: &str) -> ModelInfo {
+        serde_json::from_value(serde_json::json!({
+            "name": name,
+            "path": format!("/models/{name}.gguf"),
+            "file_size": size,
+            "format": "gguf",
+            "architecture": "qwen3",
+            "context_length": ctx,
+            "quantization": quant,
+            "candle_compatible": true,
+            "validation_status": { "level": "ok" },
+            "created_at": "2024-01-01T00:00:00Z",
+            "metadata": {
+                "format_version": 3,
+                "alignment": 32,
+                "tensor_count": 1,
+                "metadata_kv_count": 0
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn scan_filters_and_sorting() {
+        const GB: u64 = 1024 * 1024 * 1024;
+        let models = vec![
+            scanned_model("QwQ-32B", 20 * GB, Some(32768), "Q4_K_M"),
+            scanned_model("qwen3-4b", 3 * GB, Some(40960), "Q8_0"),
+            scanned_model("DeepSeek-R1-Distill", 5 * GB, None, "Q4_K_M"),
+        ];
+
+        let filters = ModelScanFilters {
+            quantization: Some("q4_k_m".into()),
+            has_thinking: Some(true),
+            sort_by: SortField::Size,
+            ..Default::default()
+        };
+        let names: Vec<_> = apply_scan_filters(models.clone(), &filters)
+            .into_iter()
+            .map(|m| m.name)
+            .collect();
+        assert_eq!(names, ["DeepSeek-R1-Distill", "QwQ-32B"]);
+
+        let filters = ModelScanFilters {
+            min_context_length: Some(32768),
+            max_file_size_gb: Some(10.0),
+            ..Default::default()
+        };
+        let names: Vec<_> = apply_scan_filters(models, &filters)
+            .into_iter()
+            .map(|m| m.name)
+            .collect();
+        assert_eq!(names, ["qwen3-4b"]);
+
+        for name in [
+            "Qwen3-4B-Thinking-2507",
+            "Phi-4-reasoning-plus",
+            "deepseek-r1-0528",
+        ] {
+            assert!(
+                is_thinking_model(&scanned_model(name, 1, None, "Q4_K_M")),
+                "{name}"
+            );
+        }
+        for name in ["gemma-3-4b-r10", "llama-3.2-1b-instruct", "unthinkable-7b"] {
+            assert!(
+                !is_thinking_model(&scanned_model(name, 1, None, "Q4_K_M")),
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn parses_gating_type() {
+        use serde_json::json;
+        assert_eq!(parse_gating_type(None), None);
+        assert_eq!(parse_gating_type(Some(&json!(false))), None);
+        assert_eq!(
+            parse_gating_type(Some(&json!("manual"))).as_deref(),
+            Some("manual")
+        );
+        assert_eq!(
+            parse_gating_type(Some(&json!(true))).as_deref(),
+            Some("auto")
+        );
+    }
+
+    #[test]
+    fn estimates_quantization_vram() {
+        assert_eq!(parse_parameter_count("7B"), Some(7.0));
+        assert_eq!(parse_parameter_count("500M"), Some(0.5));
+        assert_eq!(parse_parameter_count("8x7B"), Some(56.0));
+        assert_eq!(parse_parameter_count("large"), None);
+
+        let options = estimate_quantization_options(8.0, 8192);
+        let vram = |name: &str| options.iter().find(|o| o.0 == name).unwrap().1;
+        // 8B Q4_K_M: ~4.5 GB weights + 1 GB KV cache + overhead
+        assert_eq!(vram("Q4_K_M"), 6.0);
+        assert!(vram("Q8_0") < vram("F16"));
+    }
+
+    #[test]
+    fn parses_model_commits_and_snapshot_sha() {
+        use serde_json::json;
+        let commits = parse_model_commits(&[
+            json!({
+                "id": "abc123",
+                "title": "Upload Q4_K_M",
+                "date": "2025-01-02T03:04:05.000Z",
+                "authors": [{ "user": "qwen" }]
+            }),
+            json!({ "title": "no id" }),
+        ]);
+        assert_eq!(
+            commits,
+            vec![ModelCommit {
+                sha: "abc123".to_string(),
+                message: "Upload Q4_K_M".to_string(),
+                created_at: "2025-01-02T03:04:05.000Z".to_string(),
+                author: Some("qwen".to_string()),
+            }]
+        );
+
+        let pointer = Path::new("hub")
+            .join("models--Qwen--Qwen3-4B-GGUF")
+            .join("snapshots")
+            .join("abc123")
+            .join("model.gguf");
+        assert_eq!(snapshot_commit_sha(&pointer).as_deref(), Some("abc123"));
+        assert_eq!(snapshot_commit_sha(Path::new("model.gguf")), None);
+    }
+
+    #[test]
+    fn detects_capabilities_from_names_and_companions() {
+        let coder = scanned_model("Qwen2.5-Coder-7B-Instruct", 1, None, "Q4_K_M");
+        let caps = detect_capabilities(&coder, &[]);
+        assert!(caps.supports_code && !caps.supports_vision && !caps.embedding_only);
+
+        let vl = scanned_model("qwen3-8b", 1, None, "Q4_K_M");
+        assert!(!detect_capabilities(&vl, &[]).supports_vision);
+        let companions = vec!["mmproj-qwen3-8b-f16.gguf".to_string()];
+        assert!(detect_capabilities(&vl, &companions).supports_vision);
+        // Projector of another model in a shared folder
+        let foreign = vec!["mmproj-gemma-3-4b-f16.gguf".to_string()];
+        assert!(!detect_capabilities(&vl, &foreign).supports_vision);
+        // Repo-wide projector only counts inside that repo
+        let generic = vec!["mmproj-F16.gguf".to_string()];
+        assert!(!detect_capabilities(&vl, &generic).supports_vision);
+        let mut gemma = scanned_model("gemma-3-4b-it-Q4_K_M", 1, None, "Q4_K_M");
+        gemma.source_repo_id = Some("unsloth/gemma-3-4b-it-GGUF".into());
+        assert!(detect_capabilities(&gemma, &generic).supports_vision);
+
+        for name in [
+            "starcoder2-15b",
+            "codellama-7b",
+            "Codestral-22B",
+            "deepseek-coder-v2",
+        ] {
+            let model = scanned_model(name, 1, None, "Q4_K_M");
+            assert!(detect_capabilities(&model, &[]).supports_code, "{name}");
+        }
+        for name in ["t5-encoder-base", "whisper-decoder", "barcode-reader-1b"] {
+            let model = scanned_model(name, 1, None, "Q4_K_M");
+            assert!(!detect_capabilities(&model, &[]).supports_code, "{name}");
+        }
+
+        let mut tools = scanned_model("qwen3-4b", 1, None, "Q8_0");
+        tools.metadata.tokenizer_tokens = Some(vec!["<tool_call>".into(), "<think>".into()]);
+        let caps = detect_capabilities(&tools, &[]);
+        assert!(caps.supports_function_calling && caps.supports_thinking);
+
+        let embed = scanned_model("nomic-embed-text-v1.5", 1, None, "F16");
+        let caps = detect_capabilities(&embed, &companions);
+        assert_eq!(
+            caps,
+            ModelCapabilities {
+                embedding_only: true,
+                ..ModelCapabilities::default()
+            }
+        );
+    }
+
+    #[test]
+    fn inside_any_dir_uses_canonical_paths() {
+        let root = std::env::temp_dir().join(format!("oxide-models-dirs-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("a")).unwrap();
+        fs::create_dir_all(root.join("b")).unwrap();
+        let model = root.join("a").join("model.gguf");
+        fs::write(&model, b"").unwrap();
+
+        let dirs = vec![root.join("b").to_string_lossy().into_owned()];
+        assert!(!is_inside_any_dir(&model, &dirs));
+        let dirs = vec![root.join("a").to_string_lossy().into_owned()];
+        assert!(is_inside_any_dir(
+            &root.join("b").join("..").join("a").join("model.gguf"),
+            &dirs
+        ));
+
+        let _ = fs::remove_dir_all(root);
+    }
+}
@@ -1,13 +1,16 @@
 pub mod commands;
 pub mod device;
 pub mod download_manager;
+pub mod embedding_cache;
 pub mod local_models;
 pub mod model_cards;
+pub mod model_import;
 pub mod model_loading;
 pub mod model_manager;
 pub mod openai_server;
 pub mod performance_api;
 pub mod prefix_cache_api;
+pub mod scan_cache;
 pub mod template;
 
 pub use commands::*;
@@ -17,6 +20,7 @@ pub use local_models::{
 };
 pub use model_cards::{download_model_card_format, get_model_cards};
 pub use performance_api::{
-    clear_performance_metrics, get_average_duration, get_memory_usage, get_performance_metrics,
-    get_startup_metrics, get_system_usage,
+    clear_performance_metrics, get_average_duration, get_load_timing_reports,
+    get_load_timing_settings, get_memory_usage, get_model_memory_stats, get_performance_dashboard,
+    get_performance_metrics, get_startup_metrics, get_system_usage, set_load_timing_settings,
 };
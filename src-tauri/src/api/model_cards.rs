@@ -1,6 +1,6 @@
 use crate::api::download_manager::{StartDownloadRequest, start_model_download};
 use crate::api::model_manager::manifest::{
-    DownloadManifest, infer_quantization_from_label, save_manifest,
+    CURRENT_MANIFEST_VERSION, DownloadManifest, infer_quantization_from_label, save_manifest,
 };
 use crate::log_load;
 use chrono::Utc;
@@ -147,7 +147,7 @@ pub async fn download_model_card_format(
         ModelCardFormat::Safetensors => infer_quantization_from_label(&repo_name),
     };
     let manifest = DownloadManifest {
-        version: 1,
+        version: CURRENT_MANIFEST_VERSION,
         repo_id: format_repo_id.clone(),
         repo_name: repo_name.clone(),
         publisher: publisher.clone(),
@@ -155,6 +155,11 @@ pub async fn download_model_card_format(
         quantization: manifest_quantization,
         card_id: Some(card.id.clone()),
         card_name: Some(card.name.clone()),
+        sha256: None,
+        file_size: None,
+        tensor_count: None,
+        revision: None,
+        commit_sha: None,
         downloaded_at: Utc::now().to_rfc3339(),
     };
 
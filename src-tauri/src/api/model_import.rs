@@ -0,0 +1,326 @@
+//! Импорт библиотек моделей из других приложений (Ollama, LM Studio).
+//!
+//! GGUF-файлы переносятся в первый каталог из настроек хранения моделей:
+//! жёсткой ссылкой, если каталоги на одном томе, иначе копированием. Рядом
+//! сохраняется манифест с источником, после чего `ModelInfo` строится тем же
+//! путём, что и при сканировании.
+
+use crate::api::local_models::{ModelInfo, build_model_info, is_secondary_gguf_shard};
+use crate::api::model_manager::manifest::{
+    CURRENT_MANIFEST_VERSION, DownloadManifest, infer_quantization_from_label, save_manifest,
+};
+use crate::core::state::ModelState;
+use chrono::Utc;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, async_runtime};
+
+const OLLAMA_MODEL_MEDIA_TYPE: &str = "application/vnd.ollama.image.model";
+
+/// Файл модели, найденный в чужой библиотеке.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ImportCandidate {
+    source: PathBuf,
+    /// Путь относительно каталога моделей
+    relative_dest: PathBuf,
+    repo_id: String,
+    sha256: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaManifest {
+    #[serde(default)]
+    layers: Vec<OllamaLayer>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaLayer {
+    #[serde(rename = "mediaType")]
+    media_type: String,
+    digest: String,
+}
+
+fn files_under(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(current) = stack.pop() {
+        let Ok(entries) = fs::read_dir(&current) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                stack.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// Ollama: `manifests/<registry>/<namespace>/<model>/<tag>` ссылается на
+/// `blobs/sha256-<digest>` — сам GGUF без расширения.
+fn ollama_candidates(models_dir: &Path) -> Result<Vec<ImportCandidate>, String> {
+    let manifests_dir = models_dir.join("manifests");
+    let blobs_dir = models_dir.join("blobs");
+    if !manifests_dir.is_dir() || !blobs_dir.is_dir() {
+        return Err(format!(
+            "{} is not an Ollama models directory (expected manifests/ and blobs/)",
+            models_dir.display()
+        ));
+    }
+
+    let mut candidates = Vec::new();
+    for manifest_path in files_under(&manifests_dir) {
+        let Ok(relative) = manifest_path.strip_prefix(&manifests_dir) else {
+            continue;
+        };
+        let parts: Vec<String> = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect();
+        let [_registry, namespace, model, tag] = parts.as_slice() else {
+            continue;
+        };
+        let manifest: OllamaManifest = match fs::read_to_string(&manifest_path)
+            .map_err(|e| e.to_string())
+            .and_then(|data| serde_json::from_str(&data).map_err(|e| e.to_string()))
+        {
+            Ok(manifest) => manifest,
+            Err(err) => {
+                log::warn!(
+                    "Skipping Ollama manifest {}: {}",
+                    manifest_path.display(),
+                    err
+                );
+                continue;
+            }
+        };
+        let Some(layer) = manifest
+            .layers
+            .iter()
+            .find(|layer| layer.media_type == OLLAMA_MODEL_MEDIA_TYPE)
+        else {
+            continue;
+        };
+        let source = blobs_dir.join(layer.digest.replace(':', "-"));
+        if !source.is_file() {
+            log::warn!("Ollama blob {} is missing", source.display());
+            continue;
+        }
+        let repo_id = if namespace == "library" {
+            format!("ollama/{model}")
+        } else {
+            format!("{namespace}/{model}")
+        };
+        candidates.push(ImportCandidate {
+            source,
+            relative_dest: PathBuf::from("ollama").join(format!("{model}-{tag}.gguf")),
+            repo_id,
+            sha256: layer.digest.strip_prefix("sha256:").map(str::to_string),
+        });
+    }
+    Ok(candidates)
+}
+
+/// LM Studio: `<publisher>/<repo>/<file>.gguf`, где `publisher/repo` — id репозитория на HF.
+fn lm_studio_candidates(models_dir: &Path) -> Result<Vec<ImportCandidate>, String> {
+    if !models_dir.is_dir() {
+        return Err(format!("Path is not a directory: {}", models_dir.display()));
+    }
+    let candidates = files_under(models_dir)
+        .into_iter()
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("gguf"))
+        })
+        .filter_map(|source| {
+            let relative_dest = source.strip_prefix(models_dir).ok()?.to_path_buf();
+            let mut parts = relative_dest.components();
+            let publisher = parts.next()?.as_os_str().to_str()?.to_string();
+            let repo = parts.next()?.as_os_str().to_str()?.to_string();
+            // Файл должен лежать внутри `<publisher>/<repo>/`
+            parts.next()?;
+            Some(ImportCandidate {
+                source,
+                relative_dest,
+                repo_id: format!("{publisher}/{repo}"),
+                sha256: None,
+            })
+        })
+        .collect();
+    Ok(candidates)
+}
+
+/// Жёсткая ссылка не занимает места и не требует прав администратора на Windows,
+/// но работает только в пределах одного тома; иначе копируем.
+fn link_or_copy(source: &Path, dest: &Path) -> Result<(), String> {
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    if fs::hard_link(source, dest).is_ok() {
+        return Ok(());
+    }
+    fs::copy(source, dest)
+        .map(|_| ())
+        .map_err(|e| format!("Failed to copy {}: {}", source.display(), e))
+}
+
+fn import_candidate(candidate: &ImportCandidate, models_dir: &Path) -> Result<PathBuf, String> {
+    let dest = models_dir.join(&candidate.relative_dest);
+    if !dest.exists() {
+        link_or_copy(&candidate.source, &dest)?;
+    }
+
+    let file_name = dest
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default()
+        .to_string();
+    let (publisher, repo_name) = candidate
+        .repo_id
+        .split_once('/')
+        .map(|(publisher, name)| (publisher.to_string(), name.to_string()))
+        .unwrap_or_else(|| ("local".to_string(), candidate.repo_id.clone()));
+    let manifest = DownloadManifest {
+        version: CURRENT_MANIFEST_VERSION,
+        repo_id: candidate.repo_id.clone(),
+        repo_name,
+        publisher,
+        format: "gguf".to_string(),
+        quantization: infer_quantization_from_label(&file_name),
+        card_id: None,
+        card_name: None,
+        sha256: candidate.sha256.clone(),
+        file_size: fs::metadata(&dest).ok().map(|m| m.len()),
+        tensor_count: None,
+        revision: None,
+        commit_sha: None,
+        downloaded_at: Utc::now().to_rfc3339(),
+    };
+    save_manifest(&dest, &manifest)?;
+    Ok(dest)
+}
+
+fn import_all(candidates: Vec<ImportCandidate>, models_dir: &Path) -> Vec<ModelInfo> {
+    let mut models = Vec::new();
+    for candidate in candidates {
+        let dest = match import_candidate(&candidate, models_dir) {
+            Ok(dest) => dest,
+            Err(err) => {
+                log::warn!("Failed to import {}: {}", candidate.source.display(), err);
+                continue;
+            }
+        };
+        // Дополнительные части split-моделей копируются, но отдельной моделью не считаются
+        if is_secondary_gguf_shard(&dest) {
+            continue;
+        }
+        match build_model_info(&dest) {
+            Ok(Some(info)) => models.push(info),
+            Ok(None) => log::info!("Imported model is not supported: {}", dest.display()),
+            Err(err) => log::warn!("Failed to read imported model {}: {}", dest.display(), err),
+        }
+    }
+    models
+}
+
+/// Каталог, выбранный в UI, иначе первый каталог из настроек хранилища.
+fn target_models_dir(app: &AppHandle, destination_dir: Option<String>) -> Result<PathBuf, String> {
+    if let Some(dir) = destination_dir.filter(|dir| !dir.trim().is_empty()) {
+        return Ok(PathBuf::from(dir));
+    }
+    ModelState::load_models_storage_settings(app)?
+        .models_dirs
+        .first()
+        .map(PathBuf::from)
+        .ok_or_else(|| "Models directory is not configured".to_string())
+}
+
+/// Command: import GGUF models from an Ollama `models` directory (usually `~/.ollama/models`).
+#[tauri::command]
+pub async fn import_models_from_ollama(
+    app: AppHandle,
+    ollama_models_dir: String,
+    destination_dir: Option<String>,
+) -> Result<Vec<ModelInfo>, String> {
+    let models_dir = target_models_dir(&app, destination_dir)?;
+    let source = PathBuf::from(ollama_models_dir);
+    async_runtime::spawn_blocking(move || Ok(import_all(ollama_candidates(&source)?, &models_dir)))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Command: import GGUF models from LM Studio's `models` directory (usually `~/.lmstudio/models`).
+#[tauri::command]
+pub async fn import_lm_studio_models(
+    app: AppHandle,
+    lm_studio_dir: String,
+    destination_dir: Option<String>,
+) -> Result<Vec<ModelInfo>, String> {
+    let models_dir = target_models_dir(&app, destination_dir)?;
+    let source = PathBuf::from(lm_studio_dir);
+    async_runtime::spawn_blocking(move || {
+        Ok(import_all(lm_studio_candidates(&source)?, &models_dir))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("oxide-import-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn finds_ollama_and_lm_studio_models() {
+        let ollama = temp_dir("ollama");
+        let manifest_dir = ollama
+            .join("manifests")
+            .join("registry.ollama.ai")
+            .join("library")
+            .join("qwen3");
+        fs::create_dir_all(&manifest_dir).unwrap();
+        fs::create_dir_all(ollama.join("blobs")).unwrap();
+        fs::write(ollama.join("blobs").join("sha256-abc"), b"GGUF").unwrap();
+        fs::write(
+            manifest_dir.join("4b"),
+            r#"{"layers":[
+                {"mediaType":"application/vnd.ollama.image.template","digest":"sha256:tpl"},
+                {"mediaType":"application/vnd.ollama.image.model","digest":"sha256:abc"}
+            ]}"#,
+        )
+        .unwrap();
+        let found = ollama_candidates(&ollama).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].repo_id, "ollama/qwen3");
+        assert_eq!(
+            found[0].relative_dest,
+            Path::new("ollama").join("qwen3-4b.gguf")
+        );
+        assert_eq!(found[0].sha256.as_deref(), Some("abc"));
+
+        let lm_studio = temp_dir("lmstudio");
+        let repo_dir = lm_studio.join("Qwen").join("Qwen3-4B-GGUF");
+        fs::create_dir_all(&repo_dir).unwrap();
+        fs::write(repo_dir.join("Qwen3-4B-Q4_K_M.gguf"), b"GGUF").unwrap();
+        fs::write(lm_studio.join("stray.gguf"), b"GGUF").unwrap();
+        let found = lm_studio_candidates(&lm_studio).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].repo_id, "Qwen/Qwen3-4B-GGUF");
+
+        assert!(ollama_candidates(&lm_studio).is_err());
+        let _ = fs::remove_dir_all(ollama);
+        let _ = fs::remove_dir_all(lm_studio);
+    }
+}
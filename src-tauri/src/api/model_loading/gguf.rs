@@ -8,7 +8,7 @@ use crate::core::tokenizer::{
 };
 use crate::generate::cancel::CANCEL_LOADING;
 
-use crate::models::registry::{detect_arch, get_model_factory};
+use crate::models::registry::{detect_arch, get_model_factory, remap_custom_gguf_metadata};
 use crate::{log_load, log_template, log_template_error};
 use candle::quantized::{gguf_file, GgmlDType};
 use std::collections::HashSet;
@@ -24,7 +24,7 @@ pub fn load_gguf_model(
     request_context_length: usize, // Shadowed later
     device_pref: Option<crate::core::types::DevicePreference>,
 ) -> Result<(), String> {
-    let dbg = LoadDebugCtx::new();
+    let dbg = LoadDebugCtx::new().with_app(app);
     // Создаём трекер загрузки модели
     let tracker_result = tokio::runtime::Runtime::new()
         .map_err(|e| e.to_string())?
@@ -82,7 +82,7 @@ pub fn load_gguf_model(
     tracker.start_stage("read_header");
     dbg.stage_begin("read_header");
     let read_header_start = std::time::Instant::now();
-    let content = gguf_file::Content::read(&mut file).map_err(|e| {
+    let mut content = gguf_file::Content::read(&mut file).map_err(|e| {
         let error_msg = e.with_path(PathBuf::from(model_path.clone())).to_string();
 
         // Улучшаем сообщение об ошибке для пользователя
@@ -187,6 +187,7 @@ pub fn load_gguf_model(
         emit_load_progress_debug(&dbg, app, "detect_arch", 38, None, false, Some(&err));
         err
     })?;
+    remap_custom_gguf_metadata(&mut content.metadata, arch);
 
     // Проверяем наличие неподдерживаемых типов данных в тензорах
     check_supported_dtypes(&content).map_err(|dtype_error| {
@@ -492,9 +493,14 @@ pub fn load_gguf_model(
         .map(|m| m.len() as f64 / (1024.0 * 1024.0))
         .unwrap_or(0.0);
 
+    let timing_report = dbg.timing_report(guard.model_path.clone().unwrap_or_default());
+    let monitor = guard.performance_monitor.clone();
     let metrics = tokio::runtime::Runtime::new()
         .map_err(|e| e.to_string())?
-        .block_on(async { tracker.finish(model_size_mb).await });
+        .block_on(async {
+            monitor.record_load_report(timing_report).await;
+            tracker.finish(model_size_mb).await
+        });
 
     log_load!(
         "Метрики загрузки: total_time={}ms, memory_delta={:.2}MB, stages={:?}",
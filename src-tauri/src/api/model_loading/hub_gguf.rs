@@ -1,4 +1,4 @@
-use super::{LoadDebugCtx, emit_load_progress_debug};
+use super::{LoadDebugCtx, emit_load_progress_debug, record_load_report};
 use crate::core::state::ModelState;
 use crate::core::tokenizer::{
     extract_chat_template, find_chat_template_in_metadata, mark_special_chat_tokens,
@@ -6,7 +6,7 @@ use crate::core::tokenizer::{
 };
 use crate::generate::cancel::CANCEL_LOADING;
 
-use crate::models::registry::{detect_arch, get_model_factory};
+use crate::models::registry::{detect_arch, get_model_factory, remap_custom_gguf_metadata};
 use crate::{log_hub, log_load, log_template};
 use candle::quantized::gguf_file;
 use std::collections::HashSet;
@@ -22,7 +22,7 @@ pub fn load_hub_gguf_model(
     context_length: usize,
     _device_pref: Option<crate::core::types::DevicePreference>,
 ) -> Result<(), String> {
-    let dbg = LoadDebugCtx::new();
+    let dbg = LoadDebugCtx::new().with_app(app);
     emit_load_progress_debug(
         &dbg,
         app,
@@ -40,10 +40,13 @@ pub fn load_hub_gguf_model(
     let api = api.repo(repo);
 
     // Скачиваем GGUF-файл в кэш и открываем
+    dbg.stage_begin("hub_get");
+    let hub_get_start = std::time::Instant::now();
     let model_path = api.get(&filename).map_err(|e| {
         emit_load_progress_debug(&dbg, app, "hub_get", 10, None, false, Some(&e.to_string()));
         format!("hf_hub get {} failed: {}", filename, e)
     })?;
+    dbg.stage_end("hub_get", hub_get_start.elapsed());
     log_hub!("gguf cached at {}", model_path.display());
     emit_load_progress_debug(
         &dbg,
@@ -55,7 +58,7 @@ pub fn load_hub_gguf_model(
         None,
     );
     let mut file = File::open(&model_path).map_err(|e| e.to_string())?;
-    let content = gguf_file::Content::read(&mut file).map_err(|e| {
+    let mut content = gguf_file::Content::read(&mut file).map_err(|e| {
         let msg = e.with_path(model_path.clone()).to_string();
         emit_load_progress_debug(&dbg, app, "read_header", 25, None, false, Some(&msg));
         msg
@@ -118,6 +121,7 @@ pub fn load_hub_gguf_model(
         emit_load_progress_debug(&dbg, app, "detect_arch", 45, None, false, Some(&err));
         err
     })?;
+    remap_custom_gguf_metadata(&mut content.metadata, arch);
 
     // Проверяем наличие неподдерживаемых типов данных в тензорах
     check_supported_dtypes(&content).map_err(|dtype_error| {
@@ -180,6 +184,8 @@ pub fn load_hub_gguf_model(
     // Use the model factory to build the model
     emit_load_progress_debug(&dbg, app, "build_model", 60, None, false, None);
     // Build model
+    dbg.stage_begin("build_model_backend");
+    let build_start = std::time::Instant::now();
     let mut model_backend = get_model_factory()
         .build_from_gguf(
             arch,
@@ -193,6 +199,7 @@ pub fn load_hub_gguf_model(
             emit_load_progress_debug(&dbg, app, "build_model", 65, None, false, Some(&e));
             format!("Failed to build model: {}", e)
         })?;
+    dbg.stage_end("build_model_backend", build_start.elapsed());
 
     // Если есть JSON-конфигурация в guard.model_config_json — применим её
     if let Some(gg) = guard.model_config_json.as_ref()
@@ -267,6 +274,7 @@ pub fn load_hub_gguf_model(
         false,
         None,
     );
+    record_load_report(&dbg, guard, &repo_id);
     emit_load_progress_debug(&dbg, app, "complete", 100, Some("Готово"), true, None);
 
     Ok(())
@@ -2,16 +2,35 @@ pub mod context_algo;
 pub mod context_settings;
 pub mod gguf;
 pub mod hub_gguf;
+pub mod retry;
 pub mod safetensors;
 
 use serde::Serialize;
 use tauri::Emitter;
 
+use crate::core::performance::LoadTimingReport;
+use crate::core::state::ModelState;
+use crate::core::types::{LoadTimingSettings, STAGE_WARN_THRESHOLD_MS};
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 static LOAD_SEQ: AtomicU64 = AtomicU64::new(0);
 
+/// Текущий порог из `LoadTimingSettings` (применяется к следующим загрузкам).
+static STAGE_WARN_THRESHOLD: AtomicU64 = AtomicU64::new(STAGE_WARN_THRESHOLD_MS);
+
+pub fn apply_load_timing_settings(settings: &LoadTimingSettings) {
+    STAGE_WARN_THRESHOLD.store(settings.stage_warn_threshold_ms, Ordering::Relaxed);
+    crate::models::common::shards::set_parallel_prefetch(settings.parallel_load_shards);
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadSlowStageEvent {
+    pub stage: String,
+    pub duration_ms: u64,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct LoadProgressEvent {
     pub stage: String,
@@ -26,6 +45,10 @@ pub struct LoadDebugCtx {
     start: Instant,
     load_id: u64,
     enabled: bool,
+    /// Длительности завершённых стадий (записываются всегда, не только в debug)
+    stages: Arc<Mutex<Vec<(String, u64)>>>,
+    app: Option<tauri::AppHandle>,
+    stage_warn_threshold_ms: u64,
 }
 
 impl LoadDebugCtx {
@@ -35,6 +58,24 @@ impl LoadDebugCtx {
             start: Instant::now(),
             load_id: LOAD_SEQ.fetch_add(1, Ordering::Relaxed) + 1,
             enabled,
+            stages: Arc::new(Mutex::new(Vec::new())),
+            app: None,
+            stage_warn_threshold_ms: STAGE_WARN_THRESHOLD.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Привязать AppHandle для события `load_slow_stage`.
+    pub fn with_app(mut self, app: &tauri::AppHandle) -> Self {
+        self.app = Some(app.clone());
+        self
+    }
+
+    /// Итоговый отчёт о длительности стадий загрузки.
+    pub fn timing_report(&self, model_id: impl Into<String>) -> LoadTimingReport {
+        LoadTimingReport {
+            model_id: model_id.into(),
+            total_ms: self.elapsed_ms() as u64,
+            stages: self.stages.lock().map(|s| s.clone()).unwrap_or_default(),
         }
     }
 
@@ -64,6 +105,23 @@ impl LoadDebugCtx {
     }
 
     pub fn stage_end(&self, stage: &str, duration: Duration) {
+        let duration_ms = duration.as_millis() as u64;
+        if let Ok(mut stages) = self.stages.lock() {
+            stages.push((stage.to_string(), duration_ms));
+        }
+        if duration_ms > self.stage_warn_threshold_ms {
+            log::warn!("Model load stage '{}' took {}ms", stage, duration_ms);
+            if let Some(app) = &self.app {
+                let _ = app.emit(
+                    "load_slow_stage",
+                    LoadSlowStageEvent {
+                        stage: stage.to_string(),
+                        duration_ms,
+                    },
+                );
+            }
+        }
+
         if !self.enabled {
             return;
         }
@@ -84,6 +142,12 @@ impl Default for LoadDebugCtx {
     }
 }
 
+/// Сохранить отчёт о стадиях загрузки в мониторе производительности.
+pub(crate) fn record_load_report(dbg: &LoadDebugCtx, guard: &ModelState, model_id: &str) {
+    let report = dbg.timing_report(model_id);
+    tauri::async_runtime::block_on(guard.performance_monitor.record_load_report(report));
+}
+
 pub fn emit_load_progress(
     app: &tauri::AppHandle,
     stage: &str,
@@ -0,0 +1,168 @@
+//! Повтор загрузки модели при временных ошибках.
+//!
+//! Типичный случай — антивирус на Windows держит только что скачанный файл
+//! (`os error 32`), или чтение с сетевого диска упирается в таймаут.
+
+use serde::Serialize;
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Всего попыток, включая первую
+    pub max_attempts: usize,
+    /// Подстроки ошибки (без учёта регистра), при которых имеет смысл повторить
+    pub retry_on_patterns: Vec<String>,
+    pub initial_delay_ms: u64,
+    pub backoff_factor: f32,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            retry_on_patterns: [
+                "os error 32",
+                "os error 33",
+                "being used by another process",
+                "resource temporarily unavailable",
+                "timed out",
+                "timeout",
+            ]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+            initial_delay_ms: 500,
+            backoff_factor: 2.0,
+        }
+    }
+}
+
+/// Запись о неудачной попытке для итогового сообщения об ошибке.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct RetryAttempt {
+    pub attempt: usize,
+    pub error: String,
+    /// Пауза перед следующей попыткой; `None` у последней
+    pub delay_ms: Option<u64>,
+}
+
+impl RetryPolicy {
+    pub fn is_transient(&self, error: &str) -> bool {
+        let error = error.to_lowercase();
+        self.retry_on_patterns
+            .iter()
+            .any(|pattern| error.contains(&pattern.to_lowercase()))
+    }
+
+    /// Пауза после попытки `attempt` (с 1).
+    pub fn delay_after(&self, attempt: usize) -> Duration {
+        let factor = self
+            .backoff_factor
+            .max(1.0)
+            .powi(attempt.saturating_sub(1) as i32);
+        Duration::from_millis((self.initial_delay_ms as f64 * factor as f64) as u64)
+    }
+
+    /// Выполняет `op`, повторяя при временных ошибках. `on_retry` вызывается
+    /// перед каждой паузой; `cancelled` прерывает повторы (отмена загрузки).
+    /// При неудаче возвращает последнюю ошибку и историю всех попыток.
+    pub fn run<T>(
+        &self,
+        mut op: impl FnMut(usize) -> Result<T, String>,
+        mut on_retry: impl FnMut(&RetryAttempt),
+        cancelled: impl Fn() -> bool,
+    ) -> Result<T, (String, Vec<RetryAttempt>)> {
+        let mut history = Vec::new();
+        let mut attempt = 1;
+        loop {
+            let error = match op(attempt) {
+                Ok(value) => return Ok(value),
+                Err(error) => error,
+            };
+            let retry = attempt < self.max_attempts && self.is_transient(&error) && !cancelled();
+            if !retry {
+                history.push(RetryAttempt {
+                    attempt,
+                    error: error.clone(),
+                    delay_ms: None,
+                });
+                return Err((error, history));
+            }
+
+            let delay = self.delay_after(attempt);
+            let record = RetryAttempt {
+                attempt,
+                error,
+                delay_ms: Some(delay.as_millis() as u64),
+            };
+            on_retry(&record);
+            history.push(record);
+            std::thread::sleep(delay);
+            attempt += 1;
+        }
+    }
+}
+
+/// Человекочитаемая история попыток для события об ошибке.
+pub fn describe_history(history: &[RetryAttempt]) -> String {
+    history
+        .iter()
+        .map(|a| format!("attempt {}: {}", a.attempt, a.error))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fast_policy() -> RetryPolicy {
+        RetryPolicy {
+            initial_delay_ms: 1,
+            ..RetryPolicy::default()
+        }
+    }
+
+    #[test]
+    fn retries_transient_errors_with_backoff() {
+        let policy = RetryPolicy::default();
+        assert!(
+            policy.is_transient(
+                "Failed to open file: The process cannot access the file (os error 32)"
+            )
+        );
+        assert!(!policy.is_transient("unsupported architecture: mamba"));
+        assert_eq!(policy.delay_after(1), Duration::from_millis(500));
+        assert_eq!(policy.delay_after(3), Duration::from_millis(2000));
+
+        let mut retries = 0;
+        let result = fast_policy().run(
+            |attempt| {
+                if attempt < 3 {
+                    Err("read timed out".to_string())
+                } else {
+                    Ok(attempt)
+                }
+            },
+            |_| retries += 1,
+            || false,
+        );
+        assert_eq!(result, Ok(3));
+        assert_eq!(retries, 2);
+    }
+
+    #[test]
+    fn stops_on_permanent_error_or_exhaustion() {
+        let (error, history) = fast_policy()
+            .run(|_| Err::<(), _>("bad magic".to_string()), |_| {}, || false)
+            .unwrap_err();
+        assert_eq!(error, "bad magic");
+        assert_eq!(history.len(), 1);
+
+        let (_, history) = fast_policy()
+            .run(|_| Err::<(), _>("timeout".to_string()), |_| {}, || false)
+            .unwrap_err();
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[2].delay_ms, None);
+    }
+}
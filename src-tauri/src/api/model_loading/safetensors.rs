@@ -6,7 +6,7 @@
 use hf_hub::{Repo, RepoType, api::sync::Api};
 use std::path::Path;
 
-use super::emit_load_progress;
+use super::{LoadDebugCtx, emit_load_progress, record_load_report};
 use crate::core::device::{device_label, select_device};
 use crate::core::state::ModelState;
 use crate::core::template_registry::match_template;
@@ -28,6 +28,7 @@ pub fn load_local_safetensors_model(
     request_context_length: usize, // Shadowed later
     device_pref: Option<crate::core::types::DevicePreference>,
 ) -> Result<(), String> {
+    let dbg = LoadDebugCtx::new().with_app(app);
     emit_load_progress(
         app,
         "start",
@@ -106,11 +107,14 @@ pub fn load_local_safetensors_model(
     };
 
     // Используем универсальный загрузчик весов для определения списка файлов safetensors
+    dbg.stage_begin("scan_weights");
+    let scan_start = std::time::Instant::now();
     let filenames = local_list_safetensors(model_dir)
         .map_err(|e| format!("Failed to list safetensors files from local path: {}", e))?;
 
     // Validate the local safetensors files
     validate_safetensors_files(&filenames)?;
+    dbg.stage_end("scan_weights", scan_start.elapsed());
     if CANCEL_LOADING.load(Ordering::SeqCst) {
         emit_load_progress(app, "cancel", 44, Some("Отменено"), true, Some("cancelled"));
         return Err("cancelled".into());
@@ -268,8 +272,12 @@ pub fn load_local_safetensors_model(
 
             // Use the model factory to build the model
             emit_load_progress(app, "build_model", 60, None, false, None);
-            match get_model_factory().build_from_safetensors(arch, &filenames, &config, &dev, dtype)
-            {
+            dbg.stage_begin("build_model_backend");
+            let build_start = std::time::Instant::now();
+            let built =
+                get_model_factory().build_from_safetensors(arch, &filenames, &config, &dev, dtype);
+            dbg.stage_end("build_model_backend", build_start.elapsed());
+            match built {
                 Ok(model_backend) => {
                     built_model_opt = Some(model_backend);
                     emit_load_progress(
@@ -311,6 +319,7 @@ pub fn load_local_safetensors_model(
         "local safetensors loaded with ModelBuilder, context_length={}",
         guard.context_length
     );
+    record_load_report(&dbg, guard, &model_path.to_string_lossy());
     emit_load_progress(
         app,
         "finalize",
@@ -333,6 +342,7 @@ pub fn load_hub_safetensors_model(
     request_context_length: usize, // Shadowed later
     device_pref: Option<crate::core::types::DevicePreference>,
 ) -> Result<(), String> {
+    let dbg = LoadDebugCtx::new().with_app(app);
     emit_load_progress(
         app,
         "start",
@@ -407,8 +417,11 @@ pub fn load_hub_safetensors_model(
     );
 
     // Предзагрузим все файлы в кэш (скачать/проверить наличие)
+    dbg.stage_begin("hub_cache");
+    let cache_start = std::time::Instant::now();
     let cached_filenames = hub_cache_safetensors(&api, &filenames)
         .map_err(|e| format!("Failed to cache safetensors files: {}", e))?;
+    dbg.stage_end("hub_cache", cache_start.elapsed());
     emit_load_progress(
         app,
         "hub_cache",
@@ -561,13 +574,17 @@ pub fn load_hub_safetensors_model(
 
             // Use the model factory to build the model
             emit_load_progress(app, "build_model", 70, None, false, None);
-            match get_model_factory().build_from_safetensors(
+            dbg.stage_begin("build_model_backend");
+            let build_start = std::time::Instant::now();
+            let built = get_model_factory().build_from_safetensors(
                 arch,
                 &cached_filenames,
                 &config,
                 &dev,
                 dtype,
-            ) {
+            );
+            dbg.stage_end("build_model_backend", build_start.elapsed());
+            match built {
                 Ok(model_backend) => {
                     built_model_opt = Some(model_backend);
                     emit_load_progress(
@@ -598,6 +615,7 @@ pub fn load_hub_safetensors_model(
     guard.context_length = context_length.max(1);
     guard.model_path = None;
     guard.tokenizer_path = tokenizer_path.map(|p| p.to_string_lossy().to_string());
+    record_load_report(&dbg, guard, &repo_id);
     guard.hub_repo_id = Some(repo_id);
     guard.hub_revision = Some(rev);
     guard.safetensors_files = Some(cached_filenames);
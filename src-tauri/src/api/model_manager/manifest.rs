@@ -1,11 +1,18 @@
 use once_cell::sync::OnceCell;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 pub const MANIFEST_FILE_NAME: &str = ".oxide-manifest.json";
 
+/// Текущая версия формата манифеста.
+/// v2: добавлены `sha256`, `file_size`, `tensor_count`.
+/// v3: добавлены `revision`, `commit_sha`.
+pub const CURRENT_MANIFEST_VERSION: u32 = 3;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DownloadManifest {
     pub version: u32,
@@ -19,9 +26,63 @@ pub struct DownloadManifest {
     pub card_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub card_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_size: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tensor_count: Option<u64>,
+    /// Запрошенная ревизия (ветка или SHA), если не `main`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<String>,
+    /// SHA коммита репозитория, из которого скачан файл
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit_sha: Option<String>,
     pub downloaded_at: String,
 }
 
+/// Пошаговая миграция сырых манифестов старых версий.
+pub struct ManifestMigrator;
+
+impl ManifestMigrator {
+    pub fn migrate(mut raw: JsonValue, target_version: u32) -> Result<DownloadManifest, String> {
+        let obj = raw
+            .as_object_mut()
+            .ok_or_else(|| "Манифест должен быть JSON-объектом".to_string())?;
+        let mut version = obj
+            .get("version")
+            .and_then(JsonValue::as_u64)
+            .map(|v| v as u32)
+            .unwrap_or(1);
+
+        if version > target_version {
+            return Err(format!(
+                "Версия манифеста {version} новее поддерживаемой {target_version}"
+            ));
+        }
+
+        while version < target_version {
+            match version {
+                1 => {
+                    for key in ["sha256", "file_size", "tensor_count"] {
+                        obj.entry(key).or_insert(JsonValue::Null);
+                    }
+                }
+                2 => {
+                    for key in ["revision", "commit_sha"] {
+                        obj.entry(key).or_insert(JsonValue::Null);
+                    }
+                }
+                other => return Err(format!("Нет миграции для версии манифеста {other}")),
+            }
+            version += 1;
+        }
+        obj.insert("version".to_string(), JsonValue::from(version));
+
+        serde_json::from_value(raw).map_err(|e| format!("Не удалось разобрать манифест: {e}"))
+    }
+}
+
 pub fn resolve_manifest_path(target: &Path) -> std::path::PathBuf {
     if target.is_dir() {
         target.join(MANIFEST_FILE_NAME)
@@ -37,10 +98,17 @@ pub fn resolve_manifest_path(target: &Path) -> std::path::PathBuf {
 }
 
 pub fn save_manifest(target: &Path, manifest: &DownloadManifest) -> Result<(), String> {
-    let path = resolve_manifest_path(target);
-    let serialized = serde_json::to_string_pretty(manifest)
+    write_manifest_file(&resolve_manifest_path(target), manifest)
+}
+
+fn write_manifest_file(path: &Path, manifest: &DownloadManifest) -> Result<(), String> {
+    let manifest = DownloadManifest {
+        version: CURRENT_MANIFEST_VERSION,
+        ..manifest.clone()
+    };
+    let serialized = serde_json::to_string_pretty(&manifest)
         .map_err(|e| format!("Не удалось сериализовать манифест: {e}"))?;
-    fs::write(&path, serialized)
+    fs::write(path, serialized)
         .map_err(|e| format!("Не удалось сохранить манифест {}: {e}", path.display()))
 }
 
@@ -62,7 +130,80 @@ pub fn load_manifest(target: &Path) -> Option<DownloadManifest> {
             ))
         })
         .ok()?;
-    serde_json::from_str(&data).ok()
+    let raw: JsonValue = serde_json::from_str(&data).ok()?;
+    let mut manifest = ManifestMigrator::migrate(raw, CURRENT_MANIFEST_VERSION).ok()?;
+    if manifest.file_size.is_none() && target.is_file() {
+        manifest.file_size = fs::metadata(target).ok().map(|m| m.len());
+    }
+    Some(manifest)
+}
+
+/// Рекурсивно находит файлы манифестов в каталоге.
+/// Каждый каталог обходится один раз (по канонному пути), поэтому циклы
+/// символических ссылок не приводят к бесконечному обходу.
+pub fn find_manifest_files(dir: &Path) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let mut visited = HashSet::new();
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(current) = stack.pop() {
+        let key = fs::canonicalize(&current).unwrap_or_else(|_| current.clone());
+        if !visited.insert(key) {
+            continue;
+        }
+        let Ok(entries) = fs::read_dir(&current) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                stack.push(path);
+            } else if path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.ends_with(MANIFEST_FILE_NAME))
+            {
+                found.push(path);
+            }
+        }
+    }
+    found
+}
+
+fn read_manifest_version(path: &Path) -> Option<u32> {
+    let data = fs::read_to_string(path).ok()?;
+    let raw: JsonValue = serde_json::from_str(&data).ok()?;
+    Some(raw.get("version").and_then(JsonValue::as_u64).unwrap_or(1) as u32)
+}
+
+/// Количество манифестов каждой версии в указанных каталогах.
+pub fn manifest_version_counts(dirs: &[PathBuf]) -> HashMap<u32, u32> {
+    let mut stats = HashMap::new();
+    for path in dirs.iter().flat_map(|dir| find_manifest_files(dir)) {
+        if let Some(version) = read_manifest_version(&path) {
+            *stats.entry(version).or_insert(0) += 1;
+        }
+    }
+    stats
+}
+
+/// Переписывает устаревшие манифесты в текущую версию. Возвращает число обновлённых.
+pub fn migrate_manifests_in(dirs: &[PathBuf]) -> usize {
+    let mut migrated = 0;
+    for path in dirs.iter().flat_map(|dir| find_manifest_files(dir)) {
+        if read_manifest_version(&path).is_none_or(|v| v >= CURRENT_MANIFEST_VERSION) {
+            continue;
+        }
+        let result = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|data| serde_json::from_str(&data).map_err(|e| e.to_string()))
+            .and_then(|raw| ManifestMigrator::migrate(raw, CURRENT_MANIFEST_VERSION))
+            .and_then(|manifest| write_manifest_file(&path, &manifest));
+        match result {
+            Ok(()) => migrated += 1,
+            Err(err) => log::warn!("Failed to migrate manifest {}: {}", path.display(), err),
+        }
+    }
+    migrated
 }
 
 pub fn infer_quantization_from_label(label: &str) -> Option<String> {
@@ -85,3 +226,70 @@ fn canonicalize_quantization_label(raw: &str) -> String {
     }
     value
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn v1_manifest() -> JsonValue {
+        json!({
+            "version": 1,
+            "repo_id": "Qwen/Qwen3-4B-GGUF",
+            "repo_name": "Qwen3-4B-GGUF",
+            "publisher": "Qwen",
+            "format": "gguf",
+            "downloaded_at": "2024-01-01T00:00:00Z"
+        })
+    }
+
+    #[test]
+    fn migrates_v1_to_current() {
+        let manifest = ManifestMigrator::migrate(v1_manifest(), CURRENT_MANIFEST_VERSION).unwrap();
+        assert_eq!(manifest.version, CURRENT_MANIFEST_VERSION);
+        assert_eq!(manifest.repo_id, "Qwen/Qwen3-4B-GGUF");
+        assert!(manifest.sha256.is_none());
+        assert!(manifest.commit_sha.is_none());
+
+        assert!(ManifestMigrator::migrate(v1_manifest(), 0).is_err());
+        assert!(ManifestMigrator::migrate(json!([]), CURRENT_MANIFEST_VERSION).is_err());
+    }
+
+    #[test]
+    fn migrates_manifest_files_on_disk() {
+        let dir = std::env::temp_dir().join(format!("oxide-manifests-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        let old = dir.join(format!("model.gguf{MANIFEST_FILE_NAME}"));
+        fs::write(&old, v1_manifest().to_string()).unwrap();
+        fs::write(
+            dir.join("nested").join(MANIFEST_FILE_NAME),
+            v1_manifest().to_string(),
+        )
+        .unwrap();
+
+        let dirs = vec![dir.clone()];
+        assert_eq!(manifest_version_counts(&dirs).get(&1), Some(&2));
+        assert_eq!(migrate_manifests_in(&dirs), 2);
+        assert_eq!(
+            manifest_version_counts(&dirs).get(&CURRENT_MANIFEST_VERSION),
+            Some(&2)
+        );
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn find_manifest_files_survives_symlink_cycles() {
+        let dir = std::env::temp_dir().join(format!("oxide-manifest-loop-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("a")).unwrap();
+        fs::write(dir.join("a").join(MANIFEST_FILE_NAME), "{}").unwrap();
+        std::os::unix::fs::symlink(&dir, dir.join("a").join("loop")).unwrap();
+
+        assert_eq!(find_manifest_files(&dir).len(), 1);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -1,32 +1,44 @@
 //! OpenAI-compatible HTTP API server.
 //!
-//! Provides `/v1/chat/completions` and `/v1/models` endpoints for compatibility
-//! with OpenAI clients (Cursor, Continue, Open WebUI, etc.).
+//! Provides `/v1/chat/completions`, `/v1/batch` and `/v1/models` endpoints for compatibility
+//! with OpenAI clients (Cursor, Continue, Open WebUI, etc.), plus `/v1/jobs` for
+//! long-running generations that are polled instead of awaited.
 
 use axum::{
     Json, Router,
-    extract::State,
-    http::StatusCode,
+    body::Body,
+    extract::{
+        DefaultBodyLimit, Path, Request, State,
+        ws::{Message as WsMessage, WebSocket, WebSocketUpgrade},
+    },
+    http::{HeaderValue, StatusCode, header::CONTENT_TYPE},
+    middleware::{self, Next},
     response::{
         IntoResponse, Response,
         sse::{Event, KeepAlive, Sse},
     },
     routing::{get, post},
 };
-use futures_util::stream::{self, Stream};
+use futures_util::stream::{self, Stream, StreamExt};
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::HashMap,
     convert::Infallible,
     net::SocketAddr,
-    sync::Arc,
-    time::{SystemTime, UNIX_EPOCH},
+    sync::{
+        Arc, Mutex, RwLock,
+        atomic::{AtomicBool, AtomicU16, AtomicU32, AtomicU64, AtomicUsize, Ordering},
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
-use tokio::sync::broadcast;
-use tower_http::cors::{Any, CorsLayer};
+use tokio::sync::{broadcast, mpsc};
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
 
-use crate::core::state::SharedState;
-use crate::core::types::{ChatMessage, GenerateRequest, ToolChoice};
+use crate::api::embedding_cache::{CachedEmbedding, EMBEDDING_CACHE, EmbeddingCache};
+use crate::core::state::{ModelState, SharedState};
+use crate::core::types::{ChatMessage, GenerateRequest, OpenAiServerSettings, ToolChoice};
 use crate::generate::emit::{EmissionBackend, GenerationEvent};
+use crate::generate::grammar::{OutputFormat, validate_schema_definition};
 use crate::generate::stream::generate_stream_with_backend;
 use crate::generate::tool_call_parser::{Tool, ToolCall};
 use candle::Tensor;
@@ -41,6 +53,46 @@ pub struct EmbeddingRequest {
     pub input: EmbeddingInput,
     #[serde(default)]
     pub user: Option<String>,
+    /// Способ свёртки скрытых состояний в один вектор (по умолчанию `mean`)
+    #[serde(default)]
+    pub pooling: Option<EmbeddingPooling>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingPooling {
+    /// Среднее по всем токенам
+    #[default]
+    Mean,
+    /// Скрытое состояние последнего токена
+    Last,
+    /// Скрытое состояние первого (CLS/BOS) токена
+    Cls,
+}
+
+impl EmbeddingPooling {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Mean => "mean",
+            Self::Last => "last",
+            Self::Cls => "cls",
+        }
+    }
+
+    /// Свёртка `[1, seq_len, hidden]` в вектор `[hidden]`
+    fn pool(self, hidden_states: &Tensor) -> candle::Result<Tensor> {
+        let pooled = match self {
+            Self::Mean => hidden_states.mean(1)?,
+            Self::Last => {
+                let seq_len = hidden_states.dim(1)?;
+                hidden_states
+                    .narrow(1, seq_len.saturating_sub(1), 1)?
+                    .squeeze(1)?
+            }
+            Self::Cls => hidden_states.narrow(1, 0, 1)?.squeeze(1)?,
+        };
+        pooled.squeeze(0)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -73,18 +125,200 @@ pub struct EmbeddingUsage {
 
 pub const OPENAI_PORT: u16 = 11434;
 
+/// Upper bound of the port range tried when `OPENAI_PORT` is already taken
+/// (e.g. by a running Ollama instance).
+pub const OPENAI_PORT_RANGE_MAX: u16 = 11444;
+
+/// Streaming responses are closed if generation produces no events for this long.
+const STREAM_IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Port the server actually bound to (0 until started).
+static BOUND_PORT: AtomicU16 = AtomicU16::new(0);
+
 #[derive(Serialize)]
 pub struct ServerConfig {
     pub port: u16,
     pub running: bool,
+    pub ws_enabled: bool,
+    pub max_request_body_mb: u32,
+    pub drain_timeout_secs: u64,
+    /// Empty when any origin is allowed
+    pub cors_allowlist: Vec<String>,
+}
+
+/// Mirrors `OpenAiServerSettings::ws_enabled`; toggled without restarting the server.
+static WS_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Mirrors `OpenAiServerSettings::max_request_body_mb`; read when the router is built.
+static MAX_REQUEST_BODY_MB: AtomicU32 = AtomicU32::new(32);
+
+/// Mirrors `OpenAiServerSettings::drain_timeout_secs`; read when the server shuts down.
+static DRAIN_TIMEOUT_SECS: AtomicU64 = AtomicU64::new(30);
+
+/// Valid entries of `OpenAiServerSettings::cors_allowed_origins`; `None` allows
+/// any origin. Read when the router is built.
+static CORS_ALLOWLIST: RwLock<Option<Vec<String>>> = RwLock::new(None);
+
+pub(crate) fn apply_openai_server_settings(settings: &OpenAiServerSettings) {
+    WS_ENABLED.store(settings.ws_enabled, Ordering::Relaxed);
+    MAX_REQUEST_BODY_MB.store(settings.max_request_body_mb.max(1), Ordering::Relaxed);
+    DRAIN_TIMEOUT_SECS.store(settings.drain_timeout_secs, Ordering::Relaxed);
+    if let Ok(mut slot) = CORS_ALLOWLIST.write() {
+        *slot = parse_cors_allowlist(&settings.cors_allowed_origins);
+    }
+}
+
+fn ws_enabled() -> bool {
+    WS_ENABLED.load(Ordering::Relaxed)
+}
+
+fn max_request_body_mb() -> u32 {
+    MAX_REQUEST_BODY_MB.load(Ordering::Relaxed)
+}
+
+/// Checks that an allowlist entry is a bare `http(s)://host[:port]` origin and
+/// returns it in the form browsers send in the `Origin` header.
+pub fn validate_cors_origin(origin: &str) -> Result<String, String> {
+    if origin.contains('*') {
+        return Err(format!(
+            "Wildcards are not allowed in CORS origin '{origin}'; use '*' alone to allow any origin"
+        ));
+    }
+    let url =
+        reqwest::Url::parse(origin).map_err(|e| format!("Invalid CORS origin '{origin}': {e}"))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!(
+            "CORS origin '{origin}' must use http or https, got '{}'",
+            url.scheme()
+        ));
+    }
+    if url.host_str().is_none_or(str::is_empty) {
+        return Err(format!("CORS origin '{origin}' has no hostname"));
+    }
+    if url.port() == Some(0) {
+        return Err(format!("CORS origin '{origin}' has an invalid port"));
+    }
+    if url.path() != "/" || url.query().is_some() || url.fragment().is_some() {
+        return Err(format!(
+            "CORS origin '{origin}' must not contain a path, query or fragment"
+        ));
+    }
+    Ok(url.origin().ascii_serialization())
+}
+
+fn cors_entries(entries: &[String]) -> Vec<&str> {
+    entries
+        .iter()
+        .map(|entry| entry.trim())
+        .filter(|entry| !entry.is_empty())
+        .collect()
+}
+
+/// Normalizes the CORS allowlist before it is saved, rejecting invalid entries.
+/// An empty list or a lone `*` allows any origin and is saved as an empty list.
+pub fn normalize_cors_origins(entries: &[String]) -> Result<Vec<String>, String> {
+    let entries = cors_entries(entries);
+    if entries == ["*"] {
+        return Ok(Vec::new());
+    }
+    entries.into_iter().map(validate_cors_origin).collect()
+}
+
+/// Valid entries of a saved allowlist; `None` means any origin is allowed.
+/// Invalid entries (e.g. from a hand-edited file) are skipped, not widened to `*`.
+fn parse_cors_allowlist(entries: &[String]) -> Option<Vec<String>> {
+    let entries = cors_entries(entries);
+    if entries.is_empty() || entries == ["*"] {
+        return None;
+    }
+    let origins = entries
+        .into_iter()
+        .filter_map(|entry| match validate_cors_origin(entry) {
+            Ok(origin) => Some(origin),
+            Err(err) => {
+                log::warn!("Ignoring CORS allowlist entry: {}", err);
+                None
+            }
+        })
+        .collect();
+    Some(origins)
+}
+
+fn cors_allowlist() -> Option<Vec<String>> {
+    CORS_ALLOWLIST.read().ok()?.clone()
+}
+
+fn drain_timeout_secs() -> u64 {
+    DRAIN_TIMEOUT_SECS.load(Ordering::Relaxed)
 }
 
 #[tauri::command]
 pub fn get_server_config() -> ServerConfig {
+    let bound = BOUND_PORT.load(Ordering::Relaxed);
     ServerConfig {
-        port: OPENAI_PORT,
-        running: true,
+        port: if bound == 0 { OPENAI_PORT } else { bound },
+        running: bound != 0,
+        ws_enabled: ws_enabled(),
+        max_request_body_mb: max_request_body_mb(),
+        drain_timeout_secs: drain_timeout_secs(),
+        cors_allowlist: cors_allowlist().unwrap_or_default(),
+    }
+}
+
+#[tauri::command]
+pub fn get_openai_server_settings(app: tauri::AppHandle) -> Result<OpenAiServerSettings, String> {
+    ModelState::load_openai_server_settings(&app)
+}
+
+#[tauri::command]
+pub fn set_openai_server_settings(
+    app: tauri::AppHandle,
+    settings: OpenAiServerSettings,
+) -> Result<(), String> {
+    settings.validate()?;
+    let settings = OpenAiServerSettings {
+        cors_allowed_origins: normalize_cors_origins(&settings.cors_allowed_origins)?,
+        ..settings
+    };
+    ModelState::save_openai_server_settings(&app, &settings)?;
+    crate::core::audit_log::record(
+        &app,
+        "settings_changed",
+        serde_json::json!({
+            "setting": "openai_server",
+            "ws_enabled": settings.ws_enabled,
+            "max_request_body_mb": settings.max_request_body_mb,
+            "drain_timeout_secs": settings.drain_timeout_secs,
+            "cors_allowed_origins": settings.cors_allowed_origins,
+        }),
+    );
+    apply_openai_server_settings(&settings);
+    Ok(())
+}
+
+/// Find a free port in `min..=max`, trying each port sequentially.
+///
+/// Falls back to an OS-assigned port outside the range if every port in it
+/// is occupied.
+pub fn find_available_port_in_range(min: u16, max: u16) -> Result<u16, String> {
+    if min > max {
+        return Err(format!("Invalid port range: {min} > {max}"));
+    }
+    if min < 1024 {
+        return Err(format!("Port range must start at 1024 or above, got {min}"));
     }
+
+    for port in min..=max {
+        if std::net::TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], port))).is_ok() {
+            return Ok(port);
+        }
+    }
+
+    log::warn!("All ports in {min}-{max} are occupied, falling back to a random port");
+    std::net::TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0)))
+        .and_then(|listener| listener.local_addr())
+        .map(|addr| addr.port())
+        .map_err(|e| format!("Failed to find an available port: {e}"))
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -113,6 +347,46 @@ pub struct ChatCompletionRequest {
     /// Tool choice: auto, none, required, or specific function
     #[serde(default)]
     pub tool_choice: Option<ToolChoice>,
+    /// Structured output: `json_object` or `json_schema`
+    #[serde(default)]
+    pub response_format: Option<ResponseFormat>,
+    /// Run as a background job (see `/v1/jobs`) instead of waiting for the answer
+    #[serde(default, rename = "async")]
+    pub async_job: bool,
+}
+
+/// OpenAI `response_format`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponseFormat {
+    Text,
+    JsonObject,
+    JsonSchema { json_schema: JsonSchemaFormat },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonSchemaFormat {
+    #[serde(default)]
+    pub name: Option<String>,
+    pub schema: serde_json::Value,
+    #[serde(default)]
+    pub strict: Option<bool>,
+}
+
+impl ResponseFormat {
+    /// Converts to the generation format, rejecting schemas the grammar cannot enforce
+    fn to_output_format(&self) -> Result<OutputFormat, (StatusCode, Json<ErrorResponse>)> {
+        match self {
+            ResponseFormat::Text => Ok(OutputFormat::None),
+            ResponseFormat::JsonObject => Ok(OutputFormat::Json),
+            ResponseFormat::JsonSchema { json_schema } => {
+                validate_schema_definition(&json_schema.schema).map_err(|e| {
+                    invalid_request(&format!("Invalid response_format schema: {e}"))
+                })?;
+                Ok(OutputFormat::JsonSchema(json_schema.schema.clone()))
+            }
+        }
+    }
 }
 
 /// Stop tokens can be a single string or an array of strings
@@ -187,6 +461,9 @@ pub struct Choice {
     pub index: usize,
     pub message: ResponseMessage,
     pub finish_reason: Option<String>,
+    /// Нарушение JSON Schema из `response_format` (расширение Oxide Lab)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema_violation: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -284,6 +561,87 @@ pub struct ApiError {
     pub code: Option<String>,
 }
 
+/// Default number of batch requests generated at the same time.
+pub const DEFAULT_BATCH_CONCURRENCY: usize = 4;
+
+/// Upper bound for `max_concurrency` in `/v1/batch`.
+const MAX_BATCH_CONCURRENCY: usize = 16;
+
+/// Upper bound for the number of entries in one `/v1/batch` request.
+const MAX_BATCH_REQUESTS: usize = 256;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchRequest {
+    pub requests: Vec<ChatCompletionRequest>,
+    #[serde(default)]
+    pub max_concurrency: Option<usize>,
+}
+
+/// Result of one batch entry: either a completion or the error it failed with.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatCompletionResult {
+    pub index: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response: Option<ChatCompletion>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ApiError>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchResponse {
+    /// In the same order as the submitted requests
+    pub responses: Vec<ChatCompletionResult>,
+    pub total_tokens: u64,
+    pub duration_ms: u64,
+}
+
+/// Finished jobs are kept this long so clients can still fetch the result.
+const JOB_RETENTION: Duration = Duration::from_secs(3600);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    /// Waiting for the model (generations run one at a time)
+    Queued,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// Status of a background generation as returned by `GET /v1/jobs/{id}`.
+#[derive(Debug, Clone, Serialize)]
+pub struct AsyncJobState {
+    pub job_id: String,
+    pub status: JobStatus,
+    pub model: String,
+    pub created: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished: Option<u64>,
+    /// Text generated so far
+    pub output: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<ChatCompletion>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct JobCreated {
+    pub job_id: String,
+    pub status: JobStatus,
+}
+
+pub struct AsyncJob {
+    pub state: AsyncJobState,
+    /// Output deltas for `/v1/jobs/{id}/stream`; dropped when the job finishes
+    updates: Option<broadcast::Sender<String>>,
+    /// Checked by this job's generation loop only
+    cancel: Arc<AtomicBool>,
+}
+
+pub type JobStore = Arc<Mutex<HashMap<String, AsyncJob>>>;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CompletionRequest {
     pub model: String,
@@ -296,6 +654,46 @@ pub struct CompletionRequest {
     pub temperature: Option<f64>,
     #[serde(default)]
     pub top_p: Option<f64>,
+    #[serde(default)]
+    pub stop: Option<StopTokens>,
+    /// Вернуть prompt в начале ответа
+    #[serde(default)]
+    pub echo: bool,
+}
+
+impl CompletionRequest {
+    /// Legacy prompt оборачивается в одно сообщение пользователя и идёт через chat-пайплайн.
+    fn to_generate_request(&self) -> GenerateRequest {
+        GenerateRequest {
+            prompt: String::new(),
+            messages: Some(vec![ChatMessage {
+                role: "user".to_string(),
+                content: self.prompt.clone(),
+            }]),
+            temperature: self.temperature,
+            top_p: self.top_p,
+            max_new_tokens: self.max_tokens,
+            tools: None,
+            top_k: None,
+            min_p: None,
+            repeat_penalty: None,
+            repeat_last_n: 64,
+            seed: None,
+            use_custom_params: true,
+            tracing: None,
+            verbose_prompt: None,
+            split_prompt: None,
+            attachments: None,
+            edit_index: None,
+            format: None,
+            stop_sequences: self.stop.as_ref().map(|s| s.to_vec()),
+            tool_choice: None,
+            disable_template_substitution: true,
+            suppress_thinking: false,
+            thinking_budget_tokens: None,
+            fim_mode: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -321,11 +719,21 @@ pub struct CompletionChoice {
 
 pub struct OpenAIBackend {
     tx: tokio::sync::mpsc::UnboundedSender<GenerationEvent>,
+    /// Per-request cancel flag, e.g. `DELETE /v1/jobs/{id}`
+    cancel: Arc<AtomicBool>,
 }
 
 impl OpenAIBackend {
     pub fn new(tx: tokio::sync::mpsc::UnboundedSender<GenerationEvent>) -> Self {
-        Self { tx }
+        Self {
+            tx,
+            cancel: Arc::default(),
+        }
+    }
+
+    pub fn with_cancel(mut self, cancel: Arc<AtomicBool>) -> Self {
+        self.cancel = cancel;
+        self
     }
 }
 
@@ -333,6 +741,11 @@ impl EmissionBackend for OpenAIBackend {
     fn emit(&self, event: GenerationEvent) {
         let _ = self.tx.send(event);
     }
+
+    /// Receiver dropped (the client disconnected) or the request was cancelled
+    fn is_cancelled(&self) -> bool {
+        self.tx.is_closed() || self.cancel.load(Ordering::SeqCst)
+    }
 }
 
 // ============================================================================
@@ -342,6 +755,10 @@ impl EmissionBackend for OpenAIBackend {
 pub struct OpenAIServerState {
     pub model_state: SharedState,
     pub shutdown_tx: broadcast::Sender<()>,
+    /// Requests whose response is still being sent, plus running background jobs
+    pub active_requests: Arc<AtomicUsize>,
+    /// Background generations started via `/v1/jobs` or `"async": true`
+    pub jobs: JobStore,
 }
 
 // ============================================================================
@@ -430,6 +847,41 @@ async fn embeddings_handler(
 
     let tokenizer = guard.tokenizer.clone().unwrap();
     let model_name = req.model.clone();
+    let pooling = req.pooling.unwrap_or_default();
+
+    // Кэшируем только одиночные строки: повторы обычно приходят именно так
+    let cache_key = match &req.input {
+        EmbeddingInput::String(text) => {
+            let model_id = guard
+                .model_path
+                .as_deref()
+                .or(guard.hub_repo_id.as_deref())
+                .unwrap_or_default();
+            Some(EmbeddingCache::key(model_id, pooling.as_str(), text))
+        }
+        EmbeddingInput::Array(_) => None,
+    };
+    if let Some(key) = &cache_key {
+        let cached = EMBEDDING_CACHE.get(key);
+        guard
+            .performance_monitor
+            .record_embedding_cache(cached.is_some());
+        if let Some(cached) = cached {
+            return Ok(Json(EmbeddingResponse {
+                object: "list".to_string(),
+                data: vec![EmbeddingData {
+                    object: "embedding".to_string(),
+                    index: 0,
+                    embedding: cached.embedding,
+                }],
+                model: model_name,
+                usage: EmbeddingUsage {
+                    prompt_tokens: cached.prompt_tokens,
+                    total_tokens: cached.prompt_tokens,
+                },
+            }));
+        }
+    }
 
     let inputs = match req.input {
         EmbeddingInput::String(s) => vec![s],
@@ -459,8 +911,7 @@ async fn embeddings_handler(
 
         let result = (|| -> candle::Result<Vec<f32>> {
             let hidden_states = entry.model.get_embeddings(&input_tensor)?;
-            let embedding_tensor = hidden_states.mean(1)?.squeeze(0)?;
-            embedding_tensor.to_vec1()
+            pooling.pool(&hidden_states)?.to_vec1()
         })();
 
         let embedding = match result {
@@ -474,6 +925,16 @@ async fn embeddings_handler(
             }
         };
 
+        if let Some(key) = &cache_key {
+            EMBEDDING_CACHE.insert(
+                key.clone(),
+                CachedEmbedding {
+                    embedding: embedding.clone(),
+                    prompt_tokens,
+                },
+            );
+        }
+
         data.push(EmbeddingData {
             object: "embedding".to_string(),
             index,
@@ -496,7 +957,10 @@ async fn chat_completions_handler(
     State(state): State<Arc<OpenAIServerState>>,
     Json(req): Json<ChatCompletionRequest>,
 ) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
-    if req.stream {
+    if req.async_job {
+        let created = create_job(&state, req)?;
+        Ok((StatusCode::ACCEPTED, Json(created)).into_response())
+    } else if req.stream {
         // For streaming, return SSE
         let stream = create_completion_stream(state, req).await?;
         Ok(Sse::new(stream)
@@ -513,6 +977,12 @@ async fn create_completion(
     state: Arc<OpenAIServerState>,
     req: ChatCompletionRequest,
 ) -> Result<ChatCompletion, (StatusCode, Json<ErrorResponse>)> {
+    let format = req
+        .response_format
+        .as_ref()
+        .map(ResponseFormat::to_output_format)
+        .transpose()?;
+
     // 1. Check model loaded
     {
         let guard = state
@@ -578,9 +1048,14 @@ async fn create_completion(
         split_prompt: None,
         attachments: None,
         edit_index: None,
-        format: None,
+        format,
         stop_sequences,
         tool_choice: req.tool_choice,
+        // Промпты API-клиентов передаются модели как есть
+        disable_template_substitution: true,
+        suppress_thinking: false,
+        thinking_budget_tokens: None,
+        fim_mode: None,
     };
 
     let state_clone = state.model_state.clone();
@@ -593,6 +1068,7 @@ async fn create_completion(
     });
 
     let mut full_content = String::new();
+    let mut schema_violation = None;
     let mut tool_calls = Vec::new();
     let mut usage = Usage {
         prompt_tokens: 0,
@@ -606,6 +1082,7 @@ async fn create_completion(
             GenerationEvent::Token(t) => full_content.push_str(&t),
             GenerationEvent::Message(msg) => full_content.push_str(&msg.content),
             GenerationEvent::ToolCall(tc) => tool_calls.push(tc.into()),
+            GenerationEvent::SchemaViolation(v) => schema_violation = Some(v),
             GenerationEvent::Metrics(m) => {
                 usage.prompt_tokens = m.prompt_tokens;
                 usage.completion_tokens = m.generated_tokens;
@@ -633,15 +1110,84 @@ async fn create_completion(
                 },
             },
             finish_reason,
+            schema_violation,
         }],
         usage,
     })
 }
 
-async fn create_completion_stream(
-    state: Arc<OpenAIServerState>,
+/// `POST /v1/batch`: runs several chat completions, up to `max_concurrency`
+/// at a time. Generation itself is serialized by the model lock, so extra
+/// concurrency mainly overlaps prompt preparation and response handling.
+async fn batch_handler(
+    State(state): State<Arc<OpenAIServerState>>,
+    Json(req): Json<BatchRequest>,
+) -> Result<Json<BatchResponse>, (StatusCode, Json<ErrorResponse>)> {
+    if req.requests.len() > MAX_BATCH_REQUESTS {
+        return Err(invalid_request(&format!(
+            "Batch contains {} requests; at most {MAX_BATCH_REQUESTS} are allowed",
+            req.requests.len()
+        )));
+    }
+    let started = std::time::Instant::now();
+    let concurrency = req
+        .max_concurrency
+        .unwrap_or(DEFAULT_BATCH_CONCURRENCY)
+        .clamp(1, MAX_BATCH_CONCURRENCY);
+
+    let mut responses: Vec<ChatCompletionResult> =
+        stream::iter(req.requests.into_iter().enumerate())
+            .map(|(index, mut request)| {
+                let state = state.clone();
+                // Batch entries are always answered as whole completions
+                request.stream = false;
+                async move {
+                    match create_completion(state, request).await {
+                        Ok(completion) => ChatCompletionResult {
+                            index,
+                            response: Some(completion),
+                            error: None,
+                        },
+                        Err((_, Json(err))) => ChatCompletionResult {
+                            index,
+                            response: None,
+                            error: Some(err.error),
+                        },
+                    }
+                }
+            })
+            .buffer_unordered(concurrency)
+            .collect()
+            .await;
+    responses.sort_by_key(|r| r.index);
+
+    let total_tokens = responses
+        .iter()
+        .filter_map(|r| r.response.as_ref())
+        .map(|c| c.usage.total_tokens as u64)
+        .sum();
+
+    Ok(Json(BatchResponse {
+        responses,
+        total_tokens,
+        duration_ms: started.elapsed().as_millis() as u64,
+    }))
+}
+
+/// Проверить, что модель загружена, и запустить генерацию в фоновом потоке.
+/// Возвращает канал событий генерации (общая часть SSE- и WebSocket-стримов).
+fn start_chat_generation(
+    state: &OpenAIServerState,
     req: ChatCompletionRequest,
-) -> Result<impl Stream<Item = Result<Event, Infallible>>, (StatusCode, Json<ErrorResponse>)> {
+    cancel: Arc<AtomicBool>,
+) -> Result<tokio::sync::mpsc::UnboundedReceiver<GenerationEvent>, (StatusCode, Json<ErrorResponse>)>
+{
+    let format = req
+        .response_format
+        .as_ref()
+        .map(ResponseFormat::to_output_format)
+        .transpose()?;
+
     // Check if model is loaded - scope the guard to ensure drop
     {
         let guard = state
@@ -664,9 +1210,7 @@ async fn create_completion_stream(
     } // guard dropped here
 
     let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
-    let backend = Box::new(OpenAIBackend::new(tx));
-    let id = format!("chatcmpl-{}", generate_id());
-    let model_id = req.model.clone();
+    let backend = Box::new(OpenAIBackend::new(tx).with_cancel(cancel));
 
     // OpenAI frequency_penalty → repeat_penalty conversion
     let repeat_penalty = req
@@ -701,9 +1245,14 @@ async fn create_completion_stream(
         split_prompt: None,
         attachments: None,
         edit_index: None,
-        format: None,
+        format,
         stop_sequences,
         tool_choice: req.tool_choice,
+        // Промпты API-клиентов передаются модели как есть
+        disable_template_substitution: true,
+        suppress_thinking: false,
+        thinking_budget_tokens: None,
+        fim_mode: None,
     };
 
     let state_clone = state.model_state.clone();
@@ -715,6 +1264,297 @@ async fn create_completion_stream(
         }
     });
 
+    Ok(rx)
+}
+
+// ============================================================================
+// Async jobs
+// ============================================================================
+
+fn job_not_found(id: &str) -> (StatusCode, Json<ErrorResponse>) {
+    (
+        StatusCode::NOT_FOUND,
+        Json(ErrorResponse {
+            error: ApiError {
+                message: format!("Job '{}' not found", id),
+                error_type: "invalid_request_error".into(),
+                code: Some("job_not_found".into()),
+            },
+        }),
+    )
+}
+
+/// Запускает генерацию в фоне и регистрирует задачу; результат забирается
+/// через `GET /v1/jobs/{id}`.
+fn create_job(
+    state: &OpenAIServerState,
+    mut req: ChatCompletionRequest,
+) -> Result<JobCreated, (StatusCode, Json<ErrorResponse>)> {
+    req.stream = false;
+    req.async_job = false;
+    let job_id = format!("job-{}", generate_id());
+    let model = req.model.clone();
+    let max_tokens = req.max_tokens;
+    let cancel = Arc::new(AtomicBool::new(false));
+    let rx = start_chat_generation(state, req, cancel.clone())?;
+
+    let (updates, _) = broadcast::channel(256);
+    {
+        let mut jobs = state.jobs.lock().map_err(|_| server_error("Lock failed"))?;
+        let now = now_unix();
+        jobs.retain(|_, job| {
+            job.state
+                .finished
+                .is_none_or(|finished| now.saturating_sub(finished) < JOB_RETENTION.as_secs())
+        });
+        jobs.insert(
+            job_id.clone(),
+            AsyncJob {
+                state: AsyncJobState {
+                    job_id: job_id.clone(),
+                    status: JobStatus::Queued,
+                    model,
+                    created: now,
+                    finished: None,
+                    output: String::new(),
+                    result: None,
+                    error: None,
+                },
+                updates: Some(updates),
+                cancel,
+            },
+        );
+    }
+
+    let in_flight = InFlightGuard::new(&state.active_requests);
+    let (jobs, id) = (state.jobs.clone(), job_id.clone());
+    tokio::spawn(async move {
+        run_job(jobs, id, rx, max_tokens).await;
+        // Graceful shutdown waits for background jobs as well
+        drop(in_flight);
+    });
+    Ok(JobCreated {
+        job_id,
+        status: JobStatus::Queued,
+    })
+}
+
+fn with_job(jobs: &JobStore, id: &str, f: impl FnOnce(&mut AsyncJob)) {
+    if let Ok(mut jobs) = jobs.lock()
+        && let Some(job) = jobs.get_mut(id)
+    {
+        f(job);
+    }
+}
+
+/// Собирает события генерации в состояние задачи.
+async fn run_job(
+    jobs: JobStore,
+    job_id: String,
+    mut rx: mpsc::UnboundedReceiver<GenerationEvent>,
+    max_tokens: Option<usize>,
+) {
+    let mut started = false;
+    // Метрики приходят только после успешного завершения генерации
+    let mut finished = false;
+    let mut schema_violation = None;
+    let mut tool_calls = Vec::new();
+    let mut usage = Usage {
+        prompt_tokens: 0,
+        completion_tokens: 0,
+        total_tokens: 0,
+    };
+
+    while let Some(event) = rx.recv().await {
+        let delta = match event {
+            GenerationEvent::Start => {
+                started = true;
+                with_job(&jobs, &job_id, |job| {
+                    if job.state.status == JobStatus::Queued {
+                        job.state.status = JobStatus::Running;
+                    }
+                });
+                continue;
+            }
+            GenerationEvent::Token(t) => t,
+            GenerationEvent::Message(msg) => msg.content,
+            GenerationEvent::ToolCall(tc) => {
+                tool_calls.push(tc.into());
+                continue;
+            }
+            GenerationEvent::SchemaViolation(v) => {
+                schema_violation = Some(v);
+                continue;
+            }
+            GenerationEvent::Metrics(m) => {
+                finished = true;
+                usage.prompt_tokens = m.prompt_tokens;
+                usage.completion_tokens = m.generated_tokens;
+                usage.total_tokens = m.prompt_tokens + m.generated_tokens;
+                continue;
+            }
+            _ => continue,
+        };
+        if delta.is_empty() {
+            continue;
+        }
+        with_job(&jobs, &job_id, |job| {
+            job.state.output.push_str(&delta);
+            if let Some(updates) = &job.updates {
+                let _ = updates.send(delta);
+            }
+        });
+    }
+
+    with_job(&jobs, &job_id, |job| {
+        // Closes `/stream` subscribers
+        job.updates = None;
+        job.state.finished = Some(now_unix());
+        match job.state.status {
+            JobStatus::Cancelled => {}
+            _ if !started => {
+                job.state.status = JobStatus::Failed;
+                job.state.error = Some("Generation failed to start".into());
+            }
+            _ if !finished => {
+                job.state.status = JobStatus::Failed;
+                job.state.error = Some("Generation failed".into());
+            }
+            _ => {
+                let finish_reason = if max_tokens.is_some_and(|max| usage.completion_tokens >= max)
+                {
+                    "length"
+                } else {
+                    "stop"
+                };
+                job.state.status = JobStatus::Completed;
+                job.state.result = Some(ChatCompletion {
+                    id: format!("chatcmpl-{}", generate_id()),
+                    object: "chat.completion".to_string(),
+                    created: now_unix(),
+                    model: job.state.model.clone(),
+                    choices: vec![Choice {
+                        index: 0,
+                        message: ResponseMessage {
+                            role: "assistant".to_string(),
+                            content: job.state.output.clone(),
+                            tool_calls: if tool_calls.is_empty() {
+                                None
+                            } else {
+                                Some(tool_calls)
+                            },
+                        },
+                        finish_reason: Some(finish_reason.to_string()),
+                        schema_violation,
+                    }],
+                    usage,
+                });
+            }
+        }
+    });
+}
+
+/// `POST /v1/jobs`: starts a chat completion in the background and returns
+/// its id right away.
+async fn create_job_handler(
+    State(state): State<Arc<OpenAIServerState>>,
+    Json(req): Json<ChatCompletionRequest>,
+) -> Result<(StatusCode, Json<JobCreated>), (StatusCode, Json<ErrorResponse>)> {
+    let created = create_job(&state, req)?;
+    Ok((StatusCode::ACCEPTED, Json(created)))
+}
+
+/// `GET /v1/jobs/{id}`: status and output generated so far.
+async fn get_job_handler(
+    State(state): State<Arc<OpenAIServerState>>,
+    Path(id): Path<String>,
+) -> Result<Json<AsyncJobState>, (StatusCode, Json<ErrorResponse>)> {
+    let jobs = state.jobs.lock().map_err(|_| server_error("Lock failed"))?;
+    let job = jobs.get(&id).ok_or_else(|| job_not_found(&id))?;
+    Ok(Json(job.state.clone()))
+}
+
+/// `DELETE /v1/jobs/{id}`: cancels a queued or running job. Only this job's
+/// generation stops; other requests keep running.
+async fn cancel_job_handler(
+    State(state): State<Arc<OpenAIServerState>>,
+    Path(id): Path<String>,
+) -> Result<Json<AsyncJobState>, (StatusCode, Json<ErrorResponse>)> {
+    let mut jobs = state.jobs.lock().map_err(|_| server_error("Lock failed"))?;
+    let job = jobs.get_mut(&id).ok_or_else(|| job_not_found(&id))?;
+    if matches!(job.state.status, JobStatus::Queued | JobStatus::Running) {
+        job.cancel.store(true, Ordering::SeqCst);
+        job.state.status = JobStatus::Cancelled;
+    }
+    Ok(Json(job.state.clone()))
+}
+
+/// `GET /v1/jobs/{id}/stream`: SSE with `progress` events (`{"delta": ...}`)
+/// and a final `done` event carrying the job state.
+async fn job_stream_handler(
+    State(state): State<Arc<OpenAIServerState>>,
+    Path(id): Path<String>,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    let (output, updates) = {
+        let jobs = state.jobs.lock().map_err(|_| server_error("Lock failed"))?;
+        let job = jobs.get(&id).ok_or_else(|| job_not_found(&id))?;
+        // Снимок и подписка под одной блокировкой, чтобы не потерять дельты
+        (
+            job.state.output.clone(),
+            job.updates.as_ref().map(|tx| tx.subscribe()),
+        )
+    };
+
+    let progress = |delta: &str| {
+        Ok::<_, Infallible>(
+            Event::default()
+                .event("progress")
+                .data(serde_json::json!({ "delta": delta }).to_string()),
+        )
+    };
+    let initial = (!output.is_empty()).then(|| progress(&output));
+
+    let jobs = state.jobs.clone();
+    let live = stream::unfold((updates, id, false), move |(updates, id, finished)| {
+        let jobs = jobs.clone();
+        async move {
+            if finished {
+                return None;
+            }
+            if let Some(mut rx) = updates {
+                loop {
+                    match rx.recv().await {
+                        Ok(delta) => return Some((progress(&delta), (Some(rx), id, false))),
+                        Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    }
+                }
+            }
+            let snapshot = jobs
+                .lock()
+                .ok()
+                .and_then(|jobs| jobs.get(&id).map(|job| job.state.clone()));
+            let data = serde_json::to_string(&snapshot).unwrap_or_default();
+            Some((
+                Ok(Event::default().event("done").data(data)),
+                (None, id, true),
+            ))
+        }
+    });
+
+    Ok(Sse::new(stream::iter(initial).chain(live))
+        .keep_alive(KeepAlive::default())
+        .into_response())
+}
+
+async fn create_completion_stream(
+    state: Arc<OpenAIServerState>,
+    req: ChatCompletionRequest,
+) -> Result<impl Stream<Item = Result<Event, Infallible>>, (StatusCode, Json<ErrorResponse>)> {
+    let id = format!("chatcmpl-{}", generate_id());
+    let model_id = req.model.clone();
+    let rx = start_chat_generation(&state, req, Arc::default())?;
+
     let stream = stream::unfold(
         (rx, id, model_id, false, false), // Added done_sent state
         move |(mut rx, id, model_id, mut finished, done_sent)| async move {
@@ -730,8 +1570,8 @@ async fn create_completion_stream(
                 ));
             }
 
-            match rx.recv().await {
-                Some(event) => {
+            match tokio::time::timeout(STREAM_IDLE_TIMEOUT, rx.recv()).await {
+                Ok(Some(event)) => {
                     let chunk = match event {
                         GenerationEvent::Start => ChatCompletionChunk {
                             id: id.clone(),
@@ -804,19 +1644,21 @@ async fn create_completion_stream(
                                 }],
                             }
                         }
-                        GenerationEvent::Metrics(_) | GenerationEvent::PromptDump(_) => {
-                            ChatCompletionChunk {
-                                id: id.clone(),
-                                object: "chat.completion.chunk".to_string(),
-                                created: now_unix(),
-                                model: model_id.clone(),
-                                choices: vec![ChunkChoice {
-                                    index: 0,
-                                    delta: Delta::default(),
-                                    finish_reason: None,
-                                }],
-                            }
-                        }
+                        GenerationEvent::Metrics(_)
+                        | GenerationEvent::MemoryStats(_)
+                        | GenerationEvent::PerformanceRegression(_)
+                        | GenerationEvent::PromptDump(_)
+                        | GenerationEvent::SchemaViolation(_) => ChatCompletionChunk {
+                            id: id.clone(),
+                            object: "chat.completion.chunk".to_string(),
+                            created: now_unix(),
+                            model: model_id.clone(),
+                            choices: vec![ChunkChoice {
+                                index: 0,
+                                delta: Delta::default(),
+                                finish_reason: None,
+                            }],
+                        },
                         GenerationEvent::Done => {
                             finished = true;
                             ChatCompletionChunk {
@@ -839,7 +1681,11 @@ async fn create_completion_stream(
                         (rx, id, model_id, finished, done_sent),
                     ))
                 }
-                None => None,
+                Ok(None) => None,
+                Err(_) => {
+                    log::warn!("SSE stream idle for {:?}, closing", STREAM_IDLE_TIMEOUT);
+                    None
+                }
             }
         },
     );
@@ -847,6 +1693,137 @@ async fn create_completion_stream(
     Ok(stream)
 }
 
+/// Кадр WebSocket-стрима `/v1/ws/chat`.
+#[derive(Debug, Clone, Serialize)]
+pub struct WsChatFrame {
+    pub index: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delta: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<OpenAIToolCall>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finish_reason: Option<String>,
+}
+
+impl WsChatFrame {
+    fn new(index: usize) -> Self {
+        Self {
+            index,
+            delta: None,
+            tool_calls: None,
+            finish_reason: None,
+        }
+    }
+}
+
+/// WebSocket-альтернатива SSE для прокси, которые буферизуют `text/event-stream`.
+///
+/// Клиент отправляет `ChatCompletionRequest` первым текстовым кадром и получает
+/// кадры `{"delta": "...", "index": N}` до кадра с `finish_reason`.
+async fn ws_chat_handler(
+    State(state): State<Arc<OpenAIServerState>>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    if !ws_enabled() {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: ApiError {
+                    message: "The WebSocket endpoint is disabled in the API server settings".into(),
+                    error_type: "invalid_request_error".into(),
+                    code: None,
+                },
+            }),
+        )
+            .into_response();
+    }
+    ws.on_upgrade(move |socket| handle_ws_chat(socket, state))
+}
+
+async fn handle_ws_chat(mut socket: WebSocket, state: Arc<OpenAIServerState>) {
+    let req = match socket.recv().await {
+        Some(Ok(WsMessage::Text(text))) => {
+            serde_json::from_str::<ChatCompletionRequest>(text.as_str()).map_err(|e| {
+                ErrorResponse {
+                    error: ApiError {
+                        message: format!("Invalid request: {e}"),
+                        error_type: "invalid_request_error".into(),
+                        code: None,
+                    },
+                }
+            })
+        }
+        Some(Ok(_)) => Err(ErrorResponse {
+            error: ApiError {
+                message: "Expected a text frame with a chat completion request".into(),
+                error_type: "invalid_request_error".into(),
+                code: None,
+            },
+        }),
+        Some(Err(e)) => {
+            log::warn!("WebSocket receive failed: {}", e);
+            return;
+        }
+        None => return,
+    };
+
+    let mut rx = match req.and_then(|req| {
+        start_chat_generation(&state, req, Arc::default()).map_err(|(_, Json(error))| error)
+    }) {
+        Ok(rx) => rx,
+        Err(error) => {
+            let text = serde_json::to_string(&error).unwrap_or_default();
+            let _ = socket.send(WsMessage::Text(text.into())).await;
+            let _ = socket.send(WsMessage::Close(None)).await;
+            return;
+        }
+    };
+
+    let mut index = 0;
+    loop {
+        let event = tokio::select! {
+            event = rx.recv() => match event {
+                Some(event) => event,
+                None => break,
+            },
+            incoming = socket.recv() => match incoming {
+                // Returning drops `rx`, which stops the generation at the next token
+                None | Some(Err(_)) | Some(Ok(WsMessage::Close(_))) => {
+                    log::debug!("WebSocket client disconnected during generation");
+                    return;
+                }
+                Some(Ok(_)) => continue,
+            },
+        };
+        let mut frame = WsChatFrame::new(index);
+        let mut finished = false;
+        match event {
+            GenerationEvent::Token(t) => frame.delta = Some(t),
+            GenerationEvent::Message(msg) if !msg.content.is_empty() => {
+                frame.delta = Some(msg.content)
+            }
+            GenerationEvent::ToolCall(tc) => frame.tool_calls = Some(vec![tc.into()]),
+            GenerationEvent::Done => {
+                frame.finish_reason = Some("stop".to_string());
+                finished = true;
+            }
+            _ => continue,
+        }
+
+        let text = serde_json::to_string(&frame).unwrap_or_default();
+        if socket.send(WsMessage::Text(text.into())).await.is_err() {
+            log::debug!("WebSocket client disconnected during generation");
+            return;
+        }
+        index += 1;
+        if finished {
+            break;
+        }
+    }
+
+    let _ = socket.send(WsMessage::Close(None)).await;
+}
+
 async fn completions_handler(
     State(state): State<Arc<OpenAIServerState>>,
     Json(req): Json<CompletionRequest>,
@@ -891,28 +1868,7 @@ async fn create_legacy_completion(
     let id = format!("cmpl-{}", generate_id());
     let model_name = req.model.clone();
 
-    let gen_req = GenerateRequest {
-        prompt: req.prompt.clone(),
-        messages: None,
-        temperature: req.temperature,
-        top_p: req.top_p,
-        max_new_tokens: req.max_tokens,
-        tools: None,
-        top_k: None,
-        min_p: None,
-        repeat_penalty: None,
-        repeat_last_n: 64,
-        seed: None,
-        use_custom_params: true,
-        tracing: None,
-        verbose_prompt: None,
-        split_prompt: None,
-        attachments: None,
-        edit_index: None,
-        format: None,
-        stop_sequences: None,
-        tool_choice: None,
-    };
+    let gen_req = req.to_generate_request();
 
     let state_clone = state.model_state.clone();
 
@@ -922,7 +1878,11 @@ async fn create_legacy_completion(
         }
     });
 
-    let mut full_text = String::new();
+    let mut full_text = if req.echo {
+        req.prompt.clone()
+    } else {
+        String::new()
+    };
     let mut usage = Usage {
         prompt_tokens: 0,
         completion_tokens: 0,
@@ -985,28 +1945,7 @@ async fn create_legacy_completion_stream(
     let id = format!("cmpl-{}", generate_id());
     let model_id = req.model.clone();
 
-    let gen_req = GenerateRequest {
-        prompt: req.prompt.clone(),
-        messages: None,
-        temperature: req.temperature,
-        top_p: req.top_p,
-        max_new_tokens: req.max_tokens,
-        tools: None,
-        top_k: None,
-        min_p: None,
-        repeat_penalty: None,
-        repeat_last_n: 64,
-        seed: None,
-        use_custom_params: true,
-        tracing: None,
-        verbose_prompt: None,
-        split_prompt: None,
-        attachments: None,
-        edit_index: None,
-        format: None,
-        stop_sequences: None,
-        tool_choice: None,
-    };
+    let gen_req = req.to_generate_request();
 
     let state_clone = state.model_state.clone();
 
@@ -1016,6 +1955,29 @@ async fn create_legacy_completion_stream(
         }
     });
 
+    // echo: первым чанком отдаём сам prompt
+    let echo_chunk = req.echo.then(|| {
+        let chunk = CompletionResponse {
+            id: id.clone(),
+            object: "text_completion".to_string(),
+            created: now_unix(),
+            model: model_id.clone(),
+            choices: vec![CompletionChoice {
+                text: req.prompt.clone(),
+                index: 0,
+                finish_reason: None,
+            }],
+            usage: Usage {
+                prompt_tokens: 0,
+                completion_tokens: 0,
+                total_tokens: 0,
+            },
+        };
+        Ok::<_, Infallible>(
+            Event::default().data(serde_json::to_string(&chunk).unwrap_or_default()),
+        )
+    });
+
     let stream = stream::unfold(
         (rx, id, model_id, false, false),
         move |(mut rx, id, model_id, mut finished, done_sent)| async move {
@@ -1030,8 +1992,8 @@ async fn create_legacy_completion_stream(
                 ));
             }
 
-            match rx.recv().await {
-                Some(event) => {
+            match tokio::time::timeout(STREAM_IDLE_TIMEOUT, rx.recv()).await {
+                Ok(Some(event)) => {
                     let chunk = match event {
                         GenerationEvent::Token(t) => CompletionResponse {
                             id: id.clone(),
@@ -1109,12 +2071,29 @@ async fn create_legacy_completion_stream(
                         (rx, id, model_id, finished, done_sent),
                     ))
                 }
-                None => None,
+                Ok(None) => None,
+                Err(_) => {
+                    log::warn!("SSE stream idle for {:?}, closing", STREAM_IDLE_TIMEOUT);
+                    None
+                }
             }
         },
     );
 
-    Ok(stream)
+    Ok(stream::iter(echo_chunk).chain(stream))
+}
+
+fn invalid_request(msg: &str) -> (StatusCode, Json<ErrorResponse>) {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(ErrorResponse {
+            error: ApiError {
+                message: msg.into(),
+                error_type: "invalid_request_error".into(),
+                code: None,
+            },
+        }),
+    )
 }
 
 fn server_error(msg: &str) -> (StatusCode, Json<ErrorResponse>) {
@@ -1156,17 +2135,104 @@ fn generate_id() -> String {
 // Router
 // ============================================================================
 
+/// Reject non-JSON bodies with an OpenAI-style 415 error.
+async fn require_json_content_type(req: Request, next: Next) -> Response {
+    let is_json = req
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .is_some_and(|mime| mime.trim().eq_ignore_ascii_case("application/json"));
+
+    if !is_json {
+        return (
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            Json(ErrorResponse {
+                error: ApiError {
+                    message: "Content-Type must be application/json".into(),
+                    error_type: "invalid_request_error".into(),
+                    code: None,
+                },
+            }),
+        )
+            .into_response();
+    }
+
+    next.run(req).await
+}
+
+/// Counts one in-flight request or background job until dropped.
+struct InFlightGuard(Arc<AtomicUsize>);
+
+impl InFlightGuard {
+    fn new(counter: &Arc<AtomicUsize>) -> Self {
+        counter.fetch_add(1, Ordering::SeqCst);
+        Self(counter.clone())
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+async fn track_in_flight(
+    State(state): State<Arc<OpenAIServerState>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    // Decrements even if the client disconnects and the handler is dropped
+    let guard = InFlightGuard::new(&state.active_requests);
+    let (parts, body) = next.run(req).await.into_parts();
+    // SSE responses keep generating after the handler returns, so the guard
+    // lives until the body is fully sent or dropped
+    let body = Body::from_stream(body.into_data_stream().map(move |chunk| {
+        let _in_flight = &guard;
+        chunk
+    }));
+    Response::from_parts(parts, body)
+}
+
 pub fn create_router(state: Arc<OpenAIServerState>) -> Router {
+    let allow_origin = match cors_allowlist() {
+        None => AllowOrigin::from(Any),
+        Some(origins) => AllowOrigin::list(
+            origins
+                .iter()
+                .filter_map(|origin| HeaderValue::from_str(origin).ok()),
+        ),
+    };
     let cors = CorsLayer::new()
-        .allow_origin(Any)
+        .allow_origin(allow_origin)
         .allow_methods(Any)
         .allow_headers(Any);
 
-    Router::new()
-        .route("/v1/models", get(models_handler))
+    let json_routes = Router::new()
         .route("/v1/chat/completions", post(chat_completions_handler))
+        .route("/v1/batch", post(batch_handler))
+        .route("/v1/jobs", post(create_job_handler))
         .route("/v1/completions", post(completions_handler))
         .route("/v1/embeddings", post(embeddings_handler))
+        .route_layer(middleware::from_fn(require_json_content_type));
+
+    let router = Router::new()
+        .route("/v1/models", get(models_handler))
+        .route(
+            "/v1/jobs/{id}",
+            get(get_job_handler).delete(cancel_job_handler),
+        )
+        .route("/v1/jobs/{id}/stream", get(job_stream_handler))
+        .route("/v1/ws/chat", get(ws_chat_handler))
+        .merge(json_routes);
+
+    let max_body_bytes = max_request_body_mb() as usize * 1024 * 1024;
+    router
+        .layer(DefaultBodyLimit::max(max_body_bytes))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            track_in_flight,
+        ))
         .layer(cors)
         .with_state(state)
 }
@@ -1175,15 +2241,72 @@ pub fn create_router(state: Arc<OpenAIServerState>) -> Router {
 // Server lifecycle
 // ============================================================================
 
+/// Outcome of draining in-flight requests on shutdown.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct DrainStats {
+    /// Requests that finished while draining
+    pub completed: u64,
+    /// Requests still running when the drain deadline passed
+    pub timed_out: u64,
+}
+
+/// Handle to a running server; `shutdown` stops it and drains requests.
+pub struct OpenAIServerHandle {
+    shutdown_tx: broadcast::Sender<()>,
+    active_requests: Arc<AtomicUsize>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl OpenAIServerHandle {
+    /// Stops accepting connections and waits until in-flight requests finish
+    /// or the drain timeout passes; whatever is left is then dropped.
+    pub async fn shutdown(self) -> DrainStats {
+        let in_flight = self.active_requests.load(Ordering::SeqCst) as u64;
+        let _ = self.shutdown_tx.send(());
+
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(drain_timeout_secs());
+        while self.active_requests.load(Ordering::SeqCst) > 0
+            && tokio::time::Instant::now() < deadline
+        {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        let remaining = self.active_requests.load(Ordering::SeqCst) as u64;
+        if remaining > 0 {
+            log::warn!(
+                "OpenAI API server drain timed out with {} request(s) in flight",
+                remaining
+            );
+            self.task.abort();
+        } else {
+            // Connections close on their own once responses are sent
+            let _ = tokio::time::timeout(Duration::from_secs(1), self.task).await;
+        }
+        BOUND_PORT.store(0, Ordering::Relaxed);
+
+        DrainStats {
+            completed: in_flight.saturating_sub(remaining),
+            timed_out: remaining,
+        }
+    }
+}
+
+/// Running server, kept in app state so it can be drained on exit.
+#[derive(Default)]
+pub struct OpenAIServerSlot(pub std::sync::Mutex<Option<OpenAIServerHandle>>);
+
 pub async fn start_server(
     model_state: SharedState,
     port: u16,
-) -> Result<broadcast::Sender<()>, std::io::Error> {
+) -> Result<OpenAIServerHandle, std::io::Error> {
     let (shutdown_tx, _) = broadcast::channel::<()>(1);
+    let active_requests = Arc::new(AtomicUsize::new(0));
 
     let state = Arc::new(OpenAIServerState {
         model_state,
         shutdown_tx: shutdown_tx.clone(),
+        active_requests: active_requests.clone(),
+        jobs: JobStore::default(),
     });
 
     let app = create_router(state);
@@ -1192,10 +2315,11 @@ pub async fn start_server(
     log::info!("OpenAI API server starting on http://{}", addr);
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
+    BOUND_PORT.store(listener.local_addr()?.port(), Ordering::Relaxed);
 
     let shutdown_rx = shutdown_tx.subscribe();
 
-    tokio::spawn(async move {
+    let task = tokio::spawn(async move {
         axum::serve(listener, app)
             .with_graceful_shutdown(async move {
                 let mut rx = shutdown_rx;
@@ -1204,7 +2328,280 @@ pub async fn start_server(
             })
             .await
             .ok();
+        BOUND_PORT.store(0, Ordering::Relaxed);
     });
 
-    Ok(shutdown_tx)
+    Ok(OpenAIServerHandle {
+        shutdown_tx,
+        active_requests,
+        task,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::performance::InferenceMetrics;
+    use axum::http::Method;
+    use tower::ServiceExt;
+
+    fn test_state() -> Arc<OpenAIServerState> {
+        let (shutdown_tx, _) = broadcast::channel(1);
+        Arc::new(OpenAIServerState {
+            model_state: Arc::new(Mutex::new(ModelState::new(candle::Device::Cpu))),
+            shutdown_tx,
+            active_requests: Arc::default(),
+            jobs: JobStore::default(),
+        })
+    }
+
+    fn insert_job(state: &OpenAIServerState, id: &str, status: JobStatus) -> Arc<AtomicBool> {
+        let cancel = Arc::new(AtomicBool::new(false));
+        state.jobs.lock().unwrap().insert(
+            id.to_string(),
+            AsyncJob {
+                state: AsyncJobState {
+                    job_id: id.to_string(),
+                    status,
+                    model: "test".into(),
+                    created: now_unix(),
+                    finished: None,
+                    output: String::new(),
+                    result: None,
+                    error: None,
+                },
+                updates: None,
+                cancel: cancel.clone(),
+            },
+        );
+        cancel
+    }
+
+    async fn send(
+        state: &Arc<OpenAIServerState>,
+        method: Method,
+        uri: &str,
+        body: Option<serde_json::Value>,
+    ) -> (StatusCode, serde_json::Value) {
+        let mut req = axum::http::Request::builder().method(method).uri(uri);
+        let body = match body {
+            Some(json) => {
+                req = req.header(CONTENT_TYPE, "application/json");
+                Body::from(json.to_string())
+            }
+            None => Body::empty(),
+        };
+        let response = create_router(state.clone())
+            .oneshot(req.body(body).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap_or_default())
+    }
+
+    fn metrics(generated_tokens: usize) -> InferenceMetrics {
+        InferenceMetrics {
+            prompt_tokens: 4,
+            generated_tokens,
+            total_duration_ms: 0,
+            prefill_duration_ms: 0,
+            generation_duration_ms: 0,
+            tokens_per_second: 0.0,
+            prefill_tokens_per_second: 0.0,
+            memory_usage_mb: 0.0,
+            timestamp: String::new(),
+        }
+    }
+
+    async fn finish_job(events: Vec<GenerationEvent>, max_tokens: Option<usize>) -> AsyncJobState {
+        let state = test_state();
+        insert_job(&state, "job-1", JobStatus::Queued);
+        let (tx, rx) = mpsc::unbounded_channel();
+        for event in events {
+            let _ = tx.send(event);
+        }
+        drop(tx);
+        run_job(state.jobs.clone(), "job-1".into(), rx, max_tokens).await;
+        let jobs = state.jobs.lock().unwrap();
+        jobs["job-1"].state.clone()
+    }
+
+    #[tokio::test]
+    async fn create_job_requires_a_loaded_model() {
+        let state = test_state();
+        let body = serde_json::json!({ "model": "test", "messages": [] });
+        let (status, json) = send(&state, Method::POST, "/v1/jobs", Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(json["error"]["message"], "No model loaded");
+        assert!(state.jobs.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn rejects_unsupported_response_format_schema() {
+        let state = test_state();
+        let body = serde_json::json!({
+            "model": "test",
+            "messages": [],
+            "response_format": {
+                "type": "json_schema",
+                "json_schema": { "schema": { "type": "string", "pattern": "^a+$" } }
+            }
+        });
+        let (status, json) = send(&state, Method::POST, "/v1/chat/completions", Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(json["error"]["type"], "invalid_request_error");
+        assert!(
+            json["error"]["message"]
+                .as_str()
+                .unwrap()
+                .contains("pattern")
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_oversized_batch() {
+        let state = test_state();
+        let request = serde_json::json!({ "model": "test", "messages": [] });
+        let body = serde_json::json!({ "requests": vec![request; MAX_BATCH_REQUESTS + 1] });
+        let (status, json) = send(&state, Method::POST, "/v1/batch", Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(json["error"]["type"], "invalid_request_error");
+    }
+
+    #[tokio::test]
+    async fn polls_and_cancels_a_running_job() {
+        let state = test_state();
+        let cancel = insert_job(&state, "job-1", JobStatus::Running);
+        let other = insert_job(&state, "job-2", JobStatus::Running);
+
+        let (status, json) = send(&state, Method::GET, "/v1/jobs/job-1", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json["status"], "running");
+
+        let (status, json) = send(&state, Method::DELETE, "/v1/jobs/job-1", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json["status"], "cancelled");
+        assert!(cancel.load(Ordering::SeqCst));
+        assert!(!other.load(Ordering::SeqCst));
+
+        let (_, json) = send(&state, Method::GET, "/v1/jobs/job-1", None).await;
+        assert_eq!(json["status"], "cancelled");
+    }
+
+    #[tokio::test]
+    async fn unknown_job_id_is_not_found() {
+        let state = test_state();
+        for method in [Method::GET, Method::DELETE] {
+            let (status, json) = send(&state, method, "/v1/jobs/job-missing", None).await;
+            assert_eq!(status, StatusCode::NOT_FOUND);
+            assert_eq!(json["error"]["code"], "job_not_found");
+        }
+    }
+
+    #[tokio::test]
+    async fn job_reports_failure_after_start() {
+        let job = finish_job(
+            vec![
+                GenerationEvent::Start,
+                GenerationEvent::Token("partial".into()),
+                GenerationEvent::Done,
+            ],
+            None,
+        )
+        .await;
+        assert_eq!(job.status, JobStatus::Failed);
+        assert!(job.result.is_none());
+        assert_eq!(job.output, "partial");
+    }
+
+    #[tokio::test]
+    async fn job_reports_length_stop() {
+        let events = |generated| {
+            vec![
+                GenerationEvent::Start,
+                GenerationEvent::Token("answer".into()),
+                GenerationEvent::Done,
+                GenerationEvent::Metrics(metrics(generated)),
+            ]
+        };
+        let finish_reason = |job: &AsyncJobState| {
+            job.result.as_ref().unwrap().choices[0]
+                .finish_reason
+                .clone()
+                .unwrap()
+        };
+
+        let job = finish_job(events(16), Some(16)).await;
+        assert_eq!(job.status, JobStatus::Completed);
+        assert_eq!(finish_reason(&job), "length");
+
+        let job = finish_job(events(3), Some(16)).await;
+        assert_eq!(finish_reason(&job), "stop");
+    }
+
+    #[test]
+    fn skips_occupied_ports_in_range() {
+        let localhost = |port| SocketAddr::from(([127, 0, 0, 1], port));
+        // Ephemeral port whose neighbour is free right now
+        let (_taken, port) = (0..10)
+            .find_map(|_| {
+                let listener = std::net::TcpListener::bind(localhost(0)).ok()?;
+                let port = listener.local_addr().ok()?.port();
+                (port < u16::MAX && std::net::TcpListener::bind(localhost(port + 1)).is_ok())
+                    .then_some((listener, port))
+            })
+            .expect("no free port pair");
+
+        assert_eq!(find_available_port_in_range(port, port + 1), Ok(port + 1));
+        assert!(find_available_port_in_range(port + 1, port).is_err());
+        assert!(find_available_port_in_range(80, 90).is_err());
+    }
+
+    #[test]
+    fn validates_cors_origins() {
+        assert!(validate_cors_origin("http://localhost:3000").is_ok());
+        assert!(validate_cors_origin("https://app.example.com").is_ok());
+        // Compared byte-for-byte with the Origin header
+        for (entry, origin) in [
+            ("http://localhost:3000/", "http://localhost:3000"),
+            ("HTTPS://App.Example.com", "https://app.example.com"),
+            ("https://example.com:443", "https://example.com"),
+            ("http://bücher.example", "http://xn--bcher-kva.example"),
+        ] {
+            assert_eq!(validate_cors_origin(entry).as_deref(), Ok(origin));
+        }
+
+        for invalid in [
+            "localhost:3000",
+            "ftp://example.com",
+            "https://*.example.com",
+            "http://localhost:0",
+            "http://localhost:70000",
+            "https://example.com/app",
+            "not a url",
+        ] {
+            assert!(validate_cors_origin(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn normalizes_saved_cors_allowlist() {
+        let list = |entries: &[&str]| entries.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            normalize_cors_origins(&list(&[" HTTP://Localhost:3000/ ", ""])),
+            Ok(list(&["http://localhost:3000"]))
+        );
+        assert_eq!(normalize_cors_origins(&list(&["*"])), Ok(Vec::new()));
+        assert!(normalize_cors_origins(&list(&["http://ok.example", "ftp://bad"])).is_err());
+
+        assert_eq!(parse_cors_allowlist(&[]), None);
+        // A file with only bad entries must not fall back to allowing any origin
+        assert_eq!(
+            parse_cors_allowlist(&list(&["ftp://bad"])),
+            Some(Vec::new())
+        );
+    }
 }
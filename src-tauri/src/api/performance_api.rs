@@ -1,6 +1,10 @@
 // API команды для мониторинга производительности
-use crate::core::performance::{PerformanceMetric, StartupMetrics, SystemUsage};
-use crate::core::state::SharedState;
+use crate::core::performance::{
+    LoadTimingReport, ModelMemoryStats, PerformanceDashboard, PerformanceMetric, StartupMetrics,
+    SystemUsage,
+};
+use crate::core::state::{ModelState, SharedState};
+use crate::core::types::LoadTimingSettings;
 
 /// Получить все метрики производительности
 #[tauri::command]
@@ -40,14 +44,48 @@ pub async fn get_memory_usage(state: tauri::State<'_, SharedState>) -> Result<f6
     Ok(memory_mb)
 }
 
+/// Заполненность KV-кэша по моделям (последний снимок после генерации)
+#[tauri::command]
+pub async fn get_model_memory_stats(
+    state: tauri::State<'_, SharedState>,
+) -> Result<Vec<ModelMemoryStats>, String> {
+    let monitor = {
+        let guard = state.lock().map_err(|e| e.to_string())?;
+        guard.performance_monitor.clone()
+    };
+    Ok(monitor.get_memory_stats().await)
+}
+
 /// Очистить все метрики производительности
 #[tauri::command]
-pub async fn clear_performance_metrics(state: tauri::State<'_, SharedState>) -> Result<(), String> {
+pub async fn clear_performance_metrics(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SharedState>,
+) -> Result<(), String> {
     let monitor = {
         let guard = state.lock().map_err(|e| e.to_string())?;
         guard.performance_monitor.clone()
     };
     monitor.clear_metrics().await;
+    crate::core::audit_log::record(
+        &app,
+        "data_cleared",
+        serde_json::json!({ "kind": "performance_metrics" }),
+    );
+    Ok(())
+}
+
+/// Сбросить базовую скорость генерации модели (или всех моделей без `model_id`)
+#[tauri::command]
+pub async fn clear_performance_baseline(
+    state: tauri::State<'_, SharedState>,
+    model_id: Option<String>,
+) -> Result<(), String> {
+    let monitor = {
+        let guard = state.lock().map_err(|e| e.to_string())?;
+        guard.performance_monitor.clone()
+    };
+    monitor.clear_speed_baseline(model_id.as_deref()).await;
     Ok(())
 }
 
@@ -64,6 +102,35 @@ pub async fn get_startup_metrics(
     Ok(metrics)
 }
 
+/// Получить отчёты о длительности стадий последних загрузок моделей
+#[tauri::command]
+pub async fn get_load_timing_reports(
+    state: tauri::State<'_, SharedState>,
+) -> Result<Vec<LoadTimingReport>, String> {
+    let monitor = {
+        let guard = state.lock().map_err(|e| e.to_string())?;
+        guard.performance_monitor.clone()
+    };
+    Ok(monitor.get_load_reports().await)
+}
+
+/// Получить настройки профилирования загрузки моделей
+#[tauri::command]
+pub fn get_load_timing_settings(app: tauri::AppHandle) -> Result<LoadTimingSettings, String> {
+    ModelState::load_load_timing_settings(&app)
+}
+
+/// Сохранить порог медленной стадии загрузки
+#[tauri::command]
+pub fn set_load_timing_settings(
+    app: tauri::AppHandle,
+    settings: LoadTimingSettings,
+) -> Result<(), String> {
+    ModelState::save_load_timing_settings(&app, &settings)?;
+    crate::api::model_loading::apply_load_timing_settings(&settings);
+    Ok(())
+}
+
 /// Получить текущее использование системных ресурсов (CPU, GPU, память)
 #[tauri::command]
 pub async fn get_system_usage(state: tauri::State<'_, SharedState>) -> Result<SystemUsage, String> {
@@ -74,3 +141,26 @@ pub async fn get_system_usage(state: tauri::State<'_, SharedState>) -> Result<Sy
     let usage = monitor.get_system_usage().await;
     Ok(usage)
 }
+
+/// Сводка производительности по последним запросам (опционально для одной модели)
+#[tauri::command]
+pub async fn get_performance_dashboard(
+    state: tauri::State<'_, SharedState>,
+    model_id: Option<String>,
+) -> Result<PerformanceDashboard, String> {
+    let (monitor, is_cuda) = {
+        let guard = state.lock().map_err(|e| e.to_string())?;
+        (
+            guard.performance_monitor.clone(),
+            matches!(guard.device, candle::Device::Cuda(_)),
+        )
+    };
+    let mut dashboard = monitor.get_dashboard(model_id.as_deref()).await;
+    if is_cuda {
+        dashboard.vram_used_mb =
+            tauri::async_runtime::spawn_blocking(crate::core::device::query_nvidia_vram_used_mb)
+                .await
+                .map_err(|e| e.to_string())?;
+    }
+    Ok(dashboard)
+}
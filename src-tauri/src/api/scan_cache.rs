@@ -0,0 +1,322 @@
+//! Кэш результатов сканирования локальных GGUF-моделей.
+//!
+//! Хранится в `profile_dir/scan_cache.json`: для каждого файла запоминается
+//! время модификации и построенный `ModelInfo`. Метаданные GGUF перечитываются
+//! только если с прошлого сканирования изменился сам файл или связанные с ним
+//! файлы (манифест, шарды, mmproj).
+
+use crate::api::local_models::{ModelInfo, gguf_shard_set};
+use crate::api::model_manager::manifest::{MANIFEST_FILE_NAME, resolve_manifest_path};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+pub const SCAN_CACHE_FILE: &str = "scan_cache.json";
+
+/// Payload события `scan_complete`.
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+pub struct ScanCompleteEvent {
+    pub cached_count: u32,
+    pub refreshed_count: u32,
+    pub new_count: u32,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ModelScanCache {
+    entries: HashMap<PathBuf, (u64, ModelInfo)>,
+    /// Связанные файлы каталогов, прочитанные за текущее сканирование
+    #[serde(skip)]
+    dirs: HashMap<PathBuf, DirMtimes>,
+}
+
+/// mtime-ы шардов и mmproj-проекторов одного каталога, собранные одним `read_dir`.
+#[derive(Debug, Default)]
+struct DirMtimes {
+    /// Самый поздний mmproj-проектор
+    mmproj: Option<u64>,
+    /// Самый поздний шард каждого набора `(prefix, total)`
+    shard_sets: HashMap<(String, u32), u64>,
+}
+
+impl DirMtimes {
+    fn scan(dir: &Path) -> Self {
+        let mut mtimes = Self::default();
+        let Ok(entries) = fs::read_dir(dir) else {
+            return mtimes;
+        };
+        for path in entries.filter_map(|entry| entry.ok()).map(|e| e.path()) {
+            let is_mmproj = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.to_lowercase().contains("mmproj"));
+            let shard_set = gguf_shard_set(&path);
+            if !is_mmproj && shard_set.is_none() {
+                continue;
+            }
+            let Some(mtime) = file_mtime(&path) else {
+                continue;
+            };
+            if is_mmproj {
+                mtimes.mmproj = mtimes.mmproj.max(Some(mtime));
+            }
+            if let Some(set) = shard_set {
+                let latest = mtimes.shard_sets.entry(set).or_default();
+                *latest = (*latest).max(mtime);
+            }
+        }
+        mtimes
+    }
+}
+
+impl ModelScanCache {
+    /// Загружает кэш; отсутствующий или повреждённый файл даёт пустой кэш.
+    pub fn load(path: &Path) -> Self {
+        fs::read(path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let bytes = serde_json::to_vec(self)
+            .map_err(|e| format!("Failed to serialize scan cache: {}", e))?;
+        fs::write(path, bytes).map_err(|e| format!("Failed to write scan cache: {}", e))
+    }
+
+    /// Удаляет файл кэша, чтобы следующее сканирование перечитало все модели.
+    pub fn clear(path: &Path) -> Result<(), String> {
+        match fs::remove_file(path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(format!("Failed to clear scan cache: {}", e)),
+        }
+    }
+
+    /// Возвращает `ModelInfo` из кэша, если mtime файла и связанных файлов не изменился,
+    /// иначе строит его через `build` и обновляет запись.
+    pub fn get_or_build(
+        &mut self,
+        path: &Path,
+        force_rescan: bool,
+        stats: &mut ScanCompleteEvent,
+        build: impl FnOnce(&Path) -> Result<Option<ModelInfo>, String>,
+    ) -> Result<Option<ModelInfo>, String> {
+        let mtime = self.cache_key(path);
+        let cached = self.entries.get(path);
+
+        if !force_rescan
+            && let (Some(mtime), Some((cached_mtime, info))) = (mtime, cached)
+            && *cached_mtime == mtime
+        {
+            stats.cached_count += 1;
+            return Ok(Some(info.clone()));
+        }
+
+        if cached.is_some() {
+            stats.refreshed_count += 1;
+        } else {
+            stats.new_count += 1;
+        }
+
+        let info = build(path)?;
+        // `build` может сам записать манифест рядом с моделью
+        match (&info, self.cache_key(path)) {
+            (Some(info), Some(mtime)) => {
+                self.entries
+                    .insert(path.to_path_buf(), (mtime, info.clone()));
+            }
+            _ => {
+                self.entries.remove(path);
+            }
+        }
+        Ok(info)
+    }
+
+    /// Модели из последнего сканирования.
+    pub fn models(&self) -> impl Iterator<Item = &ModelInfo> {
+        self.entries.values().map(|(_, info)| info)
+    }
+
+    /// Удаляет записи для файлов, которых больше нет на диске.
+    pub fn prune_missing(&mut self) -> usize {
+        let before = self.entries.len();
+        self.entries.retain(|path, _| path.exists());
+        before - self.entries.len()
+    }
+
+    /// Самый поздний mtime среди файла и связанных с ним файлов: шардов,
+    /// манифестов и mmproj-проекторов в том же каталоге. Каталог читается
+    /// один раз за сканирование; манифесты проверяются каждый раз, потому что
+    /// `build` может их записать.
+    fn cache_key(&mut self, path: &Path) -> Option<u64> {
+        let own = file_mtime(path)?;
+        let Some(dir) = path.parent() else {
+            return Some(own);
+        };
+        let siblings = self
+            .dirs
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| DirMtimes::scan(dir));
+        let shards = gguf_shard_set(path).and_then(|set| siblings.shard_sets.get(&set).copied());
+        let manifests = [resolve_manifest_path(path), dir.join(MANIFEST_FILE_NAME)];
+        Some(
+            manifests
+                .iter()
+                .filter_map(|p| file_mtime(p))
+                .chain(siblings.mmproj)
+                .chain(shards)
+                .fold(own, u64::max),
+        )
+    }
+}
+
+fn file_mtime(path: &Path) -> Option<u64> {
+    fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model_info(path: &Path) -> ModelInfo {
+        serde_json::from_value(serde_json::json!({
+            "name": "m",
+            "path": path,
+            "file_size": 1,
+            "format": "gguf",
+            "candle_compatible": true,
+            "validation_status": { "level": "ok" },
+            "created_at": "2024-01-01T00:00:00Z",
+            "metadata": {
+                "format_version": 3,
+                "alignment": 32,
+                "tensor_count": 1,
+                "metadata_kv_count": 0
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn reuses_entries_until_forced_and_prunes_deleted_files() {
+        let dir = std::env::temp_dir().join(format!("oxide-scan-cache-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("m.gguf");
+        fs::write(&file, b"gguf").unwrap();
+
+        let mut cache = ModelScanCache::default();
+        let mut stats = ScanCompleteEvent::default();
+        cache
+            .get_or_build(&file, false, &mut stats, |p| Ok(Some(model_info(p))))
+            .unwrap();
+        cache
+            .get_or_build(&file, false, &mut stats, |_| panic!("must be cached"))
+            .unwrap();
+        cache
+            .get_or_build(&file, true, &mut stats, |p| Ok(Some(model_info(p))))
+            .unwrap();
+        assert_eq!(
+            stats,
+            ScanCompleteEvent {
+                cached_count: 1,
+                refreshed_count: 1,
+                new_count: 1,
+            }
+        );
+
+        let cache_path = dir.join(SCAN_CACHE_FILE);
+        cache.save(&cache_path).unwrap();
+        let mut reloaded = ModelScanCache::load(&cache_path);
+        fs::remove_file(&file).unwrap();
+        assert_eq!(reloaded.prune_missing(), 1);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn rebuilds_when_a_sibling_manifest_changes() {
+        let dir = std::env::temp_dir().join(format!("oxide-scan-sibling-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("m.gguf");
+        fs::write(&file, b"gguf").unwrap();
+        let manifest = dir.join("m.gguf.oxide-manifest.json");
+        fs::write(&manifest, b"{}").unwrap();
+        let set_mtime = |path: &Path, secs: u64| {
+            fs::File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_modified(UNIX_EPOCH + std::time::Duration::from_secs(secs))
+                .unwrap();
+        };
+        set_mtime(&file, 1_000);
+        set_mtime(&manifest, 1_000);
+
+        let mut cache = ModelScanCache::default();
+        let mut stats = ScanCompleteEvent::default();
+        cache
+            .get_or_build(&file, false, &mut stats, |p| Ok(Some(model_info(p))))
+            .unwrap();
+        set_mtime(&manifest, 2_000);
+        cache
+            .get_or_build(&file, false, &mut stats, |p| Ok(Some(model_info(p))))
+            .unwrap();
+        assert_eq!(stats.refreshed_count, 1);
+        assert_eq!(stats.cached_count, 0);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn next_scan_sees_changed_shards_and_projectors() {
+        let dir = std::env::temp_dir().join(format!("oxide-scan-shards-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let set_mtime = |path: &Path, secs: u64| {
+            fs::File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_modified(UNIX_EPOCH + std::time::Duration::from_secs(secs))
+                .unwrap();
+        };
+        let first = dir.join("m-00001-of-00002.gguf");
+        let second = dir.join("m-00002-of-00002.gguf");
+        let other = dir.join("other.gguf");
+        let mmproj = dir.join("mmproj-F16.gguf");
+        for path in [&first, &second, &other, &mmproj] {
+            fs::write(path, b"gguf").unwrap();
+            set_mtime(path, 1_000);
+        }
+        let cache_path = dir.join(SCAN_CACHE_FILE);
+        let scan = |files: &[&PathBuf]| {
+            let mut cache = ModelScanCache::load(&cache_path);
+            let mut stats = ScanCompleteEvent::default();
+            for file in files {
+                cache
+                    .get_or_build(file, false, &mut stats, |p| Ok(Some(model_info(p))))
+                    .unwrap();
+            }
+            cache.save(&cache_path).unwrap();
+            stats
+        };
+
+        assert_eq!(scan(&[&first, &other]).new_count, 2);
+        set_mtime(&second, 2_000);
+        let stats = scan(&[&first, &other]);
+        assert_eq!((stats.refreshed_count, stats.cached_count), (1, 1));
+
+        set_mtime(&mmproj, 3_000);
+        assert_eq!(scan(&[&first, &other]).refreshed_count, 2);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
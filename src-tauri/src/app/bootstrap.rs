@@ -8,8 +8,9 @@ use crate::core::audio_capture::AudioCaptureState;
 use crate::core::device::select_device;
 use crate::core::performance::StartupTracker;
 use crate::core::rayon_pool::init_global_low_priority_pool;
+use crate::core::settings_watcher::SettingsWatcher;
 use crate::core::state::{ModelState, SharedState};
-use crate::core::thread_priority::set_current_thread_above_normal;
+use crate::core::thread_priority::{ThreadPriority, set_current_thread_above_normal};
 use crate::core::types::DevicePreference;
 use crate::i18n;
 use crate::log_load_warn;
@@ -59,6 +60,7 @@ fn spawn_startup_tracker(
 pub fn run() {
     // Инициализируем i18n
     i18n::init();
+    crate::core::log::init();
 
     let shared = build_shared_state();
     let performance_monitor = {
@@ -99,13 +101,58 @@ pub fn run() {
             sql: "ALTER TABLE messages ADD COLUMN thinking TEXT NOT NULL DEFAULT '';",
             kind: MigrationKind::Up,
         },
+        Migration {
+            version: 3,
+            description: "create session_tags table",
+            sql: "
+                CREATE TABLE IF NOT EXISTS session_tags (
+                    session_id TEXT NOT NULL,
+                    tag TEXT NOT NULL,
+                    PRIMARY KEY(session_id, tag),
+                    FOREIGN KEY(session_id) REFERENCES sessions(id) ON DELETE CASCADE
+                );
+                CREATE INDEX IF NOT EXISTS idx_session_tags_tag ON session_tags(tag);
+            ",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 4,
+            description: "create full-text index on message content",
+            sql: "
+                CREATE VIRTUAL TABLE IF NOT EXISTS messages_fts USING fts5(
+                    content,
+                    content='messages',
+                    content_rowid='id'
+                );
+                CREATE TRIGGER IF NOT EXISTS messages_fts_insert AFTER INSERT ON messages BEGIN
+                    INSERT INTO messages_fts(rowid, content) VALUES (new.id, new.content);
+                END;
+                CREATE TRIGGER IF NOT EXISTS messages_fts_delete AFTER DELETE ON messages BEGIN
+                    INSERT INTO messages_fts(messages_fts, rowid, content)
+                        VALUES ('delete', old.id, old.content);
+                END;
+                CREATE TRIGGER IF NOT EXISTS messages_fts_update AFTER UPDATE OF content ON messages BEGIN
+                    INSERT INTO messages_fts(messages_fts, rowid, content)
+                        VALUES ('delete', old.id, old.content);
+                    INSERT INTO messages_fts(rowid, content) VALUES (new.id, new.content);
+                END;
+                INSERT INTO messages_fts(messages_fts) VALUES ('rebuild');
+            ",
+            kind: MigrationKind::Up,
+        },
     ];
 
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_store::Builder::new().build())
+        .plugin(
+            tauri_plugin_global_shortcut::Builder::new()
+                .with_handler(crate::core::hotkeys::handle_shortcut)
+                .build(),
+        )
         .plugin(
             Builder::default()
                 .add_migrations("sqlite:chat_history.db", migrations)
@@ -113,35 +160,66 @@ pub fn run() {
         )
         .manage(shared.clone())
         .manage(AudioCaptureState::new())
+        .manage(crate::api::openai_server::OpenAIServerSlot::default())
         .invoke_handler(tauri::generate_handler![
             crate::api::greet,
             get_app_info,
             crate::api::load_model,
             crate::api::unload_model,
             crate::api::cancel_model_loading,
+            crate::api::take_recovered_sessions,
             crate::api::generate_stream,
             crate::api::cancel_generation,
+            crate::api::validate_output_schema,
+            crate::api::code_completion,
             crate::api::set_device,
             crate::api::is_model_loaded,
             crate::api::get_chat_template,
             crate::api::render_prompt,
             crate::api::get_device_info,
+            crate::api::get_simd_capabilities,
+            crate::api::register_custom_backend,
+            crate::api::unregister_custom_backend,
+            crate::api::list_registered_backends,
             crate::api::probe_cuda,
+            crate::api::get_system_info,
             crate::api::get_precision_policy,
             crate::api::set_precision_policy,
             crate::api::get_precision,
             crate::api::set_precision,
             crate::api::get_rayon_thread_limit,
             crate::api::set_rayon_thread_limit,
+            crate::api::configure_inference_thread_pool,
+            crate::api::get_inference_thread_priority,
+            crate::api::set_inference_thread_priority,
             crate::api::gguf_list_metadata_keys_from_path,
             crate::api::gguf_list_metadata_keys,
             crate::api::get_experimental_features_enabled,
+            crate::api::get_audit_log,
+            crate::api::export_settings_bundle,
+            crate::api::import_settings_bundle,
+            crate::api::list_system_prompts,
+            crate::api::add_system_prompt,
+            crate::api::update_system_prompt,
+            crate::api::delete_system_prompt,
+            crate::api::get_system_prompt_history,
+            crate::api::list_hotkeys,
+            crate::api::register_hotkey,
+            crate::api::unregister_hotkey,
+            crate::api::generate_diagnostic_report,
+            crate::api::copy_diagnostic_to_clipboard,
             crate::api::set_experimental_features_enabled,
             crate::api::performance_api::get_performance_metrics,
+            crate::api::performance_api::get_performance_dashboard,
+            crate::api::performance_api::get_load_timing_settings,
+            crate::api::performance_api::set_load_timing_settings,
+            crate::api::performance_api::get_model_memory_stats,
             crate::api::performance_api::get_average_duration,
             crate::api::performance_api::get_memory_usage,
             crate::api::performance_api::clear_performance_metrics,
+            crate::api::performance_api::clear_performance_baseline,
             crate::api::performance_api::get_startup_metrics,
+            crate::api::performance_api::get_load_timing_reports,
             crate::api::performance_api::get_system_usage,
             crate::api::transcribe_audio,
             crate::api::start_voice_recording,
@@ -153,11 +231,25 @@ pub fn run() {
             crate::api::local_models::parse_gguf_metadata,
             crate::api::local_models::scan_models_folder,
             crate::api::local_models::scan_local_models_folder,
+            crate::api::local_models::scan_models_folders,
+            crate::api::local_models::scan_models_with_filters,
             crate::api::local_models::search_huggingface_gguf,
             crate::api::local_models::download_hf_model_file,
+            crate::api::local_models::check_model_gating,
+            crate::api::local_models::accept_model_gating,
+            crate::api::local_models::get_model_commits,
+            crate::api::local_models::recommend_quantization,
+            crate::api::model_import::import_models_from_ollama,
+            crate::api::model_import::import_lm_studio_models,
             crate::api::local_models::get_model_readme,
             crate::api::local_models::delete_local_model,
             crate::api::local_models::update_model_manifest,
+            crate::api::local_models::get_proxy_settings,
+            crate::api::local_models::set_proxy_settings,
+            crate::api::local_models::get_models_storage_settings,
+            crate::api::local_models::set_models_storage_settings,
+            crate::api::local_models::register_models_dir,
+            crate::api::local_models::manifest_version_stats,
             crate::api::model_cards::get_model_cards,
             crate::api::model_cards::import_model_cards,
             crate::api::model_cards::reset_model_cards,
@@ -169,9 +261,22 @@ pub fn run() {
             crate::api::download_manager::cancel_download,
             crate::api::download_manager::remove_download_entry,
             crate::api::download_manager::clear_download_history,
+            crate::api::download_manager::deduplicate_history,
+            crate::api::download_manager::set_download_priority,
+            crate::api::download_manager::get_download_settings,
+            crate::api::download_manager::set_download_settings,
             crate::api::get_locale,
             crate::api::set_locale,
+            crate::api::detect_system_locale,
+            crate::api::set_log_level,
+            crate::api::get_recent_logs,
+            crate::api::get_log_file_path,
+            crate::api::open_log_folder,
             crate::api::openai_server::get_server_config,
+            crate::api::openai_server::get_openai_server_settings,
+            crate::api::openai_server::set_openai_server_settings,
+            crate::api::embedding_cache::get_local_rag_settings,
+            crate::api::embedding_cache::set_local_rag_settings,
             crate::api::prefix_cache_api::get_prefix_cache_info,
             crate::api::prefix_cache_api::set_prefix_cache_enabled,
             crate::api::prefix_cache_api::clear_prefix_cache,
@@ -182,6 +287,76 @@ pub fn run() {
             let _ = set_current_thread_above_normal();
 
             let handle = app.handle();
+            // --settings-bundle <path>: восстановить настройки до их загрузки
+            if let Some(bundle) = crate::core::settings_bundle::settings_bundle_arg() {
+                match crate::core::settings_bundle::import_startup_bundle(handle, &bundle) {
+                    Ok(Some(result)) => log::info!(
+                        "Settings bundle {} imported: {:?}",
+                        bundle.display(),
+                        result.restored
+                    ),
+                    Ok(None) => log::info!(
+                        "Settings bundle {} was already imported, skipping",
+                        bundle.display()
+                    ),
+                    Err(err) => eprintln!("Failed to import settings bundle: {}", err),
+                }
+            }
+            // Локаль: сохранённая, а при первом запуске — системная, если есть перевод
+            match ModelState::load_locale(handle) {
+                Ok(Some(saved)) => {
+                    if let Ok(locale) = saved.parse::<i18n::Locale>() {
+                        i18n::set_locale(locale);
+                    }
+                }
+                Ok(None) => {
+                    if let Some(locale) = i18n::detect_system_locale() {
+                        i18n::set_locale(locale);
+                        if let Err(err) = ModelState::save_locale(handle, locale.as_str()) {
+                            eprintln!("Failed to save detected locale: {}", err);
+                        }
+                    }
+                }
+                Err(err) => eprintln!("Failed to load saved locale: {}", err),
+            }
+            // Лог на диск: profile_dir/logs, с ротацией по размеру
+            if let Err(err) = ModelState::ensure_profile_dir(handle).and_then(|dir| {
+                let settings = ModelState::load_log_file_settings(handle)?;
+                crate::core::log::init_file_logging(dir.join(crate::core::log::LOGS_DIR), settings)
+            }) {
+                eprintln!("Failed to enable file logging: {}", err);
+            }
+            match ModelState::load_log_levels(handle) {
+                Ok(levels) => {
+                    for (component, level) in levels {
+                        if let Err(err) = crate::core::log::set_component_level(&component, &level) {
+                            log::warn!("Ignoring saved log level for {}: {}", component, err);
+                        }
+                    }
+                }
+                Err(err) => eprintln!("Failed to load saved log levels: {}", err),
+            }
+            crate::api::device::log_simd_capabilities();
+            match ModelState::load_custom_backends(handle) {
+                Ok(backends) => crate::models::registry::set_custom_backends(backends),
+                Err(err) => log::warn!("Failed to load custom backends: {}", err),
+            }
+            // Сессии, оставшиеся после падения: модель была загружена, но не выгружена.
+            // Окно ещё не создано, поэтому frontend забирает их через take_recovered_sessions
+            let recovered = crate::core::session_store::SessionPersistenceStore::new(handle)
+                .map(|store| store.recover())
+                .unwrap_or_else(|err| {
+                    log::warn!("Failed to read persisted sessions: {}", err);
+                    Vec::new()
+                });
+            for session in &recovered {
+                log::info!("Recovered session for {}", session.model_id);
+            }
+            tauri::Manager::manage(
+                app,
+                crate::core::session_store::RecoveredSessions::new(recovered),
+            );
+
             match ModelState::load_thread_limit(handle) {
                 Ok(limit) => {
                     // Leave 1 core free by default to keep UI responsive during heavy loads.
@@ -203,6 +378,50 @@ pub fn run() {
                     eprintln!("Failed to load saved Rayon thread limit: {}", err);
                 }
             }
+            match ModelState::load_inference_priority(handle) {
+                Ok(ThreadPriority::Normal) => {}
+                Ok(priority) => {
+                    if let Err(err) =
+                        crate::core::rayon_pool::set_inference_thread_priority(priority)
+                    {
+                        log_load_warn!("failed to apply inference thread priority: {}", err);
+                    }
+                }
+                Err(err) => eprintln!("Failed to load saved thread priority: {}", err),
+            }
+            match ModelState::load_proxy_settings(handle) {
+                Ok(settings) => crate::api::local_models::apply_proxy_settings(settings),
+                Err(err) => eprintln!("Failed to load saved proxy settings: {}", err),
+            }
+            match ModelState::load_load_timing_settings(handle) {
+                Ok(settings) => crate::api::model_loading::apply_load_timing_settings(&settings),
+                Err(err) => eprintln!("Failed to load load timing settings: {}", err),
+            }
+            match ModelState::load_local_rag_settings(handle) {
+                Ok(settings) => crate::api::embedding_cache::apply_local_rag_settings(&settings),
+                Err(err) => eprintln!("Failed to load local RAG settings: {}", err),
+            }
+            match ModelState::load_openai_server_settings(handle) {
+                Ok(settings) => {
+                    crate::api::openai_server::apply_openai_server_settings(&settings)
+                }
+                Err(err) => eprintln!("Failed to load API server settings: {}", err),
+            }
+            match ModelState::load_models_storage_settings(handle) {
+                Ok(storage) if !storage.models_dirs.is_empty() => {
+                    let dirs: Vec<std::path::PathBuf> =
+                        storage.models_dirs.iter().map(Into::into).collect();
+                    std::thread::spawn(move || {
+                        let migrated =
+                            crate::api::model_manager::manifest::migrate_manifests_in(&dirs);
+                        if migrated > 0 {
+                            log::info!("Migrated {} model manifests to the current version", migrated);
+                        }
+                    });
+                }
+                Ok(_) => {}
+                Err(err) => eprintln!("Failed to load models storage settings: {}", err),
+            }
             spawn_startup_tracker(app.handle().clone(), performance_monitor.clone());
 
             // Start the model scheduler keep-alive task
@@ -217,25 +436,82 @@ pub fn run() {
 
                 loop {
                     interval.tick().await;
-                    if let Ok(mut guard) = scheduler_state.lock() {
-                        if let Some(unloaded_id) = guard.scheduler.check_expiration()
-                            && let Err(e) = app.emit("model_unloaded", &unloaded_id)
-                        {
+                    // Файловый ввод-вывод — только после освобождения мьютекса состояния
+                    let unloaded_id = match scheduler_state.lock() {
+                        Ok(mut guard) => guard.scheduler.check_expiration(),
+                        Err(_) => {
+                            log::error!("Scheduler keep-alive task: failed to lock state");
+                            continue;
+                        }
+                    };
+                    if let Some(unloaded_id) = unloaded_id {
+                        crate::api::commands::model::clear_persisted_sessions(&app);
+                        crate::core::audit_log::record(
+                            &app,
+                            "model_unload",
+                            serde_json::json!({
+                                "model_id": unloaded_id,
+                                "reason": "keep_alive_expired",
+                            }),
+                        );
+                        if let Err(e) = app.emit("model_unloaded", &unloaded_id) {
                             log::error!("Failed to emit model_unloaded event: {}", e);
                         }
-                    } else {
-                        log::error!("Scheduler keep-alive task: failed to lock state");
                     }
                 }
             });
 
+            // Remove partial downloads orphaned by a previous crash
+            let cleanup_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) =
+                    crate::api::download_manager::cleanup_stale_partial_files(&cleanup_handle).await
+                {
+                    log::warn!("Failed to clean up stale partial downloads: {}", e);
+                }
+            });
+            match ModelState::load_download_settings(handle) {
+                Ok(settings) => {
+                    let download_handle = app.handle().clone();
+                    tauri::async_runtime::spawn(async move {
+                        crate::api::download_manager::apply_download_settings(
+                            &download_handle,
+                            &settings,
+                        )
+                        .await;
+                    });
+                }
+                Err(err) => eprintln!("Failed to load download settings: {}", err),
+            }
+
             // Start OpenAI-compatible API server
             let openai_state = shared.clone();
+            let openai_app = app.handle().clone();
             tauri::async_runtime::spawn(async move {
-                use crate::api::openai_server::OPENAI_PORT;
-                match crate::api::openai_server::start_server(openai_state, OPENAI_PORT).await {
-                    Ok(_shutdown_tx) => {
-                        log::info!("OpenAI API server started on port {}", OPENAI_PORT);
+                use crate::api::openai_server::{
+                    OPENAI_PORT, OPENAI_PORT_RANGE_MAX, OpenAIServerSlot,
+                    find_available_port_in_range,
+                };
+                let port = match find_available_port_in_range(OPENAI_PORT, OPENAI_PORT_RANGE_MAX)
+                {
+                    Ok(port) => port,
+                    Err(e) => {
+                        log::error!("Failed to pick OpenAI API server port: {}", e);
+                        return;
+                    }
+                };
+                match crate::api::openai_server::start_server(openai_state, port).await {
+                    Ok(handle) => {
+                        log::info!("OpenAI API server started on port {}", port);
+                        let slot = tauri::Manager::state::<OpenAIServerSlot>(&openai_app);
+                        if let Ok(mut guard) = slot.0.lock() {
+                            *guard = Some(handle);
+                        }
+                        crate::core::audit_log::record(
+                            &openai_app,
+                            "openai_server_start",
+                            serde_json::json!({ "port": port }),
+                        );
                     }
                     Err(e) => {
                         log::error!("Failed to start OpenAI API server: {}", e);
@@ -243,12 +519,55 @@ pub fn run() {
                 }
             });
 
+            match crate::core::hotkeys::register_saved_hotkeys(app.handle()) {
+                Ok(registrations) => {
+                    for r in registrations.iter().filter(|r| !r.registered) {
+                        log::warn!("Global hotkey {} skipped", r.binding.hotkey);
+                    }
+                }
+                Err(err) => log::warn!("Failed to register global hotkeys: {}", err),
+            }
+
+            match SettingsWatcher::start(app.handle()) {
+                Ok(watcher) => {
+                    tauri::Manager::manage(app, watcher);
+                }
+                Err(err) => log::warn!("Settings watcher disabled: {}", err),
+            }
+
             #[cfg(debug_assertions)]
             if let Some(main_window) = app.get_webview_window("main") {
                 main_window.open_devtools();
             }
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                // Штатный выход: восстанавливать после него нечего
+                crate::api::commands::model::clear_persisted_sessions(app);
+                if let Some(watcher) = tauri::Manager::try_state::<SettingsWatcher>(app) {
+                    watcher.stop();
+                }
+                drain_openai_server(app);
+            }
+        });
+}
+
+/// Даёт запросам к OpenAI API завершиться перед выходом
+fn drain_openai_server(app: &tauri::AppHandle) {
+    let Some(slot) = tauri::Manager::try_state::<crate::api::openai_server::OpenAIServerSlot>(app)
+    else {
+        return;
+    };
+    let handle = slot.0.lock().ok().and_then(|mut guard| guard.take());
+    if let Some(handle) = handle {
+        let stats = tauri::async_runtime::block_on(handle.shutdown());
+        log::info!(
+            "OpenAI API server stopped: {} request(s) drained, {} timed out",
+            stats.completed,
+            stats.timed_out
+        );
+    }
 }
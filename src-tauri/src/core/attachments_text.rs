@@ -28,7 +28,30 @@ fn is_txt_md(att: &Attachment) -> bool {
     ok
 }
 
+fn size_limit_error(att: &Attachment, size: u64) -> String {
+    let name = att
+        .name
+        .as_deref()
+        .or(att.path.as_deref())
+        .unwrap_or("attachment");
+    format!(
+        "Attachment exceeds size limit: {} ({:.2}MB > {}MB)",
+        name,
+        size as f64 / (1024.0 * 1024.0),
+        MAX_SIZE_BYTES / (1024 * 1024)
+    )
+}
+
+/// Проверка по заявленному размеру до чтения/декодирования байтов.
+fn check_declared_size(att: &Attachment) -> Result<(), String> {
+    match att.size {
+        Some(size) if size > MAX_SIZE_BYTES => Err(size_limit_error(att, size)),
+        _ => Ok(()),
+    }
+}
+
 fn read_bytes(att: &Attachment) -> Result<Option<Vec<u8>>, String> {
+    check_declared_size(att)?;
     if let Some(b64) = &att.bytes_b64 {
         // Быстрая оценка размера без декодирования: ~3/4 длины base64
         let est = (b64.len() as u64) * 3 / 4;
@@ -116,3 +139,35 @@ pub fn gather_text_from_attachments(attachments: &[Attachment]) -> Result<String
     }
     Ok(out)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn txt_attachment(size: Option<u64>) -> Attachment {
+        Attachment {
+            kind: None,
+            mime: Some("text/plain".to_string()),
+            name: Some("notes.txt".to_string()),
+            path: None,
+            bytes_b64: Some(base64::engine::general_purpose::STANDARD.encode("hello")),
+            size,
+        }
+    }
+
+    #[test]
+    fn declared_size_over_limit_is_rejected_before_reading() {
+        let err =
+            gather_text_from_attachments(&[txt_attachment(Some(MAX_SIZE_BYTES + 1))]).unwrap_err();
+        assert!(err.starts_with("Attachment exceeds size limit: notes.txt"));
+        assert!(err.contains("> 20MB"));
+    }
+
+    #[test]
+    fn attachment_within_limit_is_read() {
+        let text = gather_text_from_attachments(&[txt_attachment(Some(5))]).unwrap();
+        assert_eq!(text, "[attached: notes.txt]\nhello");
+        let text = gather_text_from_attachments(&[txt_attachment(None)]).unwrap();
+        assert_eq!(text, "[attached: notes.txt]\nhello");
+    }
+}
@@ -0,0 +1,191 @@
+//! Журнал аудита: загрузка/выгрузка моделей, запуск OpenAI-сервера,
+//! изменения настроек и удаление данных.
+//!
+//! События пишутся append-only в `profile_dir/audit.log` в формате JSONL.
+//! При превышении `ROTATE_AUDIT_LOG_AFTER_MB` текущий файл переименовывается
+//! в `audit.log.1` (предыдущая копия перезаписывается).
+
+use crate::core::state::ModelState;
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+pub const AUDIT_LOG_FILE: &str = "audit.log";
+
+/// Размер файла, после которого журнал ротируется.
+pub const ROTATE_AUDIT_LOG_AFTER_MB: u64 = 10;
+
+/// Сериализует запись и ротацию между потоками.
+static WRITE_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEvent {
+    pub timestamp: String,
+    pub event_type: String,
+    pub details: serde_json::Value,
+    #[serde(default)]
+    pub user_agent: Option<String>,
+}
+
+impl AuditEvent {
+    pub fn new(event_type: impl Into<String>, details: serde_json::Value) -> Self {
+        Self {
+            timestamp: Utc::now().to_rfc3339(),
+            event_type: event_type.into(),
+            details,
+            user_agent: None,
+        }
+    }
+}
+
+pub struct AuditLog {
+    path: PathBuf,
+    rotate_after_bytes: u64,
+}
+
+impl AuditLog {
+    pub fn new(app: &AppHandle) -> Result<Self, String> {
+        let path = ModelState::ensure_profile_dir(app)?.join(AUDIT_LOG_FILE);
+        Ok(Self::from_path(
+            path,
+            ROTATE_AUDIT_LOG_AFTER_MB * 1024 * 1024,
+        ))
+    }
+
+    pub fn from_path(path: PathBuf, rotate_after_bytes: u64) -> Self {
+        Self {
+            path,
+            rotate_after_bytes,
+        }
+    }
+
+    fn rotated_path(&self) -> PathBuf {
+        let mut name = self.path.as_os_str().to_owned();
+        name.push(".1");
+        PathBuf::from(name)
+    }
+
+    pub fn append(&self, event: &AuditEvent) -> Result<(), String> {
+        let _lock = WRITE_LOCK.lock().map_err(|e| e.to_string())?;
+
+        if let Ok(meta) = fs::metadata(&self.path)
+            && meta.len() >= self.rotate_after_bytes
+        {
+            fs::rename(&self.path, self.rotated_path())
+                .map_err(|e| format!("Failed to rotate audit log: {}", e))?;
+        }
+
+        let mut line = serde_json::to_string(event)
+            .map_err(|e| format!("Failed to serialize audit event: {}", e))?;
+        line.push('\n');
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Failed to open audit log: {}", e))?;
+        file.write_all(line.as_bytes())
+            .map_err(|e| format!("Failed to write audit log: {}", e))
+    }
+
+    /// Последние `limit` событий (по возрастанию времени), опционально не старше `since` (RFC 3339).
+    pub fn read(&self, limit: usize, since: Option<&str>) -> Result<Vec<AuditEvent>, String> {
+        let since = since
+            .map(|s| {
+                DateTime::parse_from_rfc3339(s)
+                    .map_err(|e| format!("Invalid 'since' timestamp: {}", e))
+            })
+            .transpose()?;
+
+        let file = match fs::File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(format!("Failed to open audit log: {}", e)),
+        };
+
+        let mut events: Vec<AuditEvent> = BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str::<AuditEvent>(&line).ok())
+            .filter(|event| match since {
+                Some(since) => {
+                    DateTime::parse_from_rfc3339(&event.timestamp).is_ok_and(|ts| ts >= since)
+                }
+                None => true,
+            })
+            .collect();
+
+        if events.len() > limit {
+            events.drain(..events.len() - limit);
+        }
+        Ok(events)
+    }
+}
+
+/// Записать событие аудита. Ошибки записи не прерывают основную операцию.
+pub fn record(app: &AppHandle, event_type: &str, details: serde_json::Value) {
+    let result =
+        AuditLog::new(app).and_then(|log| log.append(&AuditEvent::new(event_type, details)));
+    if let Err(e) = result {
+        log::warn!("Failed to record audit event '{}': {}", event_type, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn temp_log(name: &str, rotate_after_bytes: u64) -> (PathBuf, AuditLog) {
+        let dir = std::env::temp_dir().join(format!("oxide-audit-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let log = AuditLog::from_path(dir.join(AUDIT_LOG_FILE), rotate_after_bytes);
+        (dir, log)
+    }
+
+    #[test]
+    fn append_and_read_respects_limit_and_since() {
+        let (dir, log) = temp_log("read", u64::MAX);
+
+        let mut old = AuditEvent::new("model_load", json!({ "model_id": "a" }));
+        old.timestamp = "2020-01-01T00:00:00+00:00".to_string();
+        log.append(&old).unwrap();
+        log.append(&AuditEvent::new("model_unload", json!({})))
+            .unwrap();
+        log.append(&AuditEvent::new("server_start", json!({ "port": 11434 })))
+            .unwrap();
+
+        let all = log.read(10, None).unwrap();
+        assert_eq!(all.len(), 3);
+
+        let last = log.read(1, None).unwrap();
+        assert_eq!(last[0].event_type, "server_start");
+
+        let recent = log.read(10, Some("2024-01-01T00:00:00Z")).unwrap();
+        assert_eq!(recent.len(), 2);
+        assert!(log.read(10, Some("not a date")).is_err());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn rotates_when_size_exceeded() {
+        let (dir, log) = temp_log("rotate", 1);
+
+        log.append(&AuditEvent::new("first", json!({}))).unwrap();
+        log.append(&AuditEvent::new("second", json!({}))).unwrap();
+
+        let current = log.read(10, None).unwrap();
+        assert_eq!(current.len(), 1);
+        assert_eq!(current[0].event_type, "second");
+        assert!(log.rotated_path().exists());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -2,6 +2,7 @@ use crate::core::types::DevicePreference;
 use crate::{log_device, log_device_error};
 use candle::Device;
 use candle::utils::{cuda_is_available, metal_is_available};
+use std::path::{Path, PathBuf};
 
 pub fn select_device(pref: Option<DevicePreference>) -> Device {
     match pref.unwrap_or(DevicePreference::Auto) {
@@ -61,3 +62,205 @@ pub fn device_label(d: &Device) -> &'static str {
         Device::Metal(_) => "Metal",
     }
 }
+
+/// Сведения о GPU NVIDIA, полученные через `nvidia-smi`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NvidiaGpuInfo {
+    pub name: String,
+    pub driver_version: String,
+    /// Максимальная версия CUDA, которую поддерживает драйвер
+    pub cuda_version: Option<String>,
+    pub total_vram_mb: u64,
+}
+
+/// Разбор вывода `nvidia-smi -q -i 0`: имя, драйвер, версия CUDA и объём
+/// видеопамяти из секции `FB Memory Usage`.
+pub fn parse_nvidia_smi_query(output: &str) -> Option<NvidiaGpuInfo> {
+    let mut name = None;
+    let mut driver_version = None;
+    let mut cuda_version = None;
+    let mut total_vram_mb = None;
+    let mut in_fb_memory = false;
+    for line in output.lines() {
+        let line = line.trim();
+        let Some((key, value)) = line.split_once(':') else {
+            // Заголовок секции без значения
+            in_fb_memory = line == "FB Memory Usage";
+            continue;
+        };
+        let (key, value) = (key.trim(), value.trim());
+        match key {
+            "Product Name" if name.is_none() => name = Some(value.to_string()),
+            "Driver Version" => driver_version = Some(value.to_string()),
+            "CUDA Version" => cuda_version = Some(value.to_string()),
+            "Total" if in_fb_memory && total_vram_mb.is_none() => {
+                total_vram_mb = value.trim_end_matches("MiB").trim().parse::<u64>().ok();
+            }
+            _ => {}
+        }
+    }
+    Some(NvidiaGpuInfo {
+        name: name.filter(|s| !s.is_empty())?,
+        driver_version: driver_version.filter(|s| !s.is_empty())?,
+        cuda_version: cuda_version.filter(|s| !s.is_empty() && s != "N/A"),
+        total_vram_mb: total_vram_mb?,
+    })
+}
+
+/// Опрашивает первый GPU NVIDIA одним вызовом `nvidia-smi`. Возвращает `None`,
+/// если утилита недоступна.
+pub fn query_nvidia_gpu() -> Option<NvidiaGpuInfo> {
+    let output = std::process::Command::new("nvidia-smi")
+        .args(["-q", "-i", "0"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_nvidia_smi_query(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Занятая видеопамять первого GPU NVIDIA в МБ.
+pub fn query_nvidia_vram_used_mb() -> Option<u64> {
+    let output = std::process::Command::new("nvidia-smi")
+        .args(["--query-gpu=memory.used", "--format=csv,noheader,nounits"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout
+        .lines()
+        .find_map(|line| line.trim().parse::<u64>().ok())
+}
+
+/// Сведения о GPU Intel (Arc / oneAPI Level Zero).
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct IntelGpuInfo {
+    /// Путь к найденному драйверу Level Zero.
+    pub driver_path: String,
+}
+
+/// Обнаружение GPU Intel по наличию драйвера Level Zero.
+///
+/// Candle не поддерживает SYCL, поэтому результат используется только для отображения.
+pub fn detect_intel_gpu() -> Option<IntelGpuInfo> {
+    #[cfg(target_os = "linux")]
+    let (names, dirs) = {
+        let mut dirs: Vec<PathBuf> = std::env::var_os("LD_LIBRARY_PATH")
+            .map(|paths| std::env::split_paths(&paths).collect())
+            .unwrap_or_default();
+        dirs.extend(
+            ["/usr/lib/x86_64-linux-gnu", "/usr/lib64", "/usr/lib"]
+                .into_iter()
+                .map(PathBuf::from),
+        );
+        (&["libze_intel_gpu.so.1", "libze_intel_gpu.so"][..], dirs)
+    };
+    #[cfg(target_os = "windows")]
+    let (names, dirs) = {
+        let root = std::env::var_os("SystemRoot").unwrap_or_else(|| "C:\\Windows".into());
+        (
+            &["ze_intel_gpu64.dll"][..],
+            vec![PathBuf::from(root).join("System32")],
+        )
+    };
+    #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+    let (names, dirs): (&[&str], Vec<PathBuf>) = (&[], Vec::new());
+
+    find_level_zero_driver(&dirs, names, |path| path.exists()).map(|path| IntelGpuInfo {
+        driver_path: path.to_string_lossy().into_owned(),
+    })
+}
+
+/// Первый найденный драйвер Level Zero: каталоги по порядку, в каждом —
+/// имена по порядку. Проверка существования передаётся снаружи.
+fn find_level_zero_driver(
+    dirs: &[PathBuf],
+    names: &[&str],
+    exists: impl Fn(&Path) -> bool,
+) -> Option<PathBuf> {
+    dirs.iter()
+        .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+        .find(|path| exists(path))
+}
+
+/// Возможности CPU, важные для ARM64 (Apple Silicon, Windows on ARM).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct CpuFeatures {
+    pub is_arm64: bool,
+    pub has_neon: bool,
+    pub has_sve: bool,
+    pub is_apple_silicon: bool,
+}
+
+/// Архитектура определяется при компиляции, расширения — во время выполнения.
+pub fn detect_cpu_features() -> CpuFeatures {
+    #[cfg(target_arch = "aarch64")]
+    let (has_neon, has_sve) = (
+        std::arch::is_aarch64_feature_detected!("neon"),
+        std::arch::is_aarch64_feature_detected!("sve"),
+    );
+    #[cfg(not(target_arch = "aarch64"))]
+    let (has_neon, has_sve) = (false, false);
+
+    let is_arm64 = cfg!(target_arch = "aarch64");
+    CpuFeatures {
+        is_arm64,
+        has_neon,
+        has_sve,
+        is_apple_silicon: is_arm64 && cfg!(target_os = "macos"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_level_zero_driver_in_search_order() {
+        let dirs = [PathBuf::from("/opt/intel/lib"), PathBuf::from("/usr/lib64")];
+        let names = ["libze_intel_gpu.so.1", "libze_intel_gpu.so"];
+        let opt_so = dirs[0].join(names[1]);
+        let lib64_so1 = dirs[1].join(names[0]);
+        let lib64_so = dirs[1].join(names[1]);
+
+        let found = find_level_zero_driver(&dirs, &names, |path| path == lib64_so);
+        assert_eq!(found.as_ref(), Some(&lib64_so));
+        // Earlier directories win over earlier names
+        let found =
+            find_level_zero_driver(&dirs, &names, |path| path == lib64_so1 || path == opt_so);
+        assert_eq!(found.as_ref(), Some(&opt_so));
+        assert_eq!(find_level_zero_driver(&dirs, &names, |_| false), None);
+        assert_eq!(find_level_zero_driver(&[], &names, |_| true), None);
+    }
+
+    #[test]
+    fn parses_nvidia_smi_query_output() {
+        let output = "\
+==============NVSMI LOG==============
+
+Timestamp                                 : Mon Jun  3 10:00:00 2024
+Driver Version                            : 550.54.14
+CUDA Version                              : 12.4
+
+Attached GPUs                             : 1
+GPU 00000000:01:00.0
+    Product Name                          : NVIDIA GeForce RTX 4090
+    FB Memory Usage
+        Total                             : 24564 MiB
+        Reserved                          : 346 MiB
+        Used                              : 1024 MiB
+    BAR1 Memory Usage
+        Total                             : 256 MiB
+";
+        let info = parse_nvidia_smi_query(output).unwrap();
+        assert_eq!(info.name, "NVIDIA GeForce RTX 4090");
+        assert_eq!(info.driver_version, "550.54.14");
+        assert_eq!(info.cuda_version.as_deref(), Some("12.4"));
+        assert_eq!(info.total_vram_mb, 24564);
+
+        assert_eq!(parse_nvidia_smi_query("No devices were found"), None);
+    }
+}
@@ -0,0 +1,172 @@
+//! Глобальные горячие клавиши.
+//!
+//! Привязки хранятся в `profile_dir/global_hotkeys.json` и регистрируются через
+//! `tauri-plugin-global-shortcut` при запуске. Показ окна выполняется в backend,
+//! остальные действия передаются во frontend событием `global_hotkey`.
+
+use crate::core::settings_watcher;
+use crate::core::state::ModelState;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutEvent, ShortcutState};
+
+pub const GLOBAL_HOTKEYS_FILE: &str = "global_hotkeys.json";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HotkeyAction {
+    ShowWindow,
+    CopyLastResponse,
+    StartNewConversation,
+    TriggerGeneration,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GlobalHotkeyBinding {
+    pub hotkey: String,
+    pub action: HotkeyAction,
+}
+
+/// Результат регистрации. Если сочетание уже занято другим приложением,
+/// привязка сохраняется, но `registered = false` и заполнено `warning`.
+#[derive(Debug, Clone, Serialize)]
+pub struct HotkeyRegistration {
+    pub binding: GlobalHotkeyBinding,
+    pub registered: bool,
+    pub warning: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HotkeyTriggeredEvent {
+    pub action: HotkeyAction,
+}
+
+/// Разбирает строку вида `CommandOrControl+Shift+Space`.
+pub fn parse_hotkey(hotkey: &str) -> Result<Shortcut, String> {
+    hotkey
+        .parse::<Shortcut>()
+        .map_err(|e| format!("Invalid hotkey '{hotkey}': {e}"))
+}
+
+pub fn load_bindings(app: &AppHandle) -> Result<Vec<GlobalHotkeyBinding>, String> {
+    let path = ModelState::profile_dir(app)?.join(GLOBAL_HOTKEYS_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let data =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read global hotkeys: {}", e))?;
+    serde_json::from_str(&data).map_err(|e| format!("Failed to parse global hotkeys: {}", e))
+}
+
+pub fn save_bindings(app: &AppHandle, bindings: &[GlobalHotkeyBinding]) -> Result<(), String> {
+    let path = ModelState::ensure_profile_dir(app)?.join(GLOBAL_HOTKEYS_FILE);
+    settings_watcher::note_internal_write(&path);
+    let data = serde_json::to_string_pretty(bindings)
+        .map_err(|e| format!("Failed to serialize global hotkeys: {}", e))?;
+    fs::write(&path, data).map_err(|e| format!("Failed to write global hotkeys: {}", e))
+}
+
+/// Регистрирует сочетание в ОС. Ошибка регистрации не фатальна: чаще всего
+/// сочетание уже занято другим приложением.
+pub fn register_binding(
+    app: &AppHandle,
+    binding: GlobalHotkeyBinding,
+) -> Result<HotkeyRegistration, String> {
+    let shortcut = parse_hotkey(&binding.hotkey)?;
+    let global_shortcut = app.global_shortcut();
+    if global_shortcut.is_registered(shortcut) {
+        return Ok(HotkeyRegistration {
+            binding,
+            registered: true,
+            warning: None,
+        });
+    }
+
+    match global_shortcut.register(shortcut) {
+        Ok(()) => Ok(HotkeyRegistration {
+            binding,
+            registered: true,
+            warning: None,
+        }),
+        Err(e) => {
+            let warning = format!(
+                "Hotkey {} is not available (it may be used by another application): {}",
+                binding.hotkey, e
+            );
+            log::warn!("{}", warning);
+            Ok(HotkeyRegistration {
+                binding,
+                registered: false,
+                warning: Some(warning),
+            })
+        }
+    }
+}
+
+/// Регистрирует сохранённые привязки при запуске.
+pub fn register_saved_hotkeys(app: &AppHandle) -> Result<Vec<HotkeyRegistration>, String> {
+    load_bindings(app)?
+        .into_iter()
+        .map(|binding| register_binding(app, binding))
+        .collect()
+}
+
+fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+/// Обработчик плагина: находит привязку для нажатого сочетания и выполняет действие.
+pub fn handle_shortcut(app: &AppHandle, shortcut: &Shortcut, event: ShortcutEvent) {
+    if event.state() != ShortcutState::Pressed {
+        return;
+    }
+    let bindings = match load_bindings(app) {
+        Ok(bindings) => bindings,
+        Err(err) => {
+            log::warn!("Failed to load global hotkeys: {}", err);
+            return;
+        }
+    };
+    let Some(binding) = bindings
+        .into_iter()
+        .find(|b| parse_hotkey(&b.hotkey).is_ok_and(|parsed| parsed.id() == shortcut.id()))
+    else {
+        return;
+    };
+
+    match binding.action {
+        HotkeyAction::ShowWindow => show_main_window(app),
+        action => {
+            if action == HotkeyAction::TriggerGeneration {
+                show_main_window(app);
+            }
+            let _ = app.emit("global_hotkey", HotkeyTriggeredEvent { action });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_hotkey_strings() {
+        assert!(parse_hotkey("CommandOrControl+Shift+Space").is_ok());
+        assert!(parse_hotkey("Alt+F9").is_ok());
+        assert!(parse_hotkey("Ctrl+Nope").is_err());
+        assert!(parse_hotkey("").is_err());
+    }
+
+    #[test]
+    fn bindings_use_snake_case_actions() {
+        let binding: GlobalHotkeyBinding =
+            serde_json::from_str(r#"{"hotkey":"Alt+Space","action":"start_new_conversation"}"#)
+                .unwrap();
+        assert_eq!(binding.action, HotkeyAction::StartNewConversation);
+    }
+}
@@ -3,53 +3,432 @@
 //! Этот модуль предоставляет унифицированные макросы и функции для логирования
 //! с префиксами для различных компонентов системы.
 
-use log::{Level, LevelFilter};
-use std::sync::Once;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use once_cell::sync::{Lazy, OnceCell};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, Once, RwLock};
 
 static INIT: Once = Once::new();
 
+/// Сколько последних записей хранится для `get_recent_logs`
+pub const LOG_HISTORY_LIMIT: usize = 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LogEntry {
+    /// Unix-время в секундах
+    pub timestamp: u64,
+    pub level: String,
+    /// Имя компонента или `other`
+    pub component: String,
+    pub message: String,
+}
+
+static LOG_HISTORY: Lazy<Mutex<VecDeque<LogEntry>>> = Lazy::new(|| Mutex::new(VecDeque::new()));
+
+/// Уровни, заданные для отдельных компонентов во время работы
+static COMPONENT_LEVELS: Lazy<RwLock<HashMap<&'static str, LevelFilter>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Уровень фильтра env_logger по умолчанию (`RUST_LOG` или INFO)
+static DEFAULT_MAX_LEVEL: OnceCell<LevelFilter> = OnceCell::new();
+
+/// Сколько последних строк хранится для каждого компонента и для ошибок
+pub const RECENT_LINES_LIMIT: usize = 20;
+
+fn remember_entry(entry: LogEntry) {
+    if let Ok(mut history) = LOG_HISTORY.lock() {
+        if history.len() == LOG_HISTORY_LIMIT {
+            history.pop_front();
+        }
+        history.push_back(entry);
+    }
+}
+
+/// Последние `limit` записей (от старых к новым), при необходимости — одного компонента
+pub fn recent_entries(component: Option<&str>, limit: usize) -> Vec<LogEntry> {
+    let Ok(history) = LOG_HISTORY.lock() else {
+        return Vec::new();
+    };
+    let mut entries: Vec<LogEntry> = history
+        .iter()
+        .rev()
+        .filter(|entry| component.is_none_or(|c| entry.component == c))
+        .take(limit)
+        .cloned()
+        .collect();
+    entries.reverse();
+    entries
+}
+
+/// Строка записи в формате файла лога
+fn format_entry(entry: &LogEntry) -> String {
+    format!(
+        "{} {:<5} [{}] {}",
+        entry.timestamp,
+        entry.level.to_uppercase(),
+        entry.component,
+        entry.message
+    )
+}
+
+/// Последние `RECENT_LINES_LIMIT` строк каждого компонента из общей истории
+fn lines_by_component(history: &VecDeque<LogEntry>) -> BTreeMap<String, Vec<String>> {
+    let mut lines: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for entry in history.iter().rev() {
+        let component = lines.entry(entry.component.clone()).or_default();
+        if component.len() < RECENT_LINES_LIMIT {
+            component.push(format_entry(entry));
+        }
+    }
+    for component in lines.values_mut() {
+        component.reverse();
+    }
+    lines
+}
+
+/// Последние `RECENT_LINES_LIMIT` ошибок из общей истории
+fn error_lines(history: &VecDeque<LogEntry>) -> Vec<String> {
+    let mut errors: Vec<String> = history
+        .iter()
+        .rev()
+        .filter(|entry| entry.level == "error")
+        .take(RECENT_LINES_LIMIT)
+        .map(format_entry)
+        .collect();
+    errors.reverse();
+    errors
+}
+
+/// Последние строки лога по компонентам
+pub fn recent_lines() -> BTreeMap<String, Vec<String>> {
+    LOG_HISTORY
+        .lock()
+        .map(|history| lines_by_component(&history))
+        .unwrap_or_default()
+}
+
+/// Последние ошибки из лога
+pub fn recent_errors() -> Vec<String> {
+    LOG_HISTORY
+        .lock()
+        .map(|history| error_lines(&history))
+        .unwrap_or_default()
+}
+
+/// Каталог логов внутри профиля
+pub const LOGS_DIR: &str = "logs";
+
+/// Ограничения файлов лога; хранятся в `log_files.json`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct LogFileSettings {
+    pub max_log_file_mb: u32,
+    pub max_log_files: u32,
+}
+
+impl Default for LogFileSettings {
+    fn default() -> Self {
+        Self {
+            max_log_file_mb: 10,
+            max_log_files: 5,
+        }
+    }
+}
+
+/// Пишет строки лога в `logs/oxide-{date}.log`. Файл переоткрывается при
+/// смене даты, а при превышении размера переименовывается в
+/// `oxide-{date}.{n}.log`; хранятся только последние `max_log_files` старых файлов.
+pub struct LogFileWriter {
+    dir: PathBuf,
+    settings: LogFileSettings,
+    date: String,
+    file: Option<File>,
+    written: u64,
+}
+
+static LOG_FILE: Lazy<Mutex<Option<LogFileWriter>>> = Lazy::new(|| Mutex::new(None));
+
+impl LogFileWriter {
+    pub fn new(dir: PathBuf, settings: LogFileSettings) -> Result<Self, String> {
+        fs::create_dir_all(&dir).map_err(|e| format!("Failed to create logs directory: {}", e))?;
+        Ok(Self {
+            dir,
+            settings,
+            date: String::new(),
+            file: None,
+            written: 0,
+        })
+    }
+
+    pub fn current_path(&self) -> PathBuf {
+        self.dir.join(format!("oxide-{}.log", self.date))
+    }
+
+    fn open(&mut self, date: String) -> std::io::Result<()> {
+        self.date = date;
+        let path = self.current_path();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        self.written = file.metadata().map(|m| m.len()).unwrap_or(0);
+        self.file = Some(file);
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.file = None;
+        let mut n = 1;
+        while self
+            .dir
+            .join(format!("oxide-{}.{}.log", self.date, n))
+            .exists()
+        {
+            n += 1;
+        }
+        let rotated = self.dir.join(format!("oxide-{}.{}.log", self.date, n));
+        fs::rename(self.current_path(), rotated)?;
+        self.prune();
+        self.open(self.date.clone())
+    }
+
+    /// Удаляет старые файлы сверх `max_log_files`, не трогая текущий.
+    fn prune(&self) {
+        let current = self.current_path();
+        let mut old: Vec<(std::time::SystemTime, PathBuf)> = fs::read_dir(&self.dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| *path != current && is_log_file(path))
+            .map(|path| {
+                let modified = fs::metadata(&path)
+                    .and_then(|m| m.modified())
+                    .unwrap_or(std::time::UNIX_EPOCH);
+                (modified, path)
+            })
+            .collect();
+        old.sort_by(|a, b| b.0.cmp(&a.0));
+        for (_, path) in old.into_iter().skip(self.settings.max_log_files as usize) {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    pub fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+        if self.file.is_none() || today != self.date {
+            self.open(today)?;
+            self.prune();
+        }
+        let max_bytes = u64::from(self.settings.max_log_file_mb.max(1)) * 1024 * 1024;
+        if self.written >= max_bytes {
+            self.rotate()?;
+        }
+        if let Some(file) = self.file.as_mut() {
+            writeln!(file, "{}", line)?;
+            self.written += line.len() as u64 + 1;
+        }
+        Ok(())
+    }
+}
+
+fn is_log_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| n.starts_with("oxide-") && n.ends_with(".log"))
+}
+
+/// Включает запись лога на диск; вызывается при старте, когда известен профиль.
+pub fn init_file_logging(dir: PathBuf, settings: LogFileSettings) -> Result<(), String> {
+    let writer = LogFileWriter::new(dir, settings)?;
+    let mut guard = LOG_FILE
+        .lock()
+        .map_err(|e| format!("Failed to lock log file: {}", e))?;
+    *guard = Some(writer);
+    Ok(())
+}
+
+/// Путь к текущему файлу лога
+pub fn log_file_path() -> Option<PathBuf> {
+    let guard = LOG_FILE.lock().ok()?;
+    let writer = guard.as_ref()?;
+    if writer.date.is_empty() {
+        // В файл ещё ничего не писали — путь на сегодня
+        let today = chrono::Local::now().format("%Y-%m-%d");
+        return Some(writer.dir.join(format!("oxide-{}.log", today)));
+    }
+    Some(writer.current_path())
+}
+
+fn write_to_log_file(line: &str) {
+    if let Ok(mut guard) = LOG_FILE.lock()
+        && let Some(writer) = guard.as_mut()
+        && let Err(e) = writer.write_line(line)
+    {
+        // Через log нельзя: попадём обратно сюда
+        eprintln!("Failed to write log file: {}", e);
+    }
+}
+
+/// Логгер поверх env_logger: для компонентов с заданным уровнем фильтрует
+/// сам, для остальных целей решает обычный фильтр env_logger.
+struct ComponentLogger {
+    /// Пропускает все записи компонентов; отвечает за формат и вывод
+    inner: env_logger::Logger,
+    /// Фильтр по умолчанию, без поправок для компонентов
+    default: env_logger::Logger,
+}
+
+impl Log for ComponentLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        match component_level(metadata.target()) {
+            Some(level) => metadata.level() <= level,
+            None => self.default.enabled(metadata),
+        }
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            self.inner.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+fn component_level(target: &str) -> Option<LevelFilter> {
+    let component = Component::find(target)?;
+    COMPONENT_LEVELS
+        .read()
+        .ok()?
+        .get(component.as_str())
+        .copied()
+}
+
+fn base_builder() -> env_logger::Builder {
+    let mut builder = env_logger::Builder::from_default_env();
+    // Respect RUST_LOG when provided; otherwise default to INFO.
+    if std::env::var("RUST_LOG").is_err() {
+        builder.filter_level(LevelFilter::Info);
+    }
+    builder
+}
+
+/// Максимальный уровень для `log` с учётом поправок компонентов.
+fn refresh_max_level() {
+    let default = DEFAULT_MAX_LEVEL
+        .get()
+        .copied()
+        .unwrap_or(LevelFilter::Info);
+    let overrides = COMPONENT_LEVELS
+        .read()
+        .map(|levels| levels.values().copied().max().unwrap_or(LevelFilter::Off))
+        .unwrap_or(LevelFilter::Off);
+    log::set_max_level(default.max(overrides));
+}
+
 /// Инициализация системы логирования
 ///
 /// Настраивает env_logger с кастомным форматированием для Oxide Lab.
 /// Вызывается автоматически при первом использовании макросов логирования.
 pub fn init() {
     INIT.call_once(|| {
-        let mut builder = env_logger::Builder::from_default_env();
-
-        // Respect RUST_LOG when provided; otherwise default to INFO.
-        if std::env::var("RUST_LOG").is_err() {
-            builder.filter_level(LevelFilter::Info);
+        let default = base_builder().build();
+        let mut builder = base_builder();
+        for component in Component::ALL {
+            builder.filter_module(component.as_str(), LevelFilter::Trace);
         }
 
-        builder
-            .format(|buf, record| {
-                use std::io::Write;
-
-                let level = match record.level() {
-                    Level::Error => "ERROR",
-                    Level::Warn => "WARN ",
-                    Level::Info => "INFO ",
-                    Level::Debug => "DEBUG",
-                    Level::Trace => "TRACE",
-                };
-
-                let timestamp = std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap_or_default()
-                    .as_secs();
-                writeln!(
-                    buf,
-                    "{} {} [{}] {}",
-                    timestamp,
-                    level,
-                    record.target(),
-                    record.args()
-                )
-            })
-            .init();
+        builder.format(|buf, record| {
+            let level = match record.level() {
+                Level::Error => "ERROR",
+                Level::Warn => "WARN ",
+                Level::Info => "INFO ",
+                Level::Debug => "DEBUG",
+                Level::Trace => "TRACE",
+            };
+
+            let timestamp = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            let line = format!(
+                "{} {} [{}] {}",
+                timestamp,
+                level,
+                record.target(),
+                record.args()
+            );
+            remember_entry(LogEntry {
+                timestamp,
+                level: record.level().as_str().to_lowercase(),
+                component: Component::find(record.target())
+                    .map_or("other", Component::as_str)
+                    .to_string(),
+                message: record.args().to_string(),
+            });
+            write_to_log_file(&line);
+            writeln!(buf, "{}", line)
+        });
+
+        let _ = DEFAULT_MAX_LEVEL.set(default.filter());
+        let logger = ComponentLogger {
+            inner: builder.build(),
+            default,
+        };
+        if log::set_boxed_logger(Box::new(logger)).is_ok() {
+            refresh_max_level();
+        }
     });
 }
 
+/// Задаёт уровень логирования компонента во время работы.
+/// `default` снимает поправку и возвращает общий фильтр.
+pub fn set_component_level(component: &str, level: &str) -> Result<(), String> {
+    let component = Component::find(component).ok_or_else(|| {
+        let known: Vec<&str> = Component::ALL.iter().map(|c| c.as_str()).collect();
+        format!(
+            "Unknown log component: {component} (known: {})",
+            known.join(", ")
+        )
+    })?;
+    let level = match level.to_lowercase().as_str() {
+        "default" => None,
+        other => Some(
+            other
+                .parse::<LevelFilter>()
+                .map_err(|_| format!("Invalid log level: {level}"))?,
+        ),
+    };
+
+    let mut levels = COMPONENT_LEVELS
+        .write()
+        .map_err(|e| format!("Failed to update log levels: {}", e))?;
+    match level {
+        Some(level) => levels.insert(component.as_str(), level),
+        None => levels.remove(component.as_str()),
+    };
+    drop(levels);
+    refresh_max_level();
+    Ok(())
+}
+
+/// Текущие поправки уровней: компонент → уровень
+pub fn component_levels() -> BTreeMap<String, String> {
+    COMPONENT_LEVELS
+        .read()
+        .map(|levels| {
+            levels
+                .iter()
+                .map(|(component, level)| (component.to_string(), level.as_str().to_lowercase()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 /// Компоненты системы для логирования
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Component {
@@ -78,6 +457,25 @@ pub enum Component {
 }
 
 impl Component {
+    pub const ALL: [Component; 11] = [
+        Component::Load,
+        Component::Infer,
+        Component::Hub,
+        Component::Local,
+        Component::Template,
+        Component::Device,
+        Component::Validate,
+        Component::Weights,
+        Component::Generate,
+        Component::Tokenizer,
+        Component::Architecture,
+    ];
+
+    /// Компонент по имени цели лога (`load`, `infer`, ...)
+    pub fn find(name: &str) -> Option<Component> {
+        Component::ALL.into_iter().find(|c| c.as_str() == name)
+    }
+
     /// Получить строковое представление компонента
     pub fn as_str(self) -> &'static str {
         match self {
@@ -342,4 +740,89 @@ mod tests {
         log_tokenizer!("Test tokenizer message");
         log_arch!("Test architecture message");
     }
+
+    #[test]
+    fn keeps_only_recent_lines_per_component() {
+        let entry = |level: &str, component: &str, message: String| LogEntry {
+            timestamp: 0,
+            level: level.to_string(),
+            component: component.to_string(),
+            message,
+        };
+        let mut history: VecDeque<LogEntry> = (0..RECENT_LINES_LIMIT + 5)
+            .map(|i| entry("error", "weights", format!("weights line {i}")))
+            .collect();
+        history.push_back(entry("info", "other", "external line".to_string()));
+
+        let recent = lines_by_component(&history);
+        let weights = &recent["weights"];
+        assert_eq!(weights.len(), RECENT_LINES_LIMIT);
+        assert!(!weights.iter().any(|l| l.ends_with("weights line 0")));
+        assert_eq!(weights.last().unwrap(), "0 ERROR [weights] weights line 24");
+        assert_eq!(recent["other"], vec!["0 INFO  [other] external line"]);
+
+        let errors = error_lines(&history);
+        assert_eq!(errors.len(), RECENT_LINES_LIMIT);
+        assert!(errors.iter().all(|l| l.contains(" ERROR ")));
+    }
+
+    #[test]
+    fn recent_entries_filter_by_component() {
+        for i in 0..LOG_HISTORY_LIMIT + 3 {
+            remember_entry(LogEntry {
+                timestamp: i as u64,
+                level: "info".to_string(),
+                component: if i % 2 == 0 { "hub" } else { "validate" }.to_string(),
+                message: format!("entry {i}"),
+            });
+        }
+
+        let all = recent_entries(None, usize::MAX);
+        assert!(all.len() <= LOG_HISTORY_LIMIT);
+        assert!(!all.iter().any(|e| e.message == "entry 0"));
+
+        let hub = recent_entries(Some("hub"), 2);
+        assert_eq!(hub.len(), 2);
+        assert!(hub.iter().all(|e| e.component == "hub"));
+    }
+
+    #[test]
+    fn log_files_rotate_and_keep_only_recent() {
+        let dir = std::env::temp_dir().join(format!("oxide-logs-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let mut writer = LogFileWriter::new(
+            dir.clone(),
+            LogFileSettings {
+                max_log_file_mb: 1,
+                max_log_files: 2,
+            },
+        )
+        .unwrap();
+
+        for i in 0..4 {
+            writer.write_line(&format!("line {i}")).unwrap();
+            // Имитируем заполненный файл, чтобы следующая запись его ротировала
+            writer.written = u64::MAX;
+        }
+
+        let files: Vec<PathBuf> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .collect();
+        assert_eq!(files.len(), 3);
+        assert!(files.iter().all(|p| is_log_file(p)));
+        let current = fs::read_to_string(writer.current_path()).unwrap();
+        assert_eq!(current.trim(), "line 3");
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn component_levels_are_validated() {
+        assert!(set_component_level("weights", "debug").is_ok());
+        assert_eq!(component_levels()["weights"], "debug");
+        assert!(set_component_level("weights", "default").is_ok());
+        assert!(!component_levels().contains_key("weights"));
+        assert!(set_component_level("nope", "debug").is_err());
+        assert!(set_component_level("load", "verbose").is_err());
+    }
 }
@@ -1,14 +1,21 @@
 pub mod audio_capture;
+pub mod audit_log;
 pub mod config;
 pub mod device;
+pub mod diagnostics;
+pub mod hotkeys;
 pub mod log;
 pub mod performance;
 pub mod precision;
 pub mod prefix_cache;
 pub mod prompt;
 pub mod scheduler;
+pub mod session_store;
+pub mod settings_bundle;
+pub mod settings_watcher;
 pub mod state;
 pub mod stt_whisper;
+pub mod system_prompts;
 pub mod token_output_stream;
 pub mod tokenizer;
 pub mod types;
@@ -22,4 +29,4 @@ pub mod thread_priority;
 pub mod template_registry;
 pub mod templates;
 
-pub use rayon_pool::INFERENCE_POOL;
+pub use rayon_pool::{INFERENCE_POOL, inference_pool};
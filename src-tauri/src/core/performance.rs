@@ -1,6 +1,8 @@
 // Модуль для мониторинга производительности
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{Duration, Instant};
 use sysinfo::{Pid, System};
 use tokio::sync::RwLock;
@@ -74,12 +76,131 @@ pub struct SystemUsage {
     pub timestamp: String,
 }
 
+/// Заполненность KV-кэша модели после последнего запроса
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelMemoryStats {
+    pub model_id: String,
+    pub kv_cache_used_tokens: usize,
+    pub kv_cache_total_tokens: usize,
+    pub ctx_size: usize,
+}
+
+/// Сколько последних отчётов о загрузке моделей хранится в памяти
+pub const LOAD_REPORTS_LIMIT: usize = 10;
+
+/// Длительность стадий загрузки модели
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadTimingReport {
+    pub model_id: String,
+    pub total_ms: u64,
+    pub stages: Vec<(String, u64)>,
+}
+
+/// Размер скользящего окна запросов для p95 латентности
+pub const INFERENCE_WINDOW_SIZE: usize = 100;
+
+/// Завершённый запрос генерации
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InferenceRecord {
+    pub model_id: Option<String>,
+    pub metrics: InferenceMetrics,
+    /// Доля контекста, занятая запросом (prompt + ответ), в процентах
+    pub context_used_percent: f32,
+}
+
+/// Порог замедления генерации относительно базовой скорости, в процентах
+pub const REGRESSION_THRESHOLD_PERCENT: f64 = 25.0;
+
+/// Сколько запросов нужно, прежде чем базовой скорости можно доверять
+pub const REGRESSION_MIN_SAMPLES: u32 = 5;
+
+/// Короткие ответы не учитываются в базовой скорости: в них доминирует prefill
+pub const REGRESSION_MIN_GENERATED_TOKENS: usize = 32;
+
+/// Вес нового замера в экспоненциальном скользящем среднем
+const BASELINE_EMA_ALPHA: f64 = 0.2;
+
+/// Сколько последних замедлений хранится для диагностического отчёта
+pub const REGRESSIONS_LIMIT: usize = 20;
+
+/// Конфигурация, для которой копится отдельная базовая скорость
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BaselineKey {
+    pub model_id: String,
+    pub device: String,
+    pub ctx_size: usize,
+}
+
+/// EMA скорости генерации (tokens/sec)
+#[derive(Debug, Clone, Copy, Default)]
+struct SpeedBaseline {
+    ema_tps: f64,
+    samples: u32,
+}
+
+impl SpeedBaseline {
+    /// Учитывает замер и возвращает `(ожидаемая скорость, замедление в %)`,
+    /// если он медленнее базовой скорости больше чем на порог.
+    fn observe(&mut self, tps: f64) -> Option<(f64, f64)> {
+        let expected = self.ema_tps;
+        let regression = (self.samples >= REGRESSION_MIN_SAMPLES && expected > 0.0)
+            .then(|| (expected - tps) / expected * 100.0)
+            .filter(|degradation| *degradation > REGRESSION_THRESHOLD_PERCENT)
+            .map(|degradation| (expected, degradation));
+
+        self.ema_tps = if self.samples == 0 {
+            tps
+        } else {
+            BASELINE_EMA_ALPHA * tps + (1.0 - BASELINE_EMA_ALPHA) * self.ema_tps
+        };
+        self.samples = self.samples.saturating_add(1);
+        regression
+    }
+}
+
+/// Генерация заметно медленнее обычного для этой модели и конфигурации
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerformanceRegression {
+    pub model_id: String,
+    pub expected_tps: f64,
+    pub actual_tps: f64,
+    pub degradation_percent: f64,
+    pub detected_at: String,
+}
+
+/// Сводка производительности для панели мониторинга
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerformanceDashboard {
+    pub model_id: Option<String>,
+    pub session_uptime_secs: u64,
+    pub requests_completed: u64,
+    pub tokens_generated: u64,
+    pub avg_tps: f64,
+    pub p95_latency_ms: u64,
+    pub vram_used_mb: Option<u64>,
+    pub kv_cache_used_percent: f32,
+    pub load_reports: Vec<LoadTimingReport>,
+    /// Ответы `/v1/embeddings`, взятые из кэша
+    #[serde(default)]
+    pub embedding_cache_hits: u64,
+    #[serde(default)]
+    pub embedding_cache_misses: u64,
+}
+
 /// Монитор производительности
 pub struct PerformanceMonitor {
     metrics: Arc<RwLock<Vec<PerformanceMetric>>>,
     max_entries: usize,
     system: Arc<RwLock<System>>,
     startup_metrics: Arc<RwLock<Option<StartupMetrics>>>,
+    inference_window: Arc<RwLock<VecDeque<InferenceRecord>>>,
+    load_reports: Arc<RwLock<VecDeque<LoadTimingReport>>>,
+    memory_stats: Arc<RwLock<HashMap<String, ModelMemoryStats>>>,
+    speed_baselines: Arc<RwLock<HashMap<BaselineKey, SpeedBaseline>>>,
+    regressions: Arc<RwLock<VecDeque<PerformanceRegression>>>,
+    embedding_cache_hits: AtomicU64,
+    embedding_cache_misses: AtomicU64,
+    started_at: Instant,
 }
 
 impl PerformanceMonitor {
@@ -92,6 +213,153 @@ impl PerformanceMonitor {
             max_entries,
             system: Arc::new(RwLock::new(system)),
             startup_metrics: Arc::new(RwLock::new(None)),
+            inference_window: Arc::new(RwLock::new(VecDeque::with_capacity(INFERENCE_WINDOW_SIZE))),
+            load_reports: Arc::new(RwLock::new(VecDeque::with_capacity(LOAD_REPORTS_LIMIT))),
+            memory_stats: Arc::new(RwLock::new(HashMap::new())),
+            speed_baselines: Arc::new(RwLock::new(HashMap::new())),
+            regressions: Arc::new(RwLock::new(VecDeque::with_capacity(REGRESSIONS_LIMIT))),
+            embedding_cache_hits: AtomicU64::new(0),
+            embedding_cache_misses: AtomicU64::new(0),
+            started_at: Instant::now(),
+        }
+    }
+
+    /// Учесть обращение к кэшу эмбеддингов
+    pub fn record_embedding_cache(&self, hit: bool) {
+        let counter = if hit {
+            &self.embedding_cache_hits
+        } else {
+            &self.embedding_cache_misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Сохранить последний снимок памяти модели
+    pub async fn record_memory_stats(&self, stats: ModelMemoryStats) {
+        let mut memory_stats = self.memory_stats.write().await;
+        memory_stats.insert(stats.model_id.clone(), stats);
+    }
+
+    /// Последние снимки памяти по всем моделям
+    pub async fn get_memory_stats(&self) -> Vec<ModelMemoryStats> {
+        self.memory_stats.read().await.values().cloned().collect()
+    }
+
+    /// Сохранить отчёт о загрузке модели (последние `LOAD_REPORTS_LIMIT`)
+    pub async fn record_load_report(&self, report: LoadTimingReport) {
+        let mut reports = self.load_reports.write().await;
+        if reports.len() >= LOAD_REPORTS_LIMIT {
+            reports.pop_front();
+        }
+        reports.push_back(report);
+    }
+
+    /// Получить отчёты о загрузке моделей
+    pub async fn get_load_reports(&self) -> Vec<LoadTimingReport> {
+        self.load_reports.read().await.iter().cloned().collect()
+    }
+
+    /// Записать завершённый запрос в скользящее окно
+    pub async fn record_inference(&self, record: InferenceRecord) {
+        let mut window = self.inference_window.write().await;
+        if window.len() >= INFERENCE_WINDOW_SIZE {
+            window.pop_front();
+        }
+        window.push_back(record);
+    }
+
+    /// Сравнить скорость запроса с базовой для `key` и обновить её.
+    /// Возвращает замедление, если оно превысило `REGRESSION_THRESHOLD_PERCENT`.
+    pub async fn check_speed_regression(
+        &self,
+        key: BaselineKey,
+        tokens_per_second: f64,
+    ) -> Option<PerformanceRegression> {
+        if !tokens_per_second.is_finite() || tokens_per_second <= 0.0 {
+            return None;
+        }
+        let model_id = key.model_id.clone();
+        let (expected_tps, degradation_percent) = self
+            .speed_baselines
+            .write()
+            .await
+            .entry(key)
+            .or_default()
+            .observe(tokens_per_second)?;
+
+        let regression = PerformanceRegression {
+            model_id,
+            expected_tps,
+            actual_tps: tokens_per_second,
+            degradation_percent,
+            detected_at: chrono::Utc::now().to_rfc3339(),
+        };
+        let mut regressions = self.regressions.write().await;
+        if regressions.len() >= REGRESSIONS_LIMIT {
+            regressions.pop_front();
+        }
+        regressions.push_back(regression.clone());
+        Some(regression)
+    }
+
+    /// Сбросить базовую скорость модели (или всех моделей при `None`)
+    pub async fn clear_speed_baseline(&self, model_id: Option<&str>) {
+        let mut baselines = self.speed_baselines.write().await;
+        match model_id {
+            Some(model_id) => baselines.retain(|key, _| key.model_id != model_id),
+            None => baselines.clear(),
+        }
+    }
+
+    /// Последние обнаруженные замедления генерации
+    pub async fn get_regressions(&self) -> Vec<PerformanceRegression> {
+        self.regressions.read().await.iter().cloned().collect()
+    }
+
+    /// Сводка по последним запросам (опционально только для `model_id`)
+    pub async fn get_dashboard(&self, model_id: Option<&str>) -> PerformanceDashboard {
+        let load_reports = self.get_load_reports().await;
+        let window = self.inference_window.read().await;
+        let records: Vec<&InferenceRecord> = window
+            .iter()
+            .filter(|r| model_id.is_none() || r.model_id.as_deref() == model_id)
+            .collect();
+
+        let tokens_generated: u64 = records
+            .iter()
+            .map(|r| r.metrics.generated_tokens as u64)
+            .sum();
+        let avg_tps = if records.is_empty() {
+            0.0
+        } else {
+            records
+                .iter()
+                .map(|r| r.metrics.tokens_per_second)
+                .sum::<f64>()
+                / records.len() as f64
+        };
+        let latencies: Vec<u64> = records
+            .iter()
+            .map(|r| r.metrics.total_duration_ms)
+            .collect();
+
+        PerformanceDashboard {
+            model_id: model_id
+                .map(str::to_string)
+                .or_else(|| records.last().and_then(|r| r.model_id.clone())),
+            session_uptime_secs: self.started_at.elapsed().as_secs(),
+            requests_completed: records.len() as u64,
+            tokens_generated,
+            avg_tps,
+            p95_latency_ms: percentile(latencies, 95),
+            vram_used_mb: None,
+            kv_cache_used_percent: records
+                .last()
+                .map(|r| r.context_used_percent)
+                .unwrap_or(0.0),
+            load_reports,
+            embedding_cache_hits: self.embedding_cache_hits.load(Ordering::Relaxed),
+            embedding_cache_misses: self.embedding_cache_misses.load(Ordering::Relaxed),
         }
     }
 
@@ -207,6 +475,16 @@ impl PerformanceMonitor {
     }
 }
 
+/// Перцентиль (nearest-rank); 0 для пустого набора
+pub fn percentile(mut values: Vec<u64>, pct: u32) -> u64 {
+    if values.is_empty() {
+        return 0;
+    }
+    values.sort_unstable();
+    let rank = (pct.min(100) as usize * values.len()).div_ceil(100);
+    values[rank.saturating_sub(1)]
+}
+
 /// Таймер для измерения производительности
 pub struct PerformanceTimer {
     start: Instant,
@@ -499,3 +777,113 @@ macro_rules! measure_performance {
         result
     }};
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key() -> BaselineKey {
+        BaselineKey {
+            model_id: "qwen3-4b".to_string(),
+            device: "CUDA".to_string(),
+            ctx_size: 4096,
+        }
+    }
+
+    fn record(model_id: &str, total_duration_ms: u64) -> InferenceRecord {
+        InferenceRecord {
+            model_id: Some(model_id.to_string()),
+            metrics: InferenceMetrics {
+                prompt_tokens: 8,
+                generated_tokens: 10,
+                total_duration_ms,
+                prefill_duration_ms: 0,
+                generation_duration_ms: total_duration_ms,
+                tokens_per_second: 20.0,
+                prefill_tokens_per_second: 0.0,
+                memory_usage_mb: 0.0,
+                timestamp: String::new(),
+            },
+            context_used_percent: total_duration_ms as f32,
+        }
+    }
+
+    #[test]
+    fn percentile_handles_small_sets() {
+        assert_eq!(percentile(vec![], 95), 0);
+        assert_eq!(percentile(vec![7], 0), 7);
+        assert_eq!(percentile(vec![7], 95), 7);
+        assert_eq!(percentile(vec![7], 100), 7);
+        assert_eq!(percentile((1..=20).rev().collect(), 95), 19);
+        assert_eq!(percentile(vec![1, 2, 3], 250), 3);
+    }
+
+    #[tokio::test]
+    async fn inference_window_evicts_oldest_records() {
+        let monitor = PerformanceMonitor::new(10);
+        let total = INFERENCE_WINDOW_SIZE as u64 + 5;
+        for ms in 1..=total {
+            monitor.record_inference(record("qwen3-4b", ms)).await;
+        }
+
+        let dashboard = monitor.get_dashboard(None).await;
+        assert_eq!(dashboard.requests_completed, INFERENCE_WINDOW_SIZE as u64);
+        assert_eq!(
+            dashboard.tokens_generated,
+            10 * INFERENCE_WINDOW_SIZE as u64
+        );
+        // Первые пять записей вытеснены: p95 считается по 6..=105
+        assert_eq!(dashboard.p95_latency_ms, 100);
+        assert_eq!(dashboard.kv_cache_used_percent, total as f32);
+
+        assert_eq!(
+            monitor
+                .get_dashboard(Some("other"))
+                .await
+                .requests_completed,
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn load_reports_are_capped() {
+        let monitor = PerformanceMonitor::new(10);
+        for i in 0..LOAD_REPORTS_LIMIT + 3 {
+            monitor
+                .record_load_report(LoadTimingReport {
+                    model_id: format!("model-{i}"),
+                    total_ms: i as u64,
+                    stages: Vec::new(),
+                })
+                .await;
+        }
+        let reports = monitor.get_load_reports().await;
+        assert_eq!(reports.len(), LOAD_REPORTS_LIMIT);
+        assert_eq!(reports[0].model_id, "model-3");
+        assert_eq!(
+            reports.last().unwrap().model_id,
+            format!("model-{}", LOAD_REPORTS_LIMIT + 2)
+        );
+    }
+
+    #[tokio::test]
+    async fn detects_slowdown_after_warmup_and_resets() {
+        let monitor = PerformanceMonitor::new(10);
+        // Пока замеров мало, даже сильное замедление не считается регрессией
+        for tps in [40.0, 40.0, 40.0, 40.0, 10.0] {
+            assert!(monitor.check_speed_regression(key(), tps).await.is_none());
+        }
+        for _ in 0..10 {
+            monitor.check_speed_regression(key(), 40.0).await;
+        }
+        assert!(monitor.check_speed_regression(key(), 35.0).await.is_none());
+
+        let regression = monitor.check_speed_regression(key(), 20.0).await.unwrap();
+        assert!(regression.expected_tps > 35.0 && regression.expected_tps < 40.0);
+        assert!(regression.degradation_percent > REGRESSION_THRESHOLD_PERCENT);
+        assert_eq!(monitor.get_regressions().await.len(), 1);
+
+        monitor.clear_speed_baseline(Some("qwen3-4b")).await;
+        assert!(monitor.check_speed_regression(key(), 5.0).await.is_none());
+    }
+}
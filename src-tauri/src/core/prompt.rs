@@ -2,6 +2,7 @@
 //! This module provides functionality to build prompts from chat message histories
 //! using Jinja-style chat templates extracted from tokenizers.
 
+use crate::core::types::FimConfig;
 use crate::{log_template, log_template_error};
 use minijinja::{Environment, Value, context};
 use once_cell::sync::OnceCell;
@@ -15,6 +16,55 @@ pub struct ChatMessage {
     pub content: String,
 }
 
+/// Значения для подстановки в шаблон системного промпта
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptContext {
+    pub current_date: String,
+    pub current_time: String,
+    pub model_name: String,
+    pub user_name: Option<String>,
+}
+
+impl PromptContext {
+    /// Контекст с текущими локальными датой и временем.
+    pub fn now(model_name: impl Into<String>, user_name: Option<String>) -> Self {
+        let now = chrono::Local::now();
+        Self {
+            current_date: now.format("%Y-%m-%d").to_string(),
+            current_time: now.format("%H:%M").to_string(),
+            model_name: model_name.into(),
+            user_name,
+        }
+    }
+}
+
+/// Подставляет `{{date}}`, `{{time}}`, `{{model}}` и `{{user}}` в системный промпт.
+/// Остальной текст, включая неизвестные переменные, не меняется.
+pub fn render_system_prompt(template: &str, context: &PromptContext) -> String {
+    template
+        .replace("{{date}}", &context.current_date)
+        .replace("{{time}}", &context.current_time)
+        .replace("{{model}}", &context.model_name)
+        .replace("{{user}}", context.user_name.as_deref().unwrap_or("User"))
+}
+
+/// Модели семейства Qwen2.5-Coder понимают FIM-токены; определяем по имени
+/// файла или репозитория, а не по всему пути (каталог может называться как угодно).
+pub fn is_coder_model(model_id: &str) -> bool {
+    model_id
+        .rsplit(['/', '\\'])
+        .next()
+        .is_some_and(|name| name.to_lowercase().contains("coder"))
+}
+
+/// FIM-промпт в формате Qwen2.5-Coder; модель дописывает середину.
+pub fn render_fim_prompt(fim: &FimConfig) -> String {
+    format!(
+        "<|fim_prefix|>{}<|fim_suffix|>{}<|fim_middle|>",
+        fim.prefix, fim.suffix
+    )
+}
+
 /// Prompt builder for creating prompts from chat templates
 pub struct PromptBuilder {
     chat_template: Option<String>,
@@ -222,8 +272,12 @@ impl Default for PromptBuilder {
 
 #[cfg(test)]
 mod tests {
-    use super::{normalize_and_validate, normalize_chat_template};
+    use super::{
+        PromptContext, is_coder_model, normalize_and_validate, normalize_chat_template,
+        render_fim_prompt, render_system_prompt,
+    };
     use crate::core::tokenizer::find_chat_template_in_metadata;
+    use crate::core::types::FimConfig;
     use candle::quantized::gguf_file;
     use std::fs::File;
     use std::path::Path;
@@ -252,6 +306,39 @@ mod tests {
         assert!(normalized.contains(r#"((content|split("</think>"))[0]|split("<think>"))[-1]"#));
     }
 
+    #[test]
+    fn renders_fim_prompt_for_coder_models() {
+        assert!(is_coder_model("Qwen2.5-Coder-7B-Instruct-Q4_K_M.gguf"));
+        assert!(is_coder_model("Qwen/Qwen2.5-Coder-1.5B"));
+        assert!(!is_coder_model("Qwen3-8B-Q4_K_M.gguf"));
+        assert!(!is_coder_model("/home/coder/models/Qwen3-8B-Q4_K_M.gguf"));
+        let fim = FimConfig {
+            prefix: "fn add(a: i32, b: i32) -> i32 {\n    ".into(),
+            suffix: "\n}".into(),
+        };
+        assert_eq!(
+            render_fim_prompt(&fim),
+            "<|fim_prefix|>fn add(a: i32, b: i32) -> i32 {\n    <|fim_suffix|>\n}<|fim_middle|>"
+        );
+    }
+
+    #[test]
+    fn substitutes_system_prompt_variables() {
+        let ctx = PromptContext {
+            current_date: "2025-03-01".into(),
+            current_time: "09:30".into(),
+            model_name: "Qwen3-0.6B".into(),
+            user_name: None,
+        };
+        assert_eq!(
+            render_system_prompt(
+                "Today is {{date}} {{time}}. You are {{model}}, talking to {{user}}. {{other}}",
+                &ctx
+            ),
+            "Today is 2025-03-01 09:30. You are Qwen3-0.6B, talking to User. {{other}}"
+        );
+    }
+
     #[test]
     fn normalize_and_validate_accepts_tooling_template() {
         let tpl = r###"{%- if tools %}
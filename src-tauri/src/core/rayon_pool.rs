@@ -1,11 +1,16 @@
-use crate::core::thread_priority::set_current_thread_below_normal;
-use std::sync::LazyLock;
+use crate::core::thread_priority::{
+    ThreadPriority, inference_thread_priority, set_current_thread_below_normal,
+    set_current_thread_priority, store_inference_thread_priority,
+};
+use std::sync::{Arc, LazyLock, RwLock};
 
 /// Sets platform-specific thread affinity/priority for inference threads.
 ///
 /// - macOS: Uses QOS_CLASS_USER_INTERACTIVE for P-core scheduling
-/// - Windows: Uses default priority (inference pool uses normal priority for max performance)
 /// - Other platforms: No-op
+///
+/// Priority itself comes from [`inference_thread_priority`] in the pool's start handler;
+/// generation runs inside the pool, so the saved setting applies to it.
 #[cfg(target_os = "macos")]
 unsafe fn set_inference_thread_affinity() {
     // USER_INTERACTIVE has the highest scheduling priority that user code
@@ -20,20 +25,65 @@ unsafe fn set_inference_thread_affinity() {
     // On non-macOS platforms we leave affinity untouched for inference pool
 }
 
-/// High-priority rayon pool for inference tasks.
-/// Uses platform-specific optimizations:
-/// - macOS: P-core affinity
-/// - Other platforms: Default thread scheduling
-pub static INFERENCE_POOL: LazyLock<rayon::ThreadPool> = LazyLock::new(|| {
+fn build_inference_pool(num_threads: usize) -> Result<rayon::ThreadPool, String> {
     rayon::ThreadPoolBuilder::new()
+        // 0 = Rayon default (RAYON_NUM_THREADS or the number of logical CPUs)
+        .num_threads(num_threads)
         .thread_name(|idx| format!("oxide-inference-{}", idx))
-        .start_handler(|_| unsafe {
-            set_inference_thread_affinity();
+        .start_handler(|_| {
+            unsafe {
+                set_inference_thread_affinity();
+            }
+            let priority = inference_thread_priority();
+            if priority != ThreadPriority::Normal {
+                let _ = set_current_thread_priority(priority);
+            }
         })
         .build()
-        .expect("Failed to build inference Rayon thread pool")
+        .map_err(|e| format!("Failed to build inference Rayon thread pool: {e}"))
+}
+
+/// High-priority rayon pool for inference tasks.
+/// Uses platform-specific optimizations:
+/// - macOS: P-core affinity
+/// - Other platforms: Default thread scheduling
+///
+/// The pool can be rebuilt at runtime with a different size (see
+/// [`rebuild_inference_pool`]); take a handle via [`inference_pool`].
+pub static INFERENCE_POOL: LazyLock<RwLock<Arc<rayon::ThreadPool>>> = LazyLock::new(|| {
+    RwLock::new(Arc::new(
+        build_inference_pool(0).expect("Failed to build inference Rayon thread pool"),
+    ))
 });
 
+/// Current inference pool. Work already running on an old pool finishes there.
+pub fn inference_pool() -> Arc<rayon::ThreadPool> {
+    INFERENCE_POOL
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+}
+
+/// Replaces the inference pool with a new one of `num_threads` threads.
+/// Returns the previous thread count.
+pub fn rebuild_inference_pool(num_threads: usize) -> Result<usize, String> {
+    let pool = Arc::new(build_inference_pool(num_threads.max(1))?);
+    let mut slot = INFERENCE_POOL
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let old_count = slot.current_num_threads();
+    *slot = pool;
+    Ok(old_count)
+}
+
+/// Changes the priority of inference threads. The pool is rebuilt with the same
+/// size so that every worker picks up the new priority.
+pub fn set_inference_thread_priority(priority: ThreadPriority) -> Result<(), String> {
+    store_inference_thread_priority(priority);
+    let threads = inference_pool().current_num_threads();
+    rebuild_inference_pool(threads).map(|_| ())
+}
+
 /// Initializes the global Rayon thread pool with a low-priority start handler.
 /// This pool is used for background tasks that shouldn't compete with inference.
 ///
@@ -76,7 +126,16 @@ mod tests {
     #[test]
     fn inference_pool_can_be_accessed() {
         // Force lazy initialization
-        let pool = &*INFERENCE_POOL;
+        let pool = inference_pool();
         assert!(pool.current_num_threads() > 0);
     }
+
+    #[test]
+    fn inference_pool_builder_uses_requested_size() {
+        // Local pool: rebuilding the global one would race with other tests
+        let pool = build_inference_pool(2).unwrap();
+        assert_eq!(pool.current_num_threads(), 2);
+        let name = pool.install(|| std::thread::current().name().map(str::to_string));
+        assert!(name.is_some_and(|n| n.starts_with("oxide-inference-")));
+    }
 }
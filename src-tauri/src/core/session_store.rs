@@ -0,0 +1,230 @@
+//! Сохранение сведений о загруженной модели для восстановления после сбоя.
+//!
+//! При успешной загрузке модели в `profile_dir/sessions/{model_id}.json`
+//! записывается исходный `LoadRequest`, при выгрузке файл удаляется.
+//! Если приложение упало, файл остаётся и по нему можно повторить загрузку.
+
+use crate::core::state::ModelState;
+use crate::core::types::LoadRequest;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+/// Сведения об активной модели, сохраняемые на диск.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersistedSession {
+    pub model_id: String,
+    pub request: LoadRequest,
+    /// PID процесса, загрузившего модель
+    pub pid: u32,
+    pub loaded_at: String,
+}
+
+impl PersistedSession {
+    pub fn new(model_id: String, request: LoadRequest) -> Self {
+        Self {
+            model_id,
+            request,
+            pid: std::process::id(),
+            loaded_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    /// Локальные файлы модели всё ещё на месте. Модели с HF Hub считаются
+    /// доступными: веса лежат в кэше hub и при необходимости докачаются.
+    pub fn is_recoverable(&self) -> bool {
+        match &self.request {
+            LoadRequest::Gguf { model_path, .. }
+            | LoadRequest::LocalSafetensors { model_path, .. } => Path::new(model_path).exists(),
+            LoadRequest::HubGguf { .. } | LoadRequest::HubSafetensors { .. } => true,
+        }
+    }
+}
+
+/// Payload события `session_recovered`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionRecoveredEvent {
+    pub model_id: String,
+    pub request: LoadRequest,
+}
+
+/// Сессии, найденные при запуске после аварийного завершения; отдаются
+/// frontend один раз через `take_recovered_sessions`.
+#[derive(Default)]
+pub struct RecoveredSessions(Mutex<Vec<PersistedSession>>);
+
+impl RecoveredSessions {
+    pub fn new(sessions: Vec<PersistedSession>) -> Self {
+        Self(Mutex::new(sessions))
+    }
+
+    pub fn take(&self) -> Vec<PersistedSession> {
+        self.0
+            .lock()
+            .map(|mut sessions| std::mem::take(&mut *sessions))
+            .unwrap_or_default()
+    }
+}
+
+/// Хранилище файлов сессий в `profile_dir/sessions`.
+pub struct SessionPersistenceStore {
+    dir: PathBuf,
+}
+
+impl SessionPersistenceStore {
+    pub fn new(app: &AppHandle) -> Result<Self, String> {
+        let dir = ModelState::ensure_profile_dir(app)?.join("sessions");
+        Ok(Self::from_dir(dir))
+    }
+
+    pub fn from_dir(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    fn session_path(&self, model_id: &str) -> PathBuf {
+        let file_stem: String = model_id
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '.' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.dir.join(format!("{file_stem}.json"))
+    }
+
+    pub fn save(&self, session: &PersistedSession) -> Result<(), String> {
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("Failed to create sessions directory: {}", e))?;
+        let data = serde_json::to_vec_pretty(session)
+            .map_err(|e| format!("Failed to serialize session: {}", e))?;
+        fs::write(self.session_path(&session.model_id), data)
+            .map_err(|e| format!("Failed to write session file: {}", e))
+    }
+
+    /// Загрузить все сохранённые сессии. Повреждённые файлы пропускаются.
+    pub fn load_all(&self) -> Vec<PersistedSession> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().and_then(|e| e.to_str()) == Some("json"))
+            .filter_map(|path| {
+                let data = fs::read(&path).ok()?;
+                match serde_json::from_slice::<PersistedSession>(&data) {
+                    Ok(session) => Some(session),
+                    Err(e) => {
+                        log::warn!("Skipping corrupt session file {}: {}", path.display(), e);
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+
+    pub fn remove(&self, model_id: &str) -> Result<(), String> {
+        match fs::remove_file(self.session_path(model_id)) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(format!("Failed to remove session file: {}", e)),
+        }
+    }
+
+    /// Разобрать сессии, оставшиеся после аварийного завершения.
+    /// Файлы удаляются в любом случае; возвращаются только сессии,
+    /// модель которых ещё можно загрузить.
+    pub fn recover(&self) -> Vec<PersistedSession> {
+        let mut recovered = Vec::new();
+        for session in self.load_all() {
+            if let Err(e) = self.remove(&session.model_id) {
+                log::warn!("{}", e);
+            }
+            if session.is_recoverable() {
+                recovered.push(session);
+            } else {
+                log::info!(
+                    "Dropping stale session {}: model files are missing",
+                    session.model_id
+                );
+            }
+        }
+        recovered
+    }
+
+    /// Удалить все сохранённые сессии (в памяти одновременно живёт одна модель).
+    pub fn clear(&self) -> Result<(), String> {
+        for session in self.load_all() {
+            self.remove(&session.model_id)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn save_load_remove_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("oxide-session-store-{}", std::process::id()));
+        let store = SessionPersistenceStore::from_dir(dir.clone());
+
+        let session = PersistedSession::new(
+            "C:\\models\\qwen.gguf".to_string(),
+            LoadRequest::Gguf {
+                model_path: "C:\\models\\qwen.gguf".to_string(),
+                tokenizer_path: None,
+                context_length: 4096,
+                device: None,
+            },
+        );
+        store.save(&session).unwrap();
+
+        let loaded = store.load_all();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].model_id, session.model_id);
+        assert_eq!(loaded[0].pid, std::process::id());
+
+        store.remove(&session.model_id).unwrap();
+        assert!(store.load_all().is_empty());
+        // Повторное удаление не является ошибкой
+        store.remove(&session.model_id).unwrap();
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn recover_drops_stale_sessions_and_files() {
+        let dir =
+            std::env::temp_dir().join(format!("oxide-session-recover-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let model = dir.join("present.gguf");
+        fs::write(&model, b"gguf").unwrap();
+        let store = SessionPersistenceStore::from_dir(dir.join("sessions"));
+
+        for path in [model.clone(), dir.join("missing.gguf")] {
+            let path = path.to_string_lossy().into_owned();
+            let request = LoadRequest::Gguf {
+                model_path: path.clone(),
+                tokenizer_path: None,
+                context_length: 4096,
+                device: None,
+            };
+            store.save(&PersistedSession::new(path, request)).unwrap();
+        }
+
+        let recovered = store.recover();
+        assert_eq!(recovered.len(), 1);
+        assert_eq!(recovered[0].model_id, model.to_string_lossy());
+        assert!(store.load_all().is_empty());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,218 @@
+//! Отслеживание внешних изменений файлов настроек.
+//!
+//! Следит за JSON-файлами настроек в `profile_dir` и после паузы в
+//! `SETTINGS_DEBOUNCE` эмитит `settings_changed_externally`. Запись самим
+//! приложением помечается через `note_internal_write` и не считается внешней.
+
+use crate::core::settings_bundle::BUNDLE_FILES;
+use crate::core::state::ModelState;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+/// Задержка, в течение которой события одной записи объединяются.
+pub const SETTINGS_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Сколько времени после собственной записи события по файлу игнорируются.
+const INTERNAL_WRITE_GRACE: Duration = Duration::from_secs(2);
+
+static INTERNAL_WRITES: Lazy<Mutex<HashMap<PathBuf, Instant>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingsChangedEvent {
+    pub files: Vec<String>,
+}
+
+/// Канонический путь каталога + имя файла (сам файл может ещё не существовать).
+fn normalize(path: &Path) -> PathBuf {
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => std::fs::canonicalize(parent)
+            .map(|parent| parent.join(name))
+            .unwrap_or_else(|_| path.to_path_buf()),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Пометить файл как записанный приложением.
+pub fn note_internal_write(path: &Path) {
+    if let Ok(mut writes) = INTERNAL_WRITES.lock() {
+        writes.insert(normalize(path), Instant::now());
+    }
+}
+
+fn is_internal_write(path: &Path) -> bool {
+    let path = normalize(path);
+    INTERNAL_WRITES
+        .lock()
+        .ok()
+        .and_then(|writes| writes.get(&path).copied())
+        .is_some_and(|at| at.elapsed() < INTERNAL_WRITE_GRACE)
+}
+
+/// Путь файла относительно `profile_dir` (через `/`), если это файл настроек.
+fn watched_file_name(profile_dir: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(profile_dir).ok()?;
+    let name = relative
+        .components()
+        .map(|c| c.as_os_str().to_str())
+        .collect::<Option<Vec<_>>>()?
+        .join("/");
+    BUNDLE_FILES
+        .iter()
+        .any(|watched| *watched == name)
+        .then_some(name)
+}
+
+/// Каталоги с файлами настроек. Наблюдение за ними идёт без рекурсии, чтобы
+/// записи в логи и кэши внутри профиля не будили поток debounce.
+fn watched_dirs(profile_dir: &Path) -> BTreeSet<PathBuf> {
+    BUNDLE_FILES
+        .iter()
+        .filter_map(|name| profile_dir.join(name).parent().map(Path::to_path_buf))
+        .collect()
+}
+
+pub struct SettingsWatcher {
+    watcher: Mutex<Option<RecommendedWatcher>>,
+}
+
+impl SettingsWatcher {
+    pub fn start(app: &AppHandle) -> Result<Self, String> {
+        let profile_dir = ModelState::ensure_profile_dir(app)?;
+        // notify сообщает канонические пути (например, /private/var на macOS)
+        let profile_dir = std::fs::canonicalize(&profile_dir).unwrap_or(profile_dir);
+        let (tx, rx) = mpsc::channel::<notify::Event>();
+
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            if let Ok(event) = res {
+                let _ = tx.send(event);
+            }
+        })
+        .map_err(|e| format!("Failed to create settings watcher: {}", e))?;
+        for dir in watched_dirs(&profile_dir) {
+            // Вложенный каталог (downloads/) может ещё не существовать
+            std::fs::create_dir_all(&dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+            watcher
+                .watch(&dir, RecursiveMode::NonRecursive)
+                .map_err(|e| format!("Failed to watch {}: {}", dir.display(), e))?;
+        }
+
+        let app = app.clone();
+        std::thread::Builder::new()
+            .name("settings-watcher".into())
+            .spawn(move || debounce_loop(app, profile_dir, rx))
+            .map_err(|e| format!("Failed to spawn settings watcher thread: {}", e))?;
+
+        Ok(Self {
+            watcher: Mutex::new(Some(watcher)),
+        })
+    }
+
+    /// Останавливает наблюдение; поток debounce завершится после закрытия канала.
+    pub fn stop(&self) {
+        if let Ok(mut watcher) = self.watcher.lock() {
+            watcher.take();
+        }
+    }
+}
+
+/// Добавляет изменённые файлы настроек; `true`, если событие касалось хотя бы одного.
+fn collect_changed(
+    profile_dir: &Path,
+    event: &notify::Event,
+    changed: &mut BTreeSet<String>,
+) -> bool {
+    if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+        return false;
+    }
+    let mut relevant = false;
+    for path in &event.paths {
+        if is_internal_write(path) {
+            continue;
+        }
+        if let Some(name) = watched_file_name(profile_dir, path) {
+            changed.insert(name);
+            relevant = true;
+        }
+    }
+    relevant
+}
+
+fn debounce_loop(app: AppHandle, profile_dir: PathBuf, rx: mpsc::Receiver<notify::Event>) {
+    // recv() вернёт ошибку, когда watcher будет остановлен и отправитель удалён
+    while let Ok(first) = rx.recv() {
+        let mut changed = BTreeSet::new();
+        if !collect_changed(&profile_dir, &first, &mut changed) {
+            continue;
+        }
+
+        // Паузу продлевают только события по файлам настроек, иначе поток
+        // соседних файлов откладывал бы уведомление бесконечно
+        let mut deadline = Instant::now() + SETTINGS_DEBOUNCE;
+        loop {
+            match rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok(event) => {
+                    if collect_changed(&profile_dir, &event, &mut changed) {
+                        deadline = Instant::now() + SETTINGS_DEBOUNCE;
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+
+        if !changed.is_empty() {
+            let files: Vec<String> = changed.into_iter().collect();
+            log::info!("Settings changed externally: {:?}", files);
+            let _ = app.emit(
+                "settings_changed_externally",
+                SettingsChangedEvent { files },
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_settings_files_are_watched() {
+        let profile = Path::new("/p");
+        assert_eq!(
+            watched_file_name(profile, Path::new("/p/proxy.json")).as_deref(),
+            Some("proxy.json")
+        );
+        assert_eq!(
+            watched_file_name(profile, Path::new("/p/downloads/history.json")).as_deref(),
+            Some("downloads/history.json")
+        );
+        assert!(watched_file_name(profile, Path::new("/p/scan_cache.json")).is_none());
+        assert!(watched_file_name(profile, Path::new("/p/audit.log")).is_none());
+        assert!(watched_file_name(profile, Path::new("/p/other/proxy.json")).is_none());
+        assert!(watched_file_name(profile, Path::new("/elsewhere/proxy.json")).is_none());
+    }
+
+    #[test]
+    fn watches_only_settings_directories() {
+        let profile = Path::new("/p");
+        let dirs: Vec<PathBuf> = watched_dirs(profile).into_iter().collect();
+        assert_eq!(dirs, [PathBuf::from("/p"), PathBuf::from("/p/downloads")]);
+    }
+
+    #[test]
+    fn internal_writes_are_ignored_briefly() {
+        let path = Path::new("/tmp/oxide-watcher-test/precision.json");
+        assert!(!is_internal_write(path));
+        note_internal_write(path);
+        assert!(is_internal_write(path));
+    }
+}
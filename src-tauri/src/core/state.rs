@@ -1,9 +1,18 @@
+use crate::core::log::LogFileSettings;
 use crate::core::performance::PerformanceMonitor;
 use crate::core::precision::{Precision, PrecisionPolicy};
 use crate::core::prefix_cache::{PrefixCache, PrefixCacheConfig};
 use crate::core::scheduler::{ModelScheduler, SchedulerConfig};
+use crate::core::settings_watcher;
+use crate::core::thread_priority::ThreadPriority;
+use crate::core::types::{
+    DownloadSettings, LoadTimingSettings, LocalRagSettings, ModelsStorageSettings,
+    OpenAiServerSettings, ProxySettings,
+};
+use crate::models::registry::UserDefinedBackendConfig;
 use candle::Device;
 use serde_json;
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::fs::create_dir_all;
 use std::path::PathBuf;
@@ -65,6 +74,7 @@ impl ModelState {
     pub fn save_precision(&self, app: &AppHandle) -> Result<(), String> {
         let profile_dir = Self::ensure_profile_dir(app)?;
         let path = profile_dir.join("precision.json");
+        settings_watcher::note_internal_write(&path);
         let file =
             File::create(&path).map_err(|e| format!("Failed to create precision file: {}", e))?;
         serde_json::to_writer(file, &self.precision_policy)
@@ -87,7 +97,7 @@ impl ModelState {
         }
     }
 
-    fn profile_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    pub(crate) fn profile_dir(app: &AppHandle) -> Result<PathBuf, String> {
         let dir = app
             .path()
             .app_local_data_dir()
@@ -95,7 +105,7 @@ impl ModelState {
         Ok(dir.join("oxide-lab"))
     }
 
-    fn ensure_profile_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    pub(crate) fn ensure_profile_dir(app: &AppHandle) -> Result<PathBuf, String> {
         let profile_dir = Self::profile_dir(app)?;
         create_dir_all(&profile_dir)
             .map_err(|e| format!("Failed to create profile directory: {}", e))?;
@@ -105,6 +115,7 @@ impl ModelState {
     pub fn save_thread_limit(app: &AppHandle, limit: Option<usize>) -> Result<(), String> {
         let profile_dir = Self::ensure_profile_dir(app)?;
         let path = profile_dir.join("thread_limit.json");
+        settings_watcher::note_internal_write(&path);
         let file = File::create(&path)
             .map_err(|e| format!("Failed to create thread limit file: {}", e))?;
         serde_json::to_writer(file, &limit)
@@ -125,6 +136,287 @@ impl ModelState {
             Ok(None)
         }
     }
+
+    pub fn save_locale(app: &AppHandle, locale: &str) -> Result<(), String> {
+        let profile_dir = Self::ensure_profile_dir(app)?;
+        let path = profile_dir.join("locale.json");
+        settings_watcher::note_internal_write(&path);
+        let file =
+            File::create(&path).map_err(|e| format!("Failed to create locale file: {}", e))?;
+        serde_json::to_writer(file, locale)
+            .map_err(|e| format!("Failed to serialize locale: {}", e))?;
+        Ok(())
+    }
+
+    /// `None` — локаль ещё не сохранялась (первый запуск).
+    pub fn load_locale(app: &AppHandle) -> Result<Option<String>, String> {
+        let profile_dir = Self::profile_dir(app)?;
+        let path = profile_dir.join("locale.json");
+        if path.exists() {
+            let file =
+                File::open(&path).map_err(|e| format!("Failed to open locale file: {}", e))?;
+            let locale: String = serde_json::from_reader(file)
+                .map_err(|e| format!("Failed to deserialize locale: {}", e))?;
+            Ok(Some(locale))
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn save_log_levels(
+        app: &AppHandle,
+        levels: &BTreeMap<String, String>,
+    ) -> Result<(), String> {
+        let profile_dir = Self::ensure_profile_dir(app)?;
+        let path = profile_dir.join("log_levels.json");
+        settings_watcher::note_internal_write(&path);
+        let file =
+            File::create(&path).map_err(|e| format!("Failed to create log levels file: {}", e))?;
+        serde_json::to_writer_pretty(file, levels)
+            .map_err(|e| format!("Failed to serialize log levels: {}", e))?;
+        Ok(())
+    }
+
+    pub fn load_log_levels(app: &AppHandle) -> Result<BTreeMap<String, String>, String> {
+        let profile_dir = Self::profile_dir(app)?;
+        let path = profile_dir.join("log_levels.json");
+        if path.exists() {
+            let file =
+                File::open(&path).map_err(|e| format!("Failed to open log levels file: {}", e))?;
+            serde_json::from_reader(file)
+                .map_err(|e| format!("Failed to deserialize log levels: {}", e))
+        } else {
+            Ok(BTreeMap::new())
+        }
+    }
+
+    pub fn save_custom_backends(
+        app: &AppHandle,
+        backends: &[UserDefinedBackendConfig],
+    ) -> Result<(), String> {
+        let profile_dir = Self::ensure_profile_dir(app)?;
+        let path = profile_dir.join("custom_backends.json");
+        settings_watcher::note_internal_write(&path);
+        let file = File::create(&path)
+            .map_err(|e| format!("Failed to create custom backends file: {}", e))?;
+        serde_json::to_writer_pretty(file, backends)
+            .map_err(|e| format!("Failed to serialize custom backends: {}", e))?;
+        Ok(())
+    }
+
+    pub fn load_custom_backends(app: &AppHandle) -> Result<Vec<UserDefinedBackendConfig>, String> {
+        let profile_dir = Self::profile_dir(app)?;
+        let path = profile_dir.join("custom_backends.json");
+        if path.exists() {
+            let file = File::open(&path)
+                .map_err(|e| format!("Failed to open custom backends file: {}", e))?;
+            serde_json::from_reader(file)
+                .map_err(|e| format!("Failed to deserialize custom backends: {}", e))
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    pub fn load_log_file_settings(app: &AppHandle) -> Result<LogFileSettings, String> {
+        let profile_dir = Self::profile_dir(app)?;
+        let path = profile_dir.join("log_files.json");
+        if path.exists() {
+            let file = File::open(&path)
+                .map_err(|e| format!("Failed to open log file settings: {}", e))?;
+            serde_json::from_reader(file)
+                .map_err(|e| format!("Failed to deserialize log file settings: {}", e))
+        } else {
+            Ok(LogFileSettings::default())
+        }
+    }
+
+    pub fn save_inference_priority(
+        app: &AppHandle,
+        priority: ThreadPriority,
+    ) -> Result<(), String> {
+        let profile_dir = Self::ensure_profile_dir(app)?;
+        let path = profile_dir.join("inference_thread_priority.json");
+        settings_watcher::note_internal_write(&path);
+        let file = File::create(&path)
+            .map_err(|e| format!("Failed to create thread priority file: {}", e))?;
+        serde_json::to_writer(file, priority.as_str())
+            .map_err(|e| format!("Failed to serialize thread priority: {}", e))?;
+        Ok(())
+    }
+
+    pub fn load_inference_priority(app: &AppHandle) -> Result<ThreadPriority, String> {
+        let profile_dir = Self::profile_dir(app)?;
+        let path = profile_dir.join("inference_thread_priority.json");
+        if path.exists() {
+            let file = File::open(&path)
+                .map_err(|e| format!("Failed to open thread priority file: {}", e))?;
+            let value: String = serde_json::from_reader(file)
+                .map_err(|e| format!("Failed to deserialize thread priority: {}", e))?;
+            ThreadPriority::parse(&value)
+        } else {
+            Ok(ThreadPriority::default())
+        }
+    }
+
+    pub fn save_proxy_settings(app: &AppHandle, settings: &ProxySettings) -> Result<(), String> {
+        let profile_dir = Self::ensure_profile_dir(app)?;
+        let path = profile_dir.join("proxy.json");
+        settings_watcher::note_internal_write(&path);
+        let file = File::create(&path)
+            .map_err(|e| format!("Failed to create proxy settings file: {}", e))?;
+        serde_json::to_writer(file, settings)
+            .map_err(|e| format!("Failed to serialize proxy settings: {}", e))?;
+        Ok(())
+    }
+
+    pub fn load_proxy_settings(app: &AppHandle) -> Result<ProxySettings, String> {
+        let profile_dir = Self::profile_dir(app)?;
+        let path = profile_dir.join("proxy.json");
+        if path.exists() {
+            let file = File::open(&path)
+                .map_err(|e| format!("Failed to open proxy settings file: {}", e))?;
+            serde_json::from_reader(file)
+                .map_err(|e| format!("Failed to deserialize proxy settings: {}", e))
+        } else {
+            Ok(ProxySettings::default())
+        }
+    }
+
+    pub fn save_download_settings(
+        app: &AppHandle,
+        settings: &DownloadSettings,
+    ) -> Result<(), String> {
+        let profile_dir = Self::ensure_profile_dir(app)?;
+        let path = profile_dir.join("download_settings.json");
+        settings_watcher::note_internal_write(&path);
+        let file = File::create(&path)
+            .map_err(|e| format!("Failed to create download settings file: {}", e))?;
+        serde_json::to_writer(file, settings)
+            .map_err(|e| format!("Failed to serialize download settings: {}", e))?;
+        Ok(())
+    }
+
+    pub fn load_download_settings(app: &AppHandle) -> Result<DownloadSettings, String> {
+        let profile_dir = Self::profile_dir(app)?;
+        let path = profile_dir.join("download_settings.json");
+        if path.exists() {
+            let file = File::open(&path)
+                .map_err(|e| format!("Failed to open download settings file: {}", e))?;
+            serde_json::from_reader(file)
+                .map_err(|e| format!("Failed to deserialize download settings: {}", e))
+        } else {
+            Ok(DownloadSettings::default())
+        }
+    }
+
+    pub fn save_load_timing_settings(
+        app: &AppHandle,
+        settings: &LoadTimingSettings,
+    ) -> Result<(), String> {
+        let profile_dir = Self::ensure_profile_dir(app)?;
+        let path = profile_dir.join("load_timing.json");
+        settings_watcher::note_internal_write(&path);
+        let file = File::create(&path)
+            .map_err(|e| format!("Failed to create load timing settings file: {}", e))?;
+        serde_json::to_writer(file, settings)
+            .map_err(|e| format!("Failed to serialize load timing settings: {}", e))?;
+        Ok(())
+    }
+
+    pub fn load_load_timing_settings(app: &AppHandle) -> Result<LoadTimingSettings, String> {
+        let profile_dir = Self::profile_dir(app)?;
+        let path = profile_dir.join("load_timing.json");
+        if path.exists() {
+            let file = File::open(&path)
+                .map_err(|e| format!("Failed to open load timing settings file: {}", e))?;
+            serde_json::from_reader(file)
+                .map_err(|e| format!("Failed to deserialize load timing settings: {}", e))
+        } else {
+            Ok(LoadTimingSettings::default())
+        }
+    }
+
+    pub fn save_local_rag_settings(
+        app: &AppHandle,
+        settings: &LocalRagSettings,
+    ) -> Result<(), String> {
+        let profile_dir = Self::ensure_profile_dir(app)?;
+        let path = profile_dir.join("local_rag.json");
+        settings_watcher::note_internal_write(&path);
+        let file = File::create(&path)
+            .map_err(|e| format!("Failed to create local RAG settings file: {}", e))?;
+        serde_json::to_writer(file, settings)
+            .map_err(|e| format!("Failed to serialize local RAG settings: {}", e))?;
+        Ok(())
+    }
+
+    pub fn load_local_rag_settings(app: &AppHandle) -> Result<LocalRagSettings, String> {
+        let profile_dir = Self::profile_dir(app)?;
+        let path = profile_dir.join("local_rag.json");
+        if path.exists() {
+            let file = File::open(&path)
+                .map_err(|e| format!("Failed to open local RAG settings file: {}", e))?;
+            serde_json::from_reader(file)
+                .map_err(|e| format!("Failed to deserialize local RAG settings: {}", e))
+        } else {
+            Ok(LocalRagSettings::default())
+        }
+    }
+
+    pub fn save_openai_server_settings(
+        app: &AppHandle,
+        settings: &OpenAiServerSettings,
+    ) -> Result<(), String> {
+        let profile_dir = Self::ensure_profile_dir(app)?;
+        let path = profile_dir.join("openai_server.json");
+        settings_watcher::note_internal_write(&path);
+        let file = File::create(&path)
+            .map_err(|e| format!("Failed to create API server settings file: {}", e))?;
+        serde_json::to_writer(file, settings)
+            .map_err(|e| format!("Failed to serialize API server settings: {}", e))?;
+        Ok(())
+    }
+
+    pub fn load_openai_server_settings(app: &AppHandle) -> Result<OpenAiServerSettings, String> {
+        let profile_dir = Self::profile_dir(app)?;
+        let path = profile_dir.join("openai_server.json");
+        if path.exists() {
+            let file = File::open(&path)
+                .map_err(|e| format!("Failed to open API server settings file: {}", e))?;
+            serde_json::from_reader(file)
+                .map_err(|e| format!("Failed to deserialize API server settings: {}", e))
+        } else {
+            Ok(OpenAiServerSettings::default())
+        }
+    }
+
+    pub fn save_models_storage_settings(
+        app: &AppHandle,
+        settings: &ModelsStorageSettings,
+    ) -> Result<(), String> {
+        let profile_dir = Self::ensure_profile_dir(app)?;
+        let path = profile_dir.join("models_storage.json");
+        settings_watcher::note_internal_write(&path);
+        let file = File::create(&path)
+            .map_err(|e| format!("Failed to create models storage settings file: {}", e))?;
+        serde_json::to_writer(file, settings)
+            .map_err(|e| format!("Failed to serialize models storage settings: {}", e))?;
+        Ok(())
+    }
+
+    pub fn load_models_storage_settings(app: &AppHandle) -> Result<ModelsStorageSettings, String> {
+        let profile_dir = Self::profile_dir(app)?;
+        let path = profile_dir.join("models_storage.json");
+        if path.exists() {
+            let file = File::open(&path)
+                .map_err(|e| format!("Failed to open models storage settings file: {}", e))?;
+            let settings: ModelsStorageSettings = serde_json::from_reader(file)
+                .map_err(|e| format!("Failed to deserialize models storage settings: {}", e))?;
+            Ok(settings.normalized())
+        } else {
+            Ok(ModelsStorageSettings::default())
+        }
+    }
 }
 
 pub type SharedState = Arc<Mutex<ModelState>>;
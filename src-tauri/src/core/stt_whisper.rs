@@ -69,6 +69,7 @@ pub fn save_settings(app: &AppHandle, settings: &SttSettings) -> Result<(), Stri
     }
     let data = serde_json::to_string_pretty(settings)
         .map_err(|e| format!("Failed to serialize STT settings: {e}"))?;
+    crate::core::settings_watcher::note_internal_write(&path);
     fs::write(&path, data).map_err(|e| format!("Failed to write STT settings: {e}"))
 }
 
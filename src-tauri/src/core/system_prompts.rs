@@ -0,0 +1,214 @@
+//! Библиотека системных промптов с версионированием.
+//!
+//! Актуальные промпты хранятся в `profile_dir/system_prompts.json`,
+//! предыдущие версии при каждом обновлении дописываются в
+//! `profile_dir/system_prompts_history.json`.
+
+use crate::core::state::ModelState;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use tauri::AppHandle;
+
+pub const SYSTEM_PROMPTS_FILE: &str = "system_prompts.json";
+pub const SYSTEM_PROMPTS_HISTORY_FILE: &str = "system_prompts_history.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NamedSystemPrompt {
+    pub id: String,
+    pub name: String,
+    pub content: String,
+    pub version: u32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Изменения для `update_system_prompt`; незаданные поля не меняются.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SystemPromptUpdate {
+    pub name: Option<String>,
+    pub content: Option<String>,
+    pub tags: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SystemPromptLibrary {
+    pub prompts: Vec<NamedSystemPrompt>,
+}
+
+impl SystemPromptLibrary {
+    pub fn get(&self, id: &str) -> Option<&NamedSystemPrompt> {
+        self.prompts.iter().find(|p| p.id == id)
+    }
+
+    pub fn add(&mut self, name: String, content: String, tags: Vec<String>) -> NamedSystemPrompt {
+        let now = Utc::now();
+        let prompt = NamedSystemPrompt {
+            id: format!("sp-{:016x}", rand::random::<u64>()),
+            name,
+            content,
+            version: 1,
+            created_at: now,
+            updated_at: now,
+            tags,
+        };
+        self.prompts.push(prompt.clone());
+        prompt
+    }
+
+    /// Применяет изменения и увеличивает `version`.
+    /// Возвращает (предыдущая версия, новая версия).
+    pub fn update(
+        &mut self,
+        id: &str,
+        update: SystemPromptUpdate,
+    ) -> Result<(NamedSystemPrompt, NamedSystemPrompt), String> {
+        let prompt = self
+            .prompts
+            .iter_mut()
+            .find(|p| p.id == id)
+            .ok_or_else(|| format!("System prompt not found: {id}"))?;
+        let previous = prompt.clone();
+
+        if let Some(name) = update.name {
+            prompt.name = name;
+        }
+        if let Some(content) = update.content {
+            prompt.content = content;
+        }
+        if let Some(tags) = update.tags {
+            prompt.tags = tags;
+        }
+        prompt.version += 1;
+        prompt.updated_at = Utc::now();
+        Ok((previous, prompt.clone()))
+    }
+
+    pub fn remove(&mut self, id: &str) -> Result<NamedSystemPrompt, String> {
+        let index = self
+            .prompts
+            .iter()
+            .position(|p| p.id == id)
+            .ok_or_else(|| format!("System prompt not found: {id}"))?;
+        Ok(self.prompts.remove(index))
+    }
+}
+
+/// Файлы библиотеки и истории версий.
+pub struct SystemPromptStore {
+    path: PathBuf,
+    history_path: PathBuf,
+}
+
+impl SystemPromptStore {
+    pub fn new(app: &AppHandle) -> Result<Self, String> {
+        let dir = ModelState::ensure_profile_dir(app)?;
+        Ok(Self::from_paths(
+            dir.join(SYSTEM_PROMPTS_FILE),
+            dir.join(SYSTEM_PROMPTS_HISTORY_FILE),
+        ))
+    }
+
+    pub fn from_paths(path: PathBuf, history_path: PathBuf) -> Self {
+        Self { path, history_path }
+    }
+
+    pub fn load(&self) -> Result<SystemPromptLibrary, String> {
+        if !self.path.exists() {
+            return Ok(SystemPromptLibrary::default());
+        }
+        let data = fs::read_to_string(&self.path)
+            .map_err(|e| format!("Failed to read system prompts: {}", e))?;
+        serde_json::from_str(&data).map_err(|e| format!("Failed to parse system prompts: {}", e))
+    }
+
+    pub fn save(&self, library: &SystemPromptLibrary) -> Result<(), String> {
+        let data = serde_json::to_string_pretty(library)
+            .map_err(|e| format!("Failed to serialize system prompts: {}", e))?;
+        fs::write(&self.path, data).map_err(|e| format!("Failed to write system prompts: {}", e))
+    }
+
+    fn load_all_history(&self) -> Result<Vec<NamedSystemPrompt>, String> {
+        if !self.history_path.exists() {
+            return Ok(Vec::new());
+        }
+        let data = fs::read_to_string(&self.history_path)
+            .map_err(|e| format!("Failed to read system prompt history: {}", e))?;
+        serde_json::from_str(&data)
+            .map_err(|e| format!("Failed to parse system prompt history: {}", e))
+    }
+
+    pub fn append_history(&self, previous: NamedSystemPrompt) -> Result<(), String> {
+        let mut history = self.load_all_history()?;
+        history.push(previous);
+        let data = serde_json::to_string_pretty(&history)
+            .map_err(|e| format!("Failed to serialize system prompt history: {}", e))?;
+        fs::write(&self.history_path, data)
+            .map_err(|e| format!("Failed to write system prompt history: {}", e))
+    }
+
+    /// Предыдущие версии промпта, от старых к новым.
+    pub fn history(&self, id: &str) -> Result<Vec<NamedSystemPrompt>, String> {
+        let mut history: Vec<NamedSystemPrompt> = self
+            .load_all_history()?
+            .into_iter()
+            .filter(|p| p.id == id)
+            .collect();
+        history.sort_by_key(|p| p.version);
+        Ok(history)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn update_bumps_version_and_keeps_history() {
+        let dir = std::env::temp_dir().join(format!("oxide-system-prompts-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let store = SystemPromptStore::from_paths(
+            dir.join(SYSTEM_PROMPTS_FILE),
+            dir.join(SYSTEM_PROMPTS_HISTORY_FILE),
+        );
+
+        let mut library = store.load().unwrap();
+        let prompt = library.add(
+            "Coder".into(),
+            "You write Rust.".into(),
+            vec!["code".into()],
+        );
+        let (previous, updated) = library
+            .update(
+                &prompt.id,
+                SystemPromptUpdate {
+                    content: Some("You write idiomatic Rust.".into()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        store.append_history(previous).unwrap();
+        store.save(&library).unwrap();
+
+        assert_eq!(updated.version, 2);
+        assert_eq!(updated.tags, ["code"]);
+        let history = store.history(&prompt.id).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].content, "You write Rust.");
+        assert_eq!(store.load().unwrap().get(&prompt.id), Some(&updated));
+
+        assert!(
+            library
+                .update("missing", SystemPromptUpdate::default())
+                .is_err()
+        );
+        library.remove(&prompt.id).unwrap();
+        assert!(library.prompts.is_empty());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
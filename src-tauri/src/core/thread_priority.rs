@@ -1,3 +1,6 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU8, Ordering};
+
 #[cfg(target_os = "windows")]
 mod windows {
     use windows_sys::Win32::System::Threading::{
@@ -18,6 +21,99 @@ mod windows {
     }
 }
 
+#[cfg(target_os = "linux")]
+mod linux {
+    // Nice value for the calling thread (Linux applies nice per thread id).
+    // Negative values require CAP_SYS_NICE, so raising priority usually fails.
+    pub fn set_nice(nice: libc::c_int) -> bool {
+        unsafe {
+            let tid = libc::gettid() as libc::id_t;
+            libc::setpriority(libc::PRIO_PROCESS, tid, nice) == 0
+        }
+    }
+}
+
+/// Priority for inference worker threads.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThreadPriority {
+    #[serde(rename = "low")]
+    BelowNormal,
+    #[default]
+    #[serde(rename = "normal")]
+    Normal,
+    #[serde(rename = "high")]
+    AboveNormal,
+}
+
+impl ThreadPriority {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::BelowNormal => "low",
+            Self::Normal => "normal",
+            Self::AboveNormal => "high",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_lowercase().as_str() {
+            "low" => Ok(Self::BelowNormal),
+            "normal" => Ok(Self::Normal),
+            "high" => Ok(Self::AboveNormal),
+            other => Err(format!(
+                "Unknown thread priority '{other}' (expected low, normal or high)"
+            )),
+        }
+    }
+
+    fn to_u8(self) -> u8 {
+        match self {
+            Self::BelowNormal => 0,
+            Self::Normal => 1,
+            Self::AboveNormal => 2,
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => Self::BelowNormal,
+            2 => Self::AboveNormal,
+            _ => Self::Normal,
+        }
+    }
+}
+
+static INFERENCE_THREAD_PRIORITY: AtomicU8 = AtomicU8::new(1);
+
+/// Priority applied to inference threads when they start.
+pub fn inference_thread_priority() -> ThreadPriority {
+    ThreadPriority::from_u8(INFERENCE_THREAD_PRIORITY.load(Ordering::Relaxed))
+}
+
+pub(crate) fn store_inference_thread_priority(priority: ThreadPriority) {
+    INFERENCE_THREAD_PRIORITY.store(priority.to_u8(), Ordering::Relaxed);
+}
+
+/// Sets the current thread priority (Windows: SetThreadPriority, Linux: nice).
+/// Returns `false` where unsupported or not permitted.
+pub fn set_current_thread_priority(priority: ThreadPriority) -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        linux::set_nice(match priority {
+            ThreadPriority::BelowNormal => 10,
+            ThreadPriority::Normal => 0,
+            ThreadPriority::AboveNormal => -5,
+        })
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        match priority {
+            ThreadPriority::BelowNormal => set_current_thread_below_normal(),
+            ThreadPriority::Normal => set_current_thread_normal(),
+            ThreadPriority::AboveNormal => set_current_thread_above_normal(),
+        }
+    }
+}
+
 /// Sets the current thread priority to below normal (Windows only).
 pub fn set_current_thread_below_normal() -> bool {
     #[cfg(target_os = "windows")]
@@ -91,4 +187,20 @@ mod tests {
     fn can_create_and_drop_guard() {
         let _guard = ThreadPriorityGuard::below_normal();
     }
+
+    #[test]
+    fn thread_priority_parses_settings_values() {
+        for priority in [
+            ThreadPriority::BelowNormal,
+            ThreadPriority::Normal,
+            ThreadPriority::AboveNormal,
+        ] {
+            assert_eq!(ThreadPriority::parse(priority.as_str()), Ok(priority));
+        }
+        assert_eq!(
+            ThreadPriority::parse(" High "),
+            Ok(ThreadPriority::AboveNormal)
+        );
+        assert!(ThreadPriority::parse("realtime").is_err());
+    }
 }
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use candle::Result;
 
 /// Потоковая декодирующая обёртка для токенов, позволяет выдавать прирост текста по мере генерации
@@ -6,6 +8,8 @@ pub struct TokenOutputStream {
     tokens: Vec<u32>,
     prev_index: usize,
     current_index: usize,
+    /// Специальные токены, которые отдаются текстом, хотя декодирование их пропускает
+    preserved: HashMap<u32, String>,
 }
 
 impl TokenOutputStream {
@@ -15,9 +19,25 @@ impl TokenOutputStream {
             tokens: Vec::new(),
             prev_index: 0,
             current_index: 0,
+            preserved: HashMap::new(),
+        }
+    }
+
+    /// Отдавать перечисленные специальные токены как текст (например `<|python_tag|>`
+    /// для парсера вызовов инструментов). Токены, которых нет в словаре, игнорируются.
+    pub fn preserve_special_tokens(&mut self, tokens: &[&str]) {
+        for &token in tokens {
+            if let Some(id) = self.tokenizer.token_to_id(token) {
+                self.preserved.insert(id, token.to_string());
+            }
         }
     }
 
+    /// Текст сохраняемого специального токена по его id
+    pub fn preserved_token(&self, token: u32) -> Option<&str> {
+        self.preserved.get(&token).map(String::as_str)
+    }
+
     fn decode(&self, tokens: &[u32]) -> Result<String> {
         match self.tokenizer.decode(tokens, true) {
             Ok(str_) => Ok(str_),
@@ -26,6 +46,15 @@ impl TokenOutputStream {
     }
 
     pub fn next_token(&mut self, token: u32) -> Result<Option<String>> {
+        if let Some(tag) = self.preserved.get(&token).cloned() {
+            // Отдаём удержанный хвост вместе с тегом и начинаем новое окно после него
+            let mut text = self.decode_rest()?.unwrap_or_default();
+            text.push_str(&tag);
+            self.tokens.push(token);
+            self.prev_index = self.tokens.len();
+            self.current_index = self.tokens.len();
+            return Ok(Some(text));
+        }
         let prev_text = if self.tokens.is_empty() {
             String::new()
         } else {
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatMessage {
@@ -114,6 +115,32 @@ pub struct GenerateRequest {
     /// Tool choice: auto, none, required, or specific function
     #[serde(default)]
     pub tool_choice: Option<ToolChoice>,
+    /// Не подставлять `{{date}}`/`{{model}}` и т.п. в системный промпт
+    #[serde(default)]
+    pub disable_template_substitution: bool,
+    /// For models that start in implicit thinking mode (DeepSeek-R1, QwQ):
+    /// close the thinking block in the prompt so the model answers directly.
+    /// Trade-off: much faster and cheaper, but weaker on multi-step problems.
+    #[serde(default)]
+    pub suppress_thinking: bool,
+    /// Limit on thinking tokens. The prompt is opened with `<think>` if needed;
+    /// once the budget is spent `</think>` is forced and the model moves on to
+    /// the answer, which may be less reliable. Ignored with `suppress_thinking`.
+    #[serde(default)]
+    pub thinking_budget_tokens: Option<u32>,
+    /// Fill-in-the-middle completion for Coder models: the prompt becomes
+    /// `<|fim_prefix|>…<|fim_suffix|>…<|fim_middle|>` instead of a chat.
+    /// Ignored unless the model name contains "coder" and its vocab has
+    /// `<|fim_prefix|>`. A long prefix is trimmed from the start.
+    #[serde(default)]
+    pub fim_mode: Option<FimConfig>,
+}
+
+/// Код до и после курсора для FIM-дополнения.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FimConfig {
+    pub prefix: String,
+    pub suffix: String,
 }
 
 /// Tool choice options for controlling function calling behavior
@@ -142,6 +169,180 @@ pub struct Attachment {
     pub name: Option<String>,
     pub path: Option<String>,
     pub bytes_b64: Option<String>,
+    /// Размер файла в байтах (если известен заранее) — для проверки до чтения
+    #[serde(default)]
+    pub size: Option<u64>,
+}
+
+/// Прокси для исходящих HTTP-запросов (HF Hub, загрузки).
+/// Если поле не задано, используются переменные окружения HTTP_PROXY/HTTPS_PROXY.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProxySettings {
+    #[serde(default)]
+    pub http_proxy: Option<String>,
+    #[serde(default)]
+    pub https_proxy: Option<String>,
+}
+
+impl ProxySettings {
+    pub fn validate(&self) -> Result<(), String> {
+        for (field, value) in [
+            ("http_proxy", &self.http_proxy),
+            ("https_proxy", &self.https_proxy),
+        ] {
+            if let Some(url) = value.as_deref().map(str::trim).filter(|u| !u.is_empty()) {
+                let parsed = reqwest::Url::parse(url)
+                    .map_err(|e| format!("Invalid {field} URL '{url}': {e}"))?;
+                if !matches!(parsed.scheme(), "http" | "https" | "socks5") {
+                    return Err(format!(
+                        "Unsupported {field} scheme '{}': use http, https or socks5",
+                        parsed.scheme()
+                    ));
+                }
+                if !parsed.has_host() {
+                    return Err(format!("Invalid {field} URL '{url}': missing host"));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Каталоги с локальными моделями.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ModelsStorageSettings {
+    #[serde(default)]
+    pub models_dirs: Vec<String>,
+}
+
+impl ModelsStorageSettings {
+    /// Убирает пустые и повторяющиеся записи в `models_dirs`.
+    pub fn normalized(mut self) -> Self {
+        let mut seen = std::collections::HashSet::new();
+        self.models_dirs = self
+            .models_dirs
+            .into_iter()
+            .map(|dir| dir.trim().to_string())
+            .filter(|dir| !dir.is_empty() && seen.insert(dir.clone()))
+            .collect();
+        self
+    }
+}
+
+/// Параметры менеджера загрузок.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DownloadSettings {
+    /// Сколько файлов качается одновременно; остальные ждут в очереди
+    #[serde(default = "default_max_concurrent_downloads")]
+    pub max_concurrent_downloads: u32,
+}
+
+fn default_max_concurrent_downloads() -> u32 {
+    2
+}
+
+impl Default for DownloadSettings {
+    fn default() -> Self {
+        Self {
+            max_concurrent_downloads: default_max_concurrent_downloads(),
+        }
+    }
+}
+
+/// Стадия загрузки дольше этого порога порождает событие `load_slow_stage`.
+pub const STAGE_WARN_THRESHOLD_MS: u64 = 10_000;
+
+/// Параметры загрузки моделей и её профилирования.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LoadTimingSettings {
+    /// Стадия загрузки дольше порога порождает событие `load_slow_stage`
+    #[serde(default = "default_stage_warn_threshold_ms")]
+    pub stage_warn_threshold_ms: u64,
+    /// Прогревать шарды SafeTensors параллельным чтением перед mmap
+    #[serde(default)]
+    pub parallel_load_shards: bool,
+}
+
+fn default_stage_warn_threshold_ms() -> u64 {
+    STAGE_WARN_THRESHOLD_MS
+}
+
+impl Default for LoadTimingSettings {
+    fn default() -> Self {
+        Self {
+            stage_warn_threshold_ms: default_stage_warn_threshold_ms(),
+            parallel_load_shards: false,
+        }
+    }
+}
+
+/// Параметры OpenAI-совместимого API-сервера.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct OpenAiServerSettings {
+    /// Эндпоинт `/v1/ws/chat` (WebSocket-стрим для прокси, буферизующих SSE)
+    #[serde(default = "default_ws_enabled")]
+    pub ws_enabled: bool,
+    /// Лимит тела входящего запроса в мегабайтах; применяется при запуске сервера
+    #[serde(default = "default_max_request_body_mb")]
+    pub max_request_body_mb: u32,
+    /// Сколько секунд ждать завершения запросов при остановке сервера
+    #[serde(default = "default_drain_timeout_secs")]
+    pub drain_timeout_secs: u64,
+    /// Origin-ы, которым разрешены запросы из браузера; пустой список — любые
+    #[serde(default)]
+    pub cors_allowed_origins: Vec<String>,
+}
+
+fn default_ws_enabled() -> bool {
+    true
+}
+
+fn default_max_request_body_mb() -> u32 {
+    32
+}
+
+fn default_drain_timeout_secs() -> u64 {
+    30
+}
+
+impl Default for OpenAiServerSettings {
+    fn default() -> Self {
+        Self {
+            ws_enabled: default_ws_enabled(),
+            max_request_body_mb: default_max_request_body_mb(),
+            drain_timeout_secs: default_drain_timeout_secs(),
+            cors_allowed_origins: Vec::new(),
+        }
+    }
+}
+
+impl OpenAiServerSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_request_body_mb == 0 {
+            return Err("max_request_body_mb must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Параметры локального RAG-конвейера.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LocalRagSettings {
+    /// Сколько секунд хранить результат `/v1/embeddings`; `0` отключает кэш
+    #[serde(default = "default_cache_ttl_secs")]
+    pub cache_ttl_secs: u64,
+}
+
+fn default_cache_ttl_secs() -> u64 {
+    300
+}
+
+impl Default for LocalRagSettings {
+    fn default() -> Self {
+        Self {
+            cache_ttl_secs: default_cache_ttl_secs(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -165,3 +366,179 @@ impl Default for SttSettings {
         }
     }
 }
+
+/// Путь к существующему файлу `.gguf`; проверяется при разборе аргументов команды.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "PathBuf")]
+pub struct ModelPath(PathBuf);
+
+impl TryFrom<String> for ModelPath {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let path = PathBuf::from(value.trim());
+        if !path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("gguf"))
+        {
+            return Err(format!("Not a GGUF file: {}", path.display()));
+        }
+        if !path.is_file() {
+            return Err(format!("File does not exist: {}", path.display()));
+        }
+        Ok(Self(path))
+    }
+}
+
+impl From<ModelPath> for PathBuf {
+    fn from(value: ModelPath) -> Self {
+        value.0
+    }
+}
+
+impl AsRef<Path> for ModelPath {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+/// Путь к существующему каталогу.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "PathBuf")]
+pub struct FolderPath(PathBuf);
+
+impl TryFrom<String> for FolderPath {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let path = PathBuf::from(value.trim());
+        if !path.exists() {
+            return Err(format!("Path does not exist: {}", path.display()));
+        }
+        if !path.is_dir() {
+            return Err(format!("Path is not a directory: {}", path.display()));
+        }
+        Ok(Self(path))
+    }
+}
+
+impl From<FolderPath> for PathBuf {
+    fn from(value: FolderPath) -> Self {
+        value.0
+    }
+}
+
+impl AsRef<Path> for FolderPath {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+/// Идентификатор репозитория Hugging Face в виде `owner/name`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct RepoId(String);
+
+impl RepoId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl TryFrom<String> for RepoId {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let value = value.trim();
+        let valid_part = |part: &str| {
+            !part.is_empty()
+                && !part.starts_with('.')
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        };
+        match value.split_once('/') {
+            Some((owner, name)) if valid_part(owner) && valid_part(name) => {
+                Ok(Self(value.to_string()))
+            }
+            _ => Err(format!(
+                "Invalid repository id '{value}': expected 'owner/name'"
+            )),
+        }
+    }
+}
+
+impl From<RepoId> for String {
+    fn from(value: RepoId) -> Self {
+        value.0
+    }
+}
+
+impl std::fmt::Display for RepoId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn openai_server_settings_default_missing_fields() {
+        let settings: OpenAiServerSettings =
+            serde_json::from_str(r#"{"ws_enabled": false}"#).unwrap();
+        assert!(!settings.ws_enabled);
+        assert_eq!(settings.max_request_body_mb, 32);
+        assert_eq!(settings.drain_timeout_secs, 30);
+        assert!(settings.validate().is_ok());
+
+        let empty_body = OpenAiServerSettings {
+            max_request_body_mb: 0,
+            ..settings
+        };
+        assert!(empty_body.validate().is_err());
+    }
+
+    #[test]
+    fn proxy_settings_accept_only_proxy_schemes() {
+        let proxy = |url: &str| ProxySettings {
+            http_proxy: Some(url.to_string()),
+            https_proxy: None,
+        };
+        for valid in [
+            "http://proxy:3128",
+            "https://user:pw@proxy",
+            "socks5://127.0.0.1:1080",
+        ] {
+            assert!(proxy(valid).validate().is_ok(), "{valid}");
+        }
+        for invalid in [
+            "ftp://proxy",
+            "file:///etc/passwd",
+            "proxy:3128",
+            "not a url",
+        ] {
+            assert!(proxy(invalid).validate().is_err(), "{invalid}");
+        }
+        assert!(ProxySettings::default().validate().is_ok());
+    }
+
+    #[test]
+    fn validated_newtypes_reject_bad_input() {
+        assert!(RepoId::try_from("Qwen/Qwen3-4B-GGUF".to_string()).is_ok());
+        for invalid in ["Qwen", "Qwen/", "/model", "a/b/c", "owner/na me", "../etc"] {
+            assert!(RepoId::try_from(invalid.to_string()).is_err(), "{invalid}");
+        }
+
+        let dir = std::env::temp_dir();
+        assert!(FolderPath::try_from(dir.display().to_string()).is_ok());
+        assert!(ModelPath::try_from(dir.display().to_string()).is_err());
+        let missing = dir.join("oxide-missing-model.gguf");
+        let err = ModelPath::try_from(missing.display().to_string()).unwrap_err();
+        assert!(err.starts_with("File does not exist"));
+
+        let err = serde_json::from_str::<RepoId>(r#""not-a-repo""#).unwrap_err();
+        assert!(err.to_string().contains("expected 'owner/name'"));
+    }
+}
@@ -1,7 +1,7 @@
 use std::time::{Duration, Instant};
 use tauri::Emitter; // Keep for TauriBackend
 
-use crate::core::performance::InferenceMetrics;
+use crate::core::performance::{InferenceMetrics, ModelMemoryStats, PerformanceRegression};
 use crate::core::types::StreamMessage;
 use crate::generate::thinking_parser::ParsedChunk;
 use crate::generate::tool_call_parser::ToolCall;
@@ -19,13 +19,24 @@ pub enum GenerationEvent {
     ToolCall(ToolCall),
     // Variant removed
     Metrics(InferenceMetrics),
+    /// Заполненность KV-кэша после запроса
+    MemoryStats(ModelMemoryStats),
+    /// Генерация заметно медленнее базовой скорости модели
+    PerformanceRegression(PerformanceRegression),
     PromptDump(String),
+    /// Ответ не соответствует запрошенной JSON Schema
+    SchemaViolation(String),
     Done,
 }
 
 /// Trait abstracting the destination of generation events
 pub trait EmissionBackend: Send {
     fn emit(&self, event: GenerationEvent);
+
+    /// Получатель больше не ждёт событий (клиент отключился, задача отменена)
+    fn is_cancelled(&self) -> bool {
+        false
+    }
 }
 
 /// Backend that emits events to Tauri frontend
@@ -60,9 +71,20 @@ impl EmissionBackend for TauriBackend {
                 log::debug!("[emit] inference_metrics");
                 let _ = self.app.emit("inference_metrics", metrics);
             }
+            GenerationEvent::MemoryStats(stats) => {
+                let _ = self.app.emit("model_memory_stats", stats);
+            }
+            GenerationEvent::PerformanceRegression(regression) => {
+                log::debug!("[emit] performance_regression_detected");
+                let _ = self.app.emit("performance_regression_detected", regression);
+            }
             GenerationEvent::PromptDump(dump) => {
                 let _ = self.app.emit("prompt_tokens_dump", dump);
             }
+            GenerationEvent::SchemaViolation(violation) => {
+                log::debug!("[emit] schema_violation: {}", violation);
+                let _ = self.app.emit("schema_violation", violation);
+            }
             GenerationEvent::Done => {
                 let _ = self.app.emit("token", "[DONE]"); // Legacy compatible
                 let _ = self.app.emit("message_done", ());
@@ -176,10 +198,29 @@ impl ChunkEmitter {
             .emit(GenerationEvent::ToolCall(tool_call.clone()));
     }
 
+    /// Emit structured output schema violation.
+    pub fn emit_schema_violation(&self, violation: String) {
+        self.backend
+            .emit(GenerationEvent::SchemaViolation(violation));
+    }
+
     /// Emit inference metrics.
     pub fn emit_metrics(&self, metrics: InferenceMetrics) {
         self.backend.emit(GenerationEvent::Metrics(metrics));
     }
+
+    pub fn emit_memory_stats(&self, stats: ModelMemoryStats) {
+        self.backend.emit(GenerationEvent::MemoryStats(stats));
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.backend.is_cancelled()
+    }
+
+    pub fn emit_performance_regression(&self, regression: PerformanceRegression) {
+        self.backend
+            .emit(GenerationEvent::PerformanceRegression(regression));
+    }
 }
 
 impl Drop for ChunkEmitter {
@@ -171,16 +171,267 @@ pub fn validate_json(output: &str) -> Result<serde_json::Value, String> {
     serde_json::from_str(output).map_err(|e| format!("Invalid JSON: {}", e))
 }
 
-/// Валидирует JSON против schema (упрощённая версия)
+/// Результат генерации со structured output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StructuredOutputResult {
+    pub content: String,
+    /// Описание нарушения схемы, если ответ ей не соответствует
+    pub schema_violation: Option<String>,
+}
+
+impl StructuredOutputResult {
+    /// Проверяет сгенерированный текст на соответствие формату
+    pub fn check(content: String, format: &OutputFormat) -> Self {
+        let schema_violation = match format {
+            OutputFormat::None => None,
+            OutputFormat::Json => validate_json(content.trim()).err(),
+            OutputFormat::JsonSchema(schema) => validate_json(content.trim())
+                .and_then(|value| validate_against_schema(&value, schema))
+                .err(),
+        };
+        Self {
+            content,
+            schema_violation,
+        }
+    }
+}
+
+/// Валидирует JSON против schema.
+///
+/// Поддерживается подмножество JSON Schema, достаточное для structured outputs:
+/// `type`, `enum`, `const`, `properties`, `required`, `additionalProperties: false`,
+/// `items`, `minItems`/`maxItems`, `minLength`/`maxLength`, `minimum`/`maximum`,
+/// `anyOf`/`oneOf`. Остальные ключевые слова отклоняет `validate_schema_definition`.
 pub fn validate_against_schema(
     json: &serde_json::Value,
-    _schema: &serde_json::Value,
+    schema: &serde_json::Value,
 ) -> Result<(), String> {
-    // Базовая проверка: JSON существует и является объектом или массивом
-    if json.is_null() {
-        return Err("JSON is null".to_string());
+    validate_node(json, schema, "$")
+}
+
+/// Ключевые слова JSON Schema, которые влияют на валидность, но не проверяются.
+/// Схему с ними отклоняем, чтобы клиент не считал ограничение действующим.
+const UNSUPPORTED_KEYWORDS: &[&str] = &[
+    "$ref",
+    "allOf",
+    "not",
+    "if",
+    "then",
+    "else",
+    "pattern",
+    "patternProperties",
+    "prefixItems",
+    "contains",
+    "uniqueItems",
+    "multipleOf",
+    "exclusiveMinimum",
+    "exclusiveMaximum",
+    "minProperties",
+    "maxProperties",
+    "dependentRequired",
+    "dependentSchemas",
+];
+
+/// Проверяет, что сама схема корректна (для валидации до генерации)
+pub fn validate_schema_definition(schema: &serde_json::Value) -> Result<(), String> {
+    let Some(obj) = schema.as_object() else {
+        return Err("Schema must be a JSON object".to_string());
+    };
+    if let Some(keyword) = UNSUPPORTED_KEYWORDS.iter().find(|k| obj.contains_key(**k)) {
+        return Err(format!("Unsupported schema keyword: {keyword}"));
+    }
+    if obj
+        .get("additionalProperties")
+        .is_some_and(|v| !v.is_boolean())
+    {
+        return Err("'additionalProperties' must be a boolean".to_string());
+    }
+    if let Some(ty) = obj.get("type") {
+        let types: Vec<&serde_json::Value> = match ty {
+            serde_json::Value::Array(items) => items.iter().collect(),
+            other => vec![other],
+        };
+        for t in types {
+            match t.as_str() {
+                Some("object" | "array" | "string" | "number" | "integer" | "boolean" | "null") => {
+                }
+                _ => return Err(format!("Unsupported schema type: {t}")),
+            }
+        }
+    }
+    if let Some(props) = obj.get("properties") {
+        let Some(props) = props.as_object() else {
+            return Err("'properties' must be an object".to_string());
+        };
+        for (name, sub) in props {
+            validate_schema_definition(sub).map_err(|e| format!("properties.{name}: {e}"))?;
+        }
+    }
+    if let Some(items) = obj.get("items") {
+        validate_schema_definition(items).map_err(|e| format!("items: {e}"))?;
+    }
+    for key in ["anyOf", "oneOf"] {
+        let Some(branches) = obj.get(key) else {
+            continue;
+        };
+        let Some(branches) = branches.as_array().filter(|b| !b.is_empty()) else {
+            return Err(format!("'{key}' must be a non-empty array"));
+        };
+        for (idx, branch) in branches.iter().enumerate() {
+            validate_schema_definition(branch).map_err(|e| format!("{key}[{idx}]: {e}"))?;
+        }
+    }
+    if let Some(required) = obj.get("required")
+        && !required
+            .as_array()
+            .is_some_and(|r| r.iter().all(|v| v.is_string()))
+    {
+        return Err("'required' must be an array of strings".to_string());
     }
-    // TODO: Полная валидация JSON Schema (jsonschema crate)
+    Ok(())
+}
+
+fn type_matches(value: &serde_json::Value, ty: &str) -> bool {
+    match ty {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn validate_node(
+    value: &serde_json::Value,
+    schema: &serde_json::Value,
+    path: &str,
+) -> Result<(), String> {
+    let Some(schema) = schema.as_object() else {
+        // `true`/пустая схема допускают любое значение
+        return Ok(());
+    };
+
+    if let Some(ty) = schema.get("type") {
+        let ok = match ty {
+            serde_json::Value::String(t) => type_matches(value, t),
+            serde_json::Value::Array(types) => types
+                .iter()
+                .filter_map(|t| t.as_str())
+                .any(|t| type_matches(value, t)),
+            _ => true,
+        };
+        if !ok {
+            return Err(format!("{path}: expected type {ty}, got {value}"));
+        }
+    }
+
+    if let Some(expected) = schema.get("const")
+        && value != expected
+    {
+        return Err(format!("{path}: expected constant {expected}"));
+    }
+
+    if let Some(variants) = schema.get("enum").and_then(|v| v.as_array())
+        && !variants.contains(value)
+    {
+        return Err(format!(
+            "{path}: value {value} is not one of the allowed values"
+        ));
+    }
+
+    if let Some(options) = schema.get("anyOf").and_then(|v| v.as_array())
+        && !options
+            .iter()
+            .any(|option| validate_node(value, option, path).is_ok())
+    {
+        return Err(format!("{path}: value does not match any anyOf branch"));
+    }
+
+    // oneOf: ровно одна ветка, а не «хотя бы одна»
+    if let Some(options) = schema.get("oneOf").and_then(|v| v.as_array()) {
+        let matched = options
+            .iter()
+            .filter(|option| validate_node(value, option, path).is_ok())
+            .count();
+        if matched != 1 {
+            return Err(format!(
+                "{path}: value matches {matched} oneOf branches, expected exactly one"
+            ));
+        }
+    }
+
+    match value {
+        serde_json::Value::Object(map) => {
+            if let Some(required) = schema.get("required").and_then(|v| v.as_array()) {
+                for key in required.iter().filter_map(|k| k.as_str()) {
+                    if !map.contains_key(key) {
+                        return Err(format!("{path}: missing required property '{key}'"));
+                    }
+                }
+            }
+            let props = schema.get("properties").and_then(|v| v.as_object());
+            let additional_forbidden = schema
+                .get("additionalProperties")
+                .is_some_and(|v| v == &serde_json::Value::Bool(false));
+            for (key, item) in map {
+                match props.and_then(|p| p.get(key)) {
+                    Some(sub) => validate_node(item, sub, &format!("{path}.{key}"))?,
+                    None if additional_forbidden => {
+                        return Err(format!("{path}: unexpected property '{key}'"));
+                    }
+                    None => {}
+                }
+            }
+        }
+        serde_json::Value::Array(items) => {
+            if let Some(min) = schema.get("minItems").and_then(|v| v.as_u64())
+                && (items.len() as u64) < min
+            {
+                return Err(format!("{path}: expected at least {min} items"));
+            }
+            if let Some(max) = schema.get("maxItems").and_then(|v| v.as_u64())
+                && (items.len() as u64) > max
+            {
+                return Err(format!("{path}: expected at most {max} items"));
+            }
+            if let Some(item_schema) = schema.get("items") {
+                for (idx, item) in items.iter().enumerate() {
+                    validate_node(item, item_schema, &format!("{path}[{idx}]"))?;
+                }
+            }
+        }
+        serde_json::Value::String(text) => {
+            let len = text.chars().count() as u64;
+            if let Some(min) = schema.get("minLength").and_then(|v| v.as_u64())
+                && len < min
+            {
+                return Err(format!("{path}: string shorter than {min}"));
+            }
+            if let Some(max) = schema.get("maxLength").and_then(|v| v.as_u64())
+                && len > max
+            {
+                return Err(format!("{path}: string longer than {max}"));
+            }
+        }
+        serde_json::Value::Number(num) => {
+            let n = num.as_f64().unwrap_or_default();
+            if let Some(min) = schema.get("minimum").and_then(|v| v.as_f64())
+                && n < min
+            {
+                return Err(format!("{path}: {n} is less than minimum {min}"));
+            }
+            if let Some(max) = schema.get("maximum").and_then(|v| v.as_f64())
+                && n > max
+            {
+                return Err(format!("{path}: {n} is greater than maximum {max}"));
+            }
+        }
+        _ => {}
+    }
+
     Ok(())
 }
 
@@ -220,4 +471,74 @@ mod tests {
         assert!(validate_json("{\"test\": 123}").is_ok());
         assert!(validate_json("not json").is_err());
     }
+
+    #[test]
+    fn test_validate_against_schema() {
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": {
+                "name": { "type": "string" },
+                "age": { "type": "integer", "minimum": 0 }
+            },
+            "required": ["name"],
+            "additionalProperties": false
+        });
+
+        let ok = serde_json::json!({ "name": "Ann", "age": 30 });
+        assert!(validate_against_schema(&ok, &schema).is_ok());
+
+        let missing = serde_json::json!({ "age": 30 });
+        assert!(validate_against_schema(&missing, &schema).is_err());
+
+        let wrong_type = serde_json::json!({ "name": "Ann", "age": "30" });
+        let err = validate_against_schema(&wrong_type, &schema).unwrap_err();
+        assert!(err.starts_with("$.age"));
+
+        let extra = serde_json::json!({ "name": "Ann", "extra": true });
+        assert!(validate_against_schema(&extra, &schema).is_err());
+    }
+
+    #[test]
+    fn test_one_of_requires_exactly_one_branch() {
+        let schema = serde_json::json!({
+            "oneOf": [{ "type": "integer" }, { "type": "number", "minimum": 10 }]
+        });
+        assert!(validate_against_schema(&serde_json::json!(3), &schema).is_ok());
+        assert!(validate_against_schema(&serde_json::json!(10.5), &schema).is_ok());
+        // 12 подходит под обе ветки
+        let err = validate_against_schema(&serde_json::json!(12), &schema).unwrap_err();
+        assert!(err.contains("2 oneOf branches"));
+        assert!(validate_against_schema(&serde_json::json!("x"), &schema).is_err());
+    }
+
+    #[test]
+    fn test_schema_definition_rejects_unsupported_keywords() {
+        let ok = serde_json::json!({
+            "type": "object",
+            "properties": { "id": { "anyOf": [{ "type": "string" }, { "type": "null" }] } }
+        });
+        assert!(validate_schema_definition(&ok).is_ok());
+
+        let with_pattern = serde_json::json!({
+            "type": "object",
+            "properties": { "zip": { "type": "string", "pattern": "^[0-9]{5}$" } }
+        });
+        let err = validate_schema_definition(&with_pattern).unwrap_err();
+        assert!(err.contains("pattern"));
+
+        let with_ref = serde_json::json!({ "oneOf": [{ "$ref": "#/$defs/item" }] });
+        assert!(validate_schema_definition(&with_ref).is_err());
+    }
+
+    #[test]
+    fn test_structured_output_result() {
+        let format = OutputFormat::JsonSchema(serde_json::json!({
+            "type": "array",
+            "items": { "type": "number" }
+        }));
+        let ok = StructuredOutputResult::check("[1, 2.5]".to_string(), &format);
+        assert!(ok.schema_violation.is_none());
+        let bad = StructuredOutputResult::check("[1, \"x\"]".to_string(), &format);
+        assert!(bad.schema_violation.is_some());
+    }
 }
@@ -4,6 +4,7 @@ pub mod emit;
 pub mod grammar;
 pub mod minp;
 pub mod sampling;
+pub mod stop_sequence;
 pub mod stream;
 pub mod thinking_parser;
 pub mod tool_call_parser;
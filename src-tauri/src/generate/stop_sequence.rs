@@ -0,0 +1,148 @@
+//! Буферизация текста для пользовательских stop sequences.
+//!
+//! Удерживает хвост сгенерированного текста, который может оказаться началом
+//! stop sequence, чтобы клиент не увидел её части (например, `"\n\nHu"` перед
+//! `"\n\nHuman:"`). Текст отдаётся только после того, как совпадение исключено.
+
+/// Результат добавления фрагмента текста.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StopCheck {
+    /// Stop sequence не найдена; содержит текст, который можно отдать клиенту.
+    Continue(String),
+    /// Найдена stop sequence; содержит текст до неё. Генерацию нужно остановить.
+    Stopped(String),
+}
+
+/// Буфер, удерживающий возможное начало stop sequence.
+#[derive(Debug, Clone, Default)]
+pub struct StopSequenceBuffer {
+    stops: Vec<String>,
+    pending: String,
+}
+
+impl StopSequenceBuffer {
+    pub fn new(stops: &[String]) -> Self {
+        Self {
+            stops: stops.iter().filter(|s| !s.is_empty()).cloned().collect(),
+            pending: String::new(),
+        }
+    }
+
+    /// Нет ни одной stop sequence — буфер работает как passthrough.
+    pub fn is_empty(&self) -> bool {
+        self.stops.is_empty()
+    }
+
+    /// Добавить фрагмент и получить текст, безопасный для отправки.
+    pub fn push(&mut self, text: &str) -> StopCheck {
+        if self.stops.is_empty() {
+            return StopCheck::Continue(text.to_string());
+        }
+
+        self.pending.push_str(text);
+
+        // Самое раннее вхождение любой stop sequence
+        if let Some(pos) = self
+            .stops
+            .iter()
+            .filter_map(|stop| self.pending.find(stop.as_str()))
+            .min()
+        {
+            let before = self.pending[..pos].to_string();
+            self.pending.clear();
+            return StopCheck::Stopped(before);
+        }
+
+        let hold = self.held_suffix_len();
+        let release_at = self.pending.len() - hold;
+        let released = self.pending[..release_at].to_string();
+        self.pending.drain(..release_at);
+        StopCheck::Continue(released)
+    }
+
+    /// Отдать удерживаемый хвост (конец генерации без срабатывания stop).
+    pub fn flush(&mut self) -> String {
+        std::mem::take(&mut self.pending)
+    }
+
+    /// Длина самого длинного суффикса `pending`, который является началом
+    /// какой-либо stop sequence.
+    fn held_suffix_len(&self) -> usize {
+        self.pending
+            .char_indices()
+            .map(|(i, _)| i)
+            .find(|&start| {
+                let suffix = &self.pending[start..];
+                self.stops.iter().any(|stop| stop.starts_with(suffix))
+            })
+            .map(|start| self.pending.len() - start)
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buffer(stops: &[&str]) -> StopSequenceBuffer {
+        let stops: Vec<String> = stops.iter().map(|s| s.to_string()).collect();
+        StopSequenceBuffer::new(&stops)
+    }
+
+    #[test]
+    fn passthrough_without_stops() {
+        let mut buf = buffer(&[]);
+        assert!(buf.is_empty());
+        assert_eq!(buf.push("hello"), StopCheck::Continue("hello".into()));
+    }
+
+    #[test]
+    fn holds_partial_stop_across_tokens() {
+        let mut buf = buffer(&["\n\nHuman:"]);
+        assert_eq!(buf.push("Sure."), StopCheck::Continue("Sure.".into()));
+        assert_eq!(buf.push("\n\nHu"), StopCheck::Continue(String::new()));
+        assert_eq!(buf.push("man:"), StopCheck::Stopped(String::new()));
+    }
+
+    #[test]
+    fn releases_held_text_when_match_fails() {
+        let mut buf = buffer(&["\n\nHuman:"]);
+        assert_eq!(buf.push("a\n\nHu"), StopCheck::Continue("a".into()));
+        assert_eq!(buf.push("go"), StopCheck::Continue("\n\nHugo".into()));
+    }
+
+    #[test]
+    fn text_before_stop_in_same_token_is_returned() {
+        let mut buf = buffer(&["END"]);
+        assert_eq!(
+            buf.push("done END tail"),
+            StopCheck::Stopped("done ".into())
+        );
+    }
+
+    #[test]
+    fn overlapping_stop_sequences_earliest_wins() {
+        let mut buf = buffer(&["\n\nHuman:", "\nH"]);
+        assert_eq!(buf.push("x\n"), StopCheck::Continue("x".into()));
+        assert_eq!(buf.push("\nHum"), StopCheck::Stopped("\n".into()));
+
+        let mut buf = buffer(&["abcd", "bc"]);
+        assert_eq!(buf.push("ab"), StopCheck::Continue(String::new()));
+        assert_eq!(buf.push("c"), StopCheck::Stopped("a".into()));
+    }
+
+    #[test]
+    fn flush_returns_held_tail() {
+        let mut buf = buffer(&["</answer>"]);
+        assert_eq!(buf.push("42</ans"), StopCheck::Continue("42".into()));
+        assert_eq!(buf.flush(), "</ans");
+        assert_eq!(buf.flush(), "");
+    }
+
+    #[test]
+    fn multibyte_text_is_split_on_char_boundaries() {
+        let mut buf = buffer(&["Стоп"]);
+        assert_eq!(buf.push("привет Ст"), StopCheck::Continue("привет ".into()));
+        assert_eq!(buf.push("оп"), StopCheck::Stopped(String::new()));
+    }
+}
@@ -12,19 +12,28 @@ use super::{
 };
 use crate::core::attachments_text::gather_text_from_attachments;
 use crate::core::config::SamplingOptions;
-use crate::core::performance::InferenceTracker;
-use crate::core::prompt::PromptBuilder;
+use crate::core::device::device_label;
+use crate::core::performance::{
+    BaselineKey, InferenceRecord, InferenceTracker, ModelMemoryStats,
+    REGRESSION_MIN_GENERATED_TOKENS,
+};
+use crate::core::prompt::{
+    PromptBuilder, PromptContext, is_coder_model, render_fim_prompt, render_system_prompt,
+};
+use crate::core::rayon_pool::inference_pool;
 use crate::core::state::SharedState;
 use crate::core::token_output_stream::TokenOutputStream;
 use crate::core::tokenizer::{extract_bos_token_str, extract_eos_ids};
-use crate::core::types::{ChatMessage, GenerateRequest};
+use crate::core::types::{ChatMessage, FimConfig, GenerateRequest};
 
 use crate::{log_infer, log_template_error};
+use std::collections::VecDeque;
 use std::sync::atomic::Ordering;
 use tracing_subscriber::prelude::*;
 // Мультимодальные вложения отключены
 
-use crate::generate::grammar::GrammarSampler; // Import
+use crate::generate::grammar::{GrammarSampler, OutputFormat, StructuredOutputResult};
+use crate::generate::stop_sequence::{StopCheck, StopSequenceBuffer};
 
 pub async fn generate_stream_cmd(
     app: tauri::AppHandle,
@@ -48,10 +57,20 @@ pub fn generate_stream_impl(
     generate_stream_with_backend(state, req, backend)
 }
 
+/// Генерация в `INFERENCE_POOL`: число потоков и их приоритет из настроек
+/// действуют на CPU-операции модели.
 pub fn generate_stream_with_backend(
     state: SharedState,
     req: GenerateRequest,
     backend: Box<dyn EmissionBackend>,
+) -> Result<(), String> {
+    inference_pool().install(move || run_generation(state, req, backend))
+}
+
+fn run_generation(
+    state: SharedState,
+    req: GenerateRequest,
+    backend: Box<dyn EmissionBackend>,
 ) -> Result<(), String> {
     let _trace_guard = if req.tracing.unwrap_or(false) {
         let (chrome_layer, guard) = tracing_chrome::ChromeLayerBuilder::new().build();
@@ -148,6 +167,22 @@ pub fn generate_stream_with_backend(
         }
     }
 
+    // Подстановка переменных шаблона в системные сообщения
+    if !req.disable_template_substitution
+        && let Some(ref mut m) = msgs
+    {
+        let model_name = guard
+            .model_path
+            .as_deref()
+            .and_then(|p| std::path::Path::new(p).file_stem())
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let context = PromptContext::now(model_name, None);
+        for msg in m.iter_mut().filter(|m| m.role == "system") {
+            msg.content = render_system_prompt(&msg.content, &context);
+        }
+    }
+
     // Determine limit for prompt: context_length - reservation
     // This ensures we always have space for generation.
     let reserve_default = 512;
@@ -162,8 +197,32 @@ pub fn generate_stream_with_backend(
         .saturating_sub(generation_reserve)
         .max(1);
 
+    // FIM для Coder-моделей: сырой промпт без чат-шаблона
+    let loaded_model_id = guard
+        .model_path
+        .as_deref()
+        .or(guard.hub_repo_id.as_deref())
+        .unwrap_or_default()
+        .to_string();
+    let supports_fim =
+        is_coder_model(&loaded_model_id) && tos.tokenizer().token_to_id("<|fim_prefix|>").is_some();
+    let fim_prompt = match req.fim_mode.as_ref() {
+        Some(fim) if supports_fim => Some(render_fim_prompt(&fit_fim_prefix(
+            tos.tokenizer(),
+            fim,
+            prompt_limit,
+        )?)),
+        Some(_) => {
+            log_infer!("fim_mode ignored: {} is not a Coder model", loaded_model_id);
+            None
+        }
+        None => None,
+    };
+
     // Ollama-style "smart" truncation via ctx::smart_truncate
-    let prompt = if let Some(messages) = msgs {
+    let mut prompt = if let Some(fim_prompt) = fim_prompt {
+        fim_prompt
+    } else if let Some(messages) = msgs {
         use crate::generate::ctx::smart_truncate;
         smart_truncate(
             tos.tokenizer(),
@@ -176,8 +235,15 @@ pub fn generate_stream_with_backend(
         prompt_str
     };
 
-    // Detect implicit thinking: if prompt ends with <think>, start parser in thinking mode
-    let starts_in_thinking = prompt.trim_end().ends_with("<think>");
+    let (starts_in_thinking, thinking_budget) = apply_thinking_controls(
+        &mut prompt,
+        guard.chat_template.as_deref(),
+        req.suppress_thinking,
+        req.thinking_budget_tokens,
+    );
+    let mut thinking_budget = thinking_budget
+        .map(|limit| ThinkingBudget::new(limit, tos.tokenizer()))
+        .transpose()?;
 
     let tokens = tos
         .tokenizer()
@@ -378,11 +444,15 @@ pub fn generate_stream_with_backend(
             // For MVP: if tool_choice is Function { name }, we still use all tools but logic might differ.
             // However, the prompt might need adjustment for "required" or "function".
             log_infer!("tool calling enabled with {} tools", tools.len());
-            ToolCallParser::with_json_tag(tools.clone())
+            ToolCallParser::for_template(tools.clone(), guard.chat_template.as_deref())
         })
     } else {
         None
     };
+    // `<|python_tag|>` и `<|eom_id|>` — специальные токены, без этого парсер их не увидит
+    if let Some(tcp) = tool_call_parser.as_ref() {
+        tos.preserve_special_tokens(tcp.special_tokens());
+    }
 
     // Инициализация grammar sampler
     let mut grammar_sampler = if req
@@ -395,8 +465,24 @@ pub fn generate_stream_with_backend(
     } else {
         None
     };
+    // Полный ответ нужен только для проверки по JSON Schema
+    let output_schema = match req.format.as_ref() {
+        Some(format @ OutputFormat::JsonSchema(_)) => Some(format.clone()),
+        _ => None,
+    };
+    let mut structured_output = String::new();
+    // Удерживает возможное начало пользовательской stop sequence до подтверждения
+    let mut stop_buffer = StopSequenceBuffer::new(req.stop_sequences.as_deref().unwrap_or(&[]));
+    let mut stopped_by_sequence = false;
 
-    if let Some(t) = tos.next_token(next_token).map_err(|e| e.to_string())? {
+    if let Some(raw) = tos.next_token(next_token).map_err(|e| e.to_string())? {
+        let t = match stop_buffer.push(&raw) {
+            StopCheck::Continue(text) => text,
+            StopCheck::Stopped(text) => {
+                stopped_by_sequence = true;
+                text
+            }
+        };
         // Update grammar sampler for the first token
         if let Some(sampler) = grammar_sampler.as_mut() {
             sampler.update(&t);
@@ -410,6 +496,9 @@ pub fn generate_stream_with_backend(
                 emitter.emit_tool_call(&call);
             }
         }
+        if output_schema.is_some() {
+            structured_output.push_str(&chunk.content);
+        }
         emitter.emit_message(chunk);
     }
 
@@ -420,11 +509,18 @@ pub fn generate_stream_with_backend(
     let eos_token = stop_ids[0];
 
     let mut all_tokens: Vec<u32> = vec![next_token];
+    let mut forced_tokens: VecDeque<u32> = VecDeque::new();
     let mut stop_text_buf = String::new();
+    let mut cancelled = false;
     for index in 0..to_sample_soft_cap {
         let _span = tracing::info_span!("decode", index).entered();
-        if CANCEL_GENERATION.load(Ordering::SeqCst) {
+        if CANCEL_GENERATION.load(Ordering::SeqCst) || emitter.is_cancelled() {
             log_infer!("cancelled by user");
+            cancelled = true;
+            break;
+        }
+        if stopped_by_sequence {
+            log_infer!("stop sequence detected");
             break;
         }
         let input = Tensor::new(&[next_token], &guard.device)
@@ -475,12 +571,24 @@ pub fn generate_stream_with_backend(
             }
         }
         let logits = minp.apply(&logits)?;
-        next_token = logits_processor
-            .sample(&logits)
-            .map_err(|e| e.to_string())?;
+        next_token = match forced_tokens.pop_front() {
+            Some(token) => token,
+            None => logits_processor
+                .sample(&logits)
+                .map_err(|e| e.to_string())?,
+        };
         all_tokens.push(next_token);
         inference_tracker.increment_generated_tokens();
 
+        if let Some(budget) = thinking_budget.as_mut()
+            && thinking_parser.is_in_thinking_mode()
+            && budget.spend()
+        {
+            log_infer!("thinking budget of {} tokens reached", budget.limit);
+            forced_tokens.extend(budget.close_ids.iter().copied());
+            thinking_budget = None;
+        }
+
         if all_tokens.len() < 20 {
             let text = tos
                 .tokenizer()
@@ -495,10 +603,25 @@ pub fn generate_stream_with_backend(
         }
 
         if next_token == eos_token || stop_ids.contains(&next_token) {
+            // `<|eot_id|>` тоже закрывает вызов после `<|python_tag|>`
+            if let Some(ref mut tcp) = tool_call_parser
+                && let Some(tag) = tos.preserved_token(next_token)
+            {
+                for call in tcp.add(tag).calls {
+                    emitter.emit_tool_call(&call);
+                }
+            }
             break;
         }
 
-        if let Some(t) = tos.next_token(next_token).map_err(|e| e.to_string())? {
+        if let Some(raw) = tos.next_token(next_token).map_err(|e| e.to_string())? {
+            let t = match stop_buffer.push(&raw) {
+                StopCheck::Continue(text) => text,
+                StopCheck::Stopped(text) => {
+                    stopped_by_sequence = true;
+                    text
+                }
+            };
             // Update grammar sampler
             if let Some(sampler) = grammar_sampler.as_mut() {
                 sampler.update(&t);
@@ -506,6 +629,9 @@ pub fn generate_stream_with_backend(
                     log_infer!("grammar: JSON complete, stopping generation");
                     // Emit remaining buffer if any
                     let chunk = thinking_parser.process_token(&t);
+                    if output_schema.is_some() {
+                        structured_output.push_str(&chunk.content);
+                    }
                     emitter.emit_message(chunk);
                     // Also finalize thinking parser if needed
                     break;
@@ -520,8 +646,16 @@ pub fn generate_stream_with_backend(
                     emitter.emit_tool_call(&call);
                 }
             }
+            if output_schema.is_some() {
+                structured_output.push_str(&chunk.content);
+            }
             emitter.emit_message(chunk);
-            stop_text_buf.push_str(&t);
+            if stopped_by_sequence {
+                log_infer!("stop sequence detected");
+                break;
+            }
+
+            stop_text_buf.push_str(&raw);
             if stop_text_buf.len() > 128 {
                 let mut cut = stop_text_buf.len() - 128;
                 while cut < stop_text_buf.len() && !stop_text_buf.is_char_boundary(cut) {
@@ -532,16 +666,6 @@ pub fn generate_stream_with_backend(
                 }
             }
 
-            // Check user-provided stop sequences first
-            if req
-                .stop_sequences
-                .as_ref()
-                .is_some_and(|seqs| seqs.iter().any(|s| stop_text_buf.contains(s)))
-            {
-                log_infer!("stop sequence detected");
-                break;
-            }
-
             // Fallback to hardcoded EOS sequences
             if stop_text_buf.contains("<end_of_turn>")
                 || stop_text_buf.contains("<|end_of_turn|>")
@@ -553,13 +677,37 @@ pub fn generate_stream_with_backend(
         }
     }
 
-    if let Some(rest) = tos.decode_rest().map_err(|e| e.to_string())? {
-        let chunk = thinking_parser.process_token(&rest);
+    // Хвост, удержанный stop-буфером, и недекодированный остаток отдаём,
+    // только если генерация не была остановлена stop sequence
+    if !stopped_by_sequence {
+        let mut tail = stop_buffer.flush();
+        if let Some(rest) = tos.decode_rest().map_err(|e| e.to_string())? {
+            tail.push_str(&rest);
+        }
+        // Остаток тоже может завершить stop sequence
+        let tail = match stop_buffer.push(&tail) {
+            StopCheck::Continue(text) => text + &stop_buffer.flush(),
+            StopCheck::Stopped(text) => text,
+        };
+        let chunk = thinking_parser.process_token(&tail);
+        if output_schema.is_some() {
+            structured_output.push_str(&chunk.content);
+        }
         emitter.emit_message(chunk);
     }
     // Flush any remaining buffered partial tags
     let final_chunk = thinking_parser.flush();
+    if output_schema.is_some() {
+        structured_output.push_str(&final_chunk.content);
+    }
     emitter.emit_message(final_chunk);
+    if let Some(format) = output_schema.as_ref() {
+        let result = StructuredOutputResult::check(structured_output, format);
+        if let Some(violation) = result.schema_violation {
+            log_infer!("structured output violates schema: {}", violation);
+            emitter.emit_schema_violation(violation);
+        }
+    }
     emitter.finalize();
 
     // ============ Prefix Cache: сохраняем позицию ============
@@ -582,6 +730,8 @@ pub fn generate_stream_with_backend(
     {
         entry.model.clear_kv_cache();
     }
+    // Без prefix cache KV-кэш очищается после каждого запроса
+    let kv_cache_retained = guard.prefix_cache.enabled();
 
     // Финализируем метрики inference - используем существующий runtime если доступен
     let inference_metrics = match tokio::runtime::Handle::try_current() {
@@ -603,12 +753,183 @@ pub fn generate_stream_with_backend(
         inference_metrics.memory_usage_mb
     );
 
+    // Скользящее окно для панели производительности
+    let context_used_percent = if guard.context_length > 0 {
+        ((inference_metrics.prompt_tokens + inference_metrics.generated_tokens) as f32
+            / guard.context_length as f32
+            * 100.0)
+            .min(100.0)
+    } else {
+        0.0
+    };
+    let record = InferenceRecord {
+        model_id: guard.scheduler.get_model_id(),
+        metrics: inference_metrics.clone(),
+        context_used_percent,
+    };
+    // Кэш не может быть заполнен больше, чем на размер контекста
+    let kv_cache_used_tokens = if kv_cache_retained {
+        (inference_metrics.prompt_tokens + inference_metrics.generated_tokens)
+            .min(guard.context_length)
+    } else {
+        0
+    };
+    let memory_stats = record.model_id.clone().map(|model_id| ModelMemoryStats {
+        model_id,
+        kv_cache_used_tokens,
+        kv_cache_total_tokens: guard.context_length,
+        ctx_size: guard.context_length,
+    });
+    // Отменённые и слишком короткие генерации искажают базовую скорость
+    let comparable =
+        !cancelled && inference_metrics.generated_tokens >= REGRESSION_MIN_GENERATED_TOKENS;
+    let baseline_key = record
+        .model_id
+        .clone()
+        .filter(|_| comparable)
+        .map(|model_id| BaselineKey {
+            model_id,
+            device: device_label(&guard.device).to_string(),
+            ctx_size: guard.context_length,
+        });
+    let tokens_per_second = inference_metrics.tokens_per_second;
+    let monitor = guard.performance_monitor.clone();
+    let record_all = async {
+        monitor.record_inference(record).await;
+        if let Some(stats) = memory_stats.clone() {
+            monitor.record_memory_stats(stats).await;
+        }
+        match baseline_key {
+            Some(key) => monitor.check_speed_regression(key, tokens_per_second).await,
+            None => None,
+        }
+    };
+    let regression = match tokio::runtime::Handle::try_current() {
+        Ok(handle) => handle.block_on(record_all),
+        Err(_) => tokio::runtime::Runtime::new()
+            .ok()
+            .and_then(|rt| rt.block_on(record_all)),
+    };
+    if let Some(stats) = memory_stats {
+        emitter.emit_memory_stats(stats);
+    }
+    if let Some(regression) = regression {
+        log_infer!(
+            "Генерация медленнее обычного на {:.0}%: {:.2} tok/s вместо {:.2}",
+            regression.degradation_percent,
+            regression.actual_tps,
+            regression.expected_tps
+        );
+        emitter.emit_performance_regression(regression);
+    }
+
     // Отправляем метрики на фронтенд
     emitter.emit_metrics(inference_metrics);
 
     Ok(())
 }
 
+/// FIM-промпт не проходит через smart_truncate: если не влезает в контекст,
+/// отрезаем начало префикса — код рядом с курсором важнее.
+fn fit_fim_prefix(
+    tokenizer: &tokenizers::Tokenizer,
+    fim: &FimConfig,
+    limit: usize,
+) -> Result<FimConfig, String> {
+    let count = |text: &str| -> Result<usize, String> {
+        tokenizer
+            .encode(text, false)
+            .map(|enc| enc.get_ids().len())
+            .map_err(|e| e.to_string())
+    };
+    // Три служебных FIM-токена плюс суффикс
+    let prefix_limit = limit.saturating_sub(count(&fim.suffix)? + 3);
+    let prefix = tokenizer
+        .encode(fim.prefix.as_str(), false)
+        .map_err(|e| e.to_string())?;
+    let offsets = prefix.get_offsets();
+    if offsets.len() <= prefix_limit {
+        return Ok(fim.clone());
+    }
+    let mut start = offsets
+        .get(offsets.len() - prefix_limit)
+        .map_or(fim.prefix.len(), |&(start, _)| start);
+    while !fim.prefix.is_char_boundary(start) {
+        start += 1;
+    }
+    log_infer!(
+        "fim prefix truncated: kept {} of {} tokens",
+        prefix_limit,
+        offsets.len()
+    );
+    Ok(FimConfig {
+        prefix: fim.prefix[start..].to_string(),
+        suffix: fim.suffix.clone(),
+    })
+}
+
+/// Applies `suppress_thinking` / `thinking_budget_tokens` to the rendered prompt.
+/// Returns whether the parser starts in thinking mode and the budget to enforce.
+/// An open `<think>` is only added for templates that know the tag; other models
+/// would just echo it back as text.
+fn apply_thinking_controls(
+    prompt: &mut String,
+    chat_template: Option<&str>,
+    suppress_thinking: bool,
+    budget_tokens: Option<u32>,
+) -> (bool, Option<u32>) {
+    // Implicit thinking: the template already opened the block
+    let starts_in_thinking = prompt.trim_end().ends_with("<think>");
+    if suppress_thinking {
+        // Пустой блок размышлений: модель сразу переходит к ответу
+        if starts_in_thinking {
+            prompt.push_str("\n</think>\n\n");
+        }
+        return (false, None);
+    }
+    let Some(budget) = budget_tokens else {
+        return (starts_in_thinking, None);
+    };
+    if starts_in_thinking {
+        return (true, Some(budget));
+    }
+    if chat_template.is_some_and(|t| t.contains("<think>")) {
+        prompt.push_str("<think>\n");
+        return (true, Some(budget));
+    }
+    log_infer!("thinking_budget_tokens ignored: chat template has no <think> block");
+    (false, None)
+}
+
+/// Counts thinking tokens and holds the ids that close the block once the
+/// budget runs out.
+struct ThinkingBudget {
+    limit: u32,
+    spent: u32,
+    close_ids: Vec<u32>,
+}
+
+impl ThinkingBudget {
+    fn new(limit: u32, tokenizer: &tokenizers::Tokenizer) -> Result<Self, String> {
+        let close_ids = tokenizer
+            .encode("\n</think>\n\n", false)
+            .map_err(|e| e.to_string())?
+            .get_ids()
+            .to_vec();
+        Ok(Self {
+            limit,
+            spent: 0,
+            close_ids,
+        })
+    }
+
+    /// Records one thinking token; true once the budget is exhausted.
+    fn spend(&mut self) -> bool {
+        self.spent += 1;
+        self.spent >= self.limit
+    }
+}
+
 /// Build a prompt using the prompt builder with chat template support
 pub fn build_prompt_with_template_bos(
     chat_template: &Option<String>,
@@ -648,3 +969,94 @@ pub fn build_prompt_with_template(
 ) -> Result<String, String> {
     build_prompt_with_template_bos(chat_template, messages, None)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THINK_TEMPLATE: &str = "{% if enable_thinking %}<think>\n{% endif %}";
+
+    fn stub_tokenizer() -> tokenizers::Tokenizer {
+        let json = serde_json::json!({
+            "version": "1.0",
+            "truncation": null,
+            "padding": null,
+            "added_tokens": [],
+            "normalizer": null,
+            "pre_tokenizer": { "type": "WhitespaceSplit" },
+            "post_processor": null,
+            "decoder": null,
+            "model": {
+                "type": "WordLevel",
+                "vocab": { "<unk>": 0, "<think>": 1, "</think>": 2, "hello": 3 },
+                "unk_token": "<unk>"
+            }
+        });
+        tokenizers::Tokenizer::from_bytes(json.to_string().as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn budget_opens_think_block_for_capable_templates() {
+        let mut prompt = "<|im_start|>assistant\n".to_string();
+        let state = apply_thinking_controls(&mut prompt, Some(THINK_TEMPLATE), false, Some(8));
+        assert_eq!(state, (true, Some(8)));
+        assert!(prompt.ends_with("<think>\n"));
+    }
+
+    #[test]
+    fn budget_leaves_prompt_alone_without_think_support() {
+        let mut prompt = "[INST] hi [/INST]".to_string();
+        let state = apply_thinking_controls(&mut prompt, Some("[INST]"), false, Some(8));
+        assert_eq!(state, (false, None));
+        assert_eq!(prompt, "[INST] hi [/INST]");
+
+        let mut prompt = "hi".to_string();
+        assert_eq!(
+            apply_thinking_controls(&mut prompt, None, false, Some(8)),
+            (false, None)
+        );
+        assert_eq!(prompt, "hi");
+    }
+
+    #[test]
+    fn budget_keeps_implicit_think_block() {
+        let mut prompt = "assistant\n<think>\n".to_string();
+        let state = apply_thinking_controls(&mut prompt, None, false, Some(4));
+        assert_eq!(state, (true, Some(4)));
+        assert_eq!(prompt, "assistant\n<think>\n");
+    }
+
+    #[test]
+    fn suppress_closes_implicit_think_block() {
+        let mut prompt = "assistant\n<think>".to_string();
+        let state = apply_thinking_controls(&mut prompt, Some(THINK_TEMPLATE), true, Some(4));
+        assert_eq!(state, (false, None));
+        assert!(prompt.ends_with("<think>\n</think>\n\n"));
+    }
+
+    #[test]
+    fn fim_prefix_is_trimmed_from_the_left() {
+        let tokenizer = stub_tokenizer();
+        let fim = FimConfig {
+            prefix: "hello <think> </think> hello".into(),
+            suffix: "hello".into(),
+        };
+        // 1 token of suffix + 3 FIM tokens leaves room for 2 prefix tokens
+        let fitted = fit_fim_prefix(&tokenizer, &fim, 6).unwrap();
+        assert_eq!(fitted.prefix, "</think> hello");
+        assert_eq!(fitted.suffix, "hello");
+
+        assert_eq!(fit_fim_prefix(&tokenizer, &fim, 64).unwrap(), fim);
+    }
+
+    #[test]
+    fn exhausted_budget_forces_think_close() {
+        let tokenizer = stub_tokenizer();
+        let mut budget = ThinkingBudget::new(3, &tokenizer).unwrap();
+        assert_eq!(budget.close_ids, vec![2]);
+
+        assert!(!budget.spend());
+        assert!(!budget.spend());
+        assert!(budget.spend());
+    }
+}
@@ -336,9 +336,13 @@ impl Default for ThinkingParser {
 
 /// Find longest overlap between suffix of s and prefix of delim.
 /// Returns the overlap length, or None if no overlap.
+/// Only char boundaries of `delim` are tried, so multi-byte custom tags
+/// (e.g. `◁think▷`) are safe.
 fn overlap(s: &str, delim: &str) -> Option<usize> {
     let max = std::cmp::min(delim.len(), s.len());
-    (1..=max).rev().find(|&i| s.ends_with(&delim[..i]))
+    (1..=max)
+        .rev()
+        .find(|&i| delim.is_char_boundary(i) && s.ends_with(&delim[..i]))
 }
 
 /// Count trailing whitespace bytes in a string.
@@ -620,4 +624,85 @@ mod tests {
         assert_eq!(trailing_whitespace_len("  "), 2);
         assert_eq!(trailing_whitespace_len(""), 0);
     }
+
+    /// Feeds `text` split at every char boundary, collecting the output.
+    fn feed_char_by_char(parser: &mut ThinkingParser, text: &str) -> ParsedChunk {
+        let mut out = ParsedChunk::default();
+        for ch in text.chars() {
+            let chunk = parser.process_token(&ch.to_string());
+            out.thinking.push_str(&chunk.thinking);
+            out.content.push_str(&chunk.content);
+        }
+        let rest = parser.flush();
+        out.thinking.push_str(&rest.thinking);
+        out.content.push_str(&rest.content);
+        out
+    }
+
+    #[test]
+    fn custom_tags_split_across_chunks() {
+        let mut parser = ThinkingParser::with_tags("<reasoning>", "</reasoning>");
+
+        let r1 = parser.process_token("<reas");
+        assert!(r1.is_empty());
+        assert_eq!(parser.state(), ThinkingState::LookingForOpening);
+
+        let r2 = parser.process_token("oning>plan");
+        assert_eq!(r2.thinking, "plan");
+        assert!(parser.is_in_thinking_mode());
+
+        let r3 = parser.process_token("</reaso");
+        assert!(r3.is_empty());
+
+        let r4 = parser.process_token("ning>done");
+        assert_eq!(r4.content, "done");
+        assert_eq!(parser.state(), ThinkingState::CollectingContent);
+    }
+
+    #[test]
+    fn multibyte_custom_tags_char_by_char() {
+        let mut parser = ThinkingParser::with_tags("◁think▷", "◁/think▷");
+
+        let out = feed_char_by_char(&mut parser, "◁think▷размышление◁/think▷ответ");
+        assert_eq!(out.thinking, "размышление");
+        assert_eq!(out.content, "ответ");
+    }
+
+    #[test]
+    fn default_tags_char_by_char() {
+        let mut parser = ThinkingParser::new();
+
+        let out = feed_char_by_char(&mut parser, "<think>a < b</think>c");
+        assert_eq!(out.thinking, "a < b");
+        assert_eq!(out.content, "c");
+    }
+
+    #[test]
+    fn partial_opening_tag_mismatch_flushes_as_content() {
+        let mut parser = ThinkingParser::with_tags("<reasoning>", "</reasoning>");
+
+        let r1 = parser.process_token("<rea");
+        assert!(r1.is_empty());
+
+        let r2 = parser.process_token("d this>");
+        assert_eq!(r2.content, "<read this>");
+        assert_eq!(r2.thinking, "");
+        assert_eq!(parser.state(), ThinkingState::CollectingContent);
+    }
+
+    #[test]
+    fn partial_opening_tag_flushed_at_end_of_stream() {
+        let mut parser = ThinkingParser::new();
+
+        assert!(parser.process_token("<thi").is_empty());
+        let rest = parser.flush();
+        assert_eq!(rest.content, "<thi");
+        assert_eq!(rest.thinking, "");
+    }
+
+    #[test]
+    fn overlap_multibyte_delimiter() {
+        assert_eq!(overlap("abc◁/th", "◁/think▷"), Some("◁/th".len()));
+        assert_eq!(overlap("abc", "◁/think▷"), None);
+    }
 }
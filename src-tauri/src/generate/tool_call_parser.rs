@@ -8,6 +8,8 @@
 //! - Parses function name and JSON arguments in streaming fashion
 //! - Buffers partial JSON until complete object is found
 //! - For `{` or `[` tags, only parses if first non-whitespace matches
+//! - For custom tags, a section ends at its closing tag (`</tool_call>`,
+//!   `<|tool_call_end|>`, `<|eom_id|>`); text after it is returned as content
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -41,6 +43,57 @@ pub struct ToolCallFunction {
     pub index: usize,
 }
 
+/// Opening tag of Qwen3-Coder / DeepSeek style tool calls:
+/// `<|tool_call_begin|>function<|tool_sep|>name\n{...}<|tool_call_end|>`.
+pub const TOOL_CALL_BEGIN_TAG: &str = "<|tool_call_begin|>";
+
+/// Closing tag of Qwen3-Coder / DeepSeek style tool calls.
+pub const TOOL_CALL_END_TAG: &str = "<|tool_call_end|>";
+
+/// Opening tag of Hermes / Qwen2.5 style tool calls: `<tool_call>{...}</tool_call>`.
+pub const HERMES_TOOL_CALL_TAG: &str = "<tool_call>";
+
+/// Closing tag of Hermes / Qwen2.5 style tool calls.
+pub const HERMES_TOOL_CALL_END_TAG: &str = "</tool_call>";
+
+/// Llama 3.1+ tag preceding tool calls (`<|python_tag|>...<|eom_id|>`).
+pub const PYTHON_TAG: &str = "<|python_tag|>";
+
+/// Tokens ending a Llama 3.1+ tool call message.
+const PYTHON_TAG_END_TAGS: &[&str] = &["<|eom_id|>", "<|eot_id|>"];
+
+/// Closing tags that end a tool call section started by `tag`.
+/// JSON tags (`{`, `[`) end with the balanced object instead.
+fn end_tags_for(tag: &str) -> &'static [&'static str] {
+    match tag {
+        TOOL_CALL_BEGIN_TAG => &[TOOL_CALL_END_TAG],
+        HERMES_TOOL_CALL_TAG => &[HERMES_TOOL_CALL_END_TAG],
+        PYTHON_TAG => PYTHON_TAG_END_TAGS,
+        _ => &[],
+    }
+}
+
+/// Serialization used by Llama 3.1+ after `<|python_tag|>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LlamaToolCallFormat {
+    /// `{"name": "...", "parameters": {...}}`
+    Json,
+    /// Built-in tool syntax: `brave_search.call(query="...")`
+    Python,
+}
+
+impl LlamaToolCallFormat {
+    /// Detect format from the text directly following the tool name.
+    fn detect(after_name: &str) -> Self {
+        let rest = after_name.trim_start();
+        if rest.starts_with(".call(") || rest.starts_with('(') {
+            Self::Python
+        } else {
+            Self::Json
+        }
+    }
+}
+
 /// Parser state.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ToolsState {
@@ -66,6 +119,10 @@ pub struct ToolCallParser {
     state: ToolsState,
     buffer: Vec<u8>,
     call_count: usize,
+    /// Calls parsed since the current opening tag
+    section_calls: usize,
+    /// Switch to the JSON tag `{` if the output starts with a bare object
+    json_fallback: bool,
 }
 
 impl ToolCallParser {
@@ -77,6 +134,8 @@ impl ToolCallParser {
             state: ToolsState::LookingForTag,
             buffer: Vec::new(),
             call_count: 0,
+            section_calls: 0,
+            json_fallback: false,
         }
     }
 
@@ -85,6 +144,24 @@ impl ToolCallParser {
         Self::new(tools, "{")
     }
 
+    /// Create parser with the tag matching the model's chat template.
+    ///
+    /// Falls back to the JSON object tag `{` when the template does not use
+    /// a dedicated tool call marker.
+    pub fn for_template(tools: Vec<Tool>, chat_template: Option<&str>) -> Self {
+        match chat_template {
+            Some(t) if t.contains(TOOL_CALL_BEGIN_TAG) => Self::new(tools, TOOL_CALL_BEGIN_TAG),
+            // Llama 3.1+ prefixes only built-in tools with `<|python_tag|>`;
+            // custom tools are answered with bare `{"name": ..., "parameters": ...}`
+            Some(t) if t.contains(PYTHON_TAG) => Self {
+                json_fallback: true,
+                ..Self::new(tools, PYTHON_TAG)
+            },
+            Some(t) if t.contains(HERMES_TOOL_CALL_TAG) => Self::new(tools, HERMES_TOOL_CALL_TAG),
+            _ => Self::with_json_tag(tools),
+        }
+    }
+
     /// Process incoming string and return parsed tool calls and remaining content.
     pub fn add(&mut self, s: &str) -> ParseResult {
         if self.state == ToolsState::Done {
@@ -99,6 +176,21 @@ impl ToolCallParser {
         let mut result = ParseResult::default();
 
         if self.state == ToolsState::LookingForTag {
+            if self.json_fallback {
+                let first = String::from_utf8_lossy(&self.buffer)
+                    .trim_start()
+                    .chars()
+                    .next();
+                match first {
+                    Some('{') => {
+                        self.tag = "{".to_string();
+                        self.json_fallback = false;
+                    }
+                    Some(_) => self.json_fallback = false,
+                    None => {}
+                }
+            }
+
             let (idx, found) = self.find_tag();
 
             if idx == -1 {
@@ -131,6 +223,26 @@ impl ToolCallParser {
         // Parse tool calls
         while let Some(call) = self.parse_tool_call() {
             result.calls.push(call);
+            self.section_calls += 1;
+        }
+
+        // Tag-based formats: the section ends at the closing tag, and whatever
+        // follows it is regular content (or another tool call section).
+        if let Some((pos, len)) = self.find_end_tag() {
+            if self.section_calls == 0 {
+                // Nothing recognised inside the section: keep it as plain text
+                result
+                    .content
+                    .push_str(&String::from_utf8_lossy(&self.buffer[..pos + len]));
+            }
+            let rest = String::from_utf8_lossy(&self.buffer[pos + len..]).to_string();
+            self.buffer.clear();
+            self.section_calls = 0;
+            self.state = ToolsState::LookingForTag;
+            let tail = self.add(&rest);
+            result.calls.extend(tail.calls);
+            result.content.push_str(&tail.content);
+            return result;
         }
 
         // Check if done
@@ -170,11 +282,36 @@ impl ToolCallParser {
         (-1, false)
     }
 
+    /// Position and length of the earliest closing tag for the current tag.
+    fn find_end_tag(&self) -> Option<(usize, usize)> {
+        end_tags_for(&self.tag)
+            .iter()
+            .filter_map(|end| {
+                self.buffer
+                    .windows(end.len())
+                    .position(|w| w == end.as_bytes())
+                    .map(|pos| (pos, end.len()))
+            })
+            .min_by_key(|(pos, _)| *pos)
+    }
+
     /// Try to parse a complete tool call from buffer.
     fn parse_tool_call(&mut self) -> Option<ToolCall> {
         let (tool, end) = self.find_tool()?;
 
-        let (args, args_end) = self.find_arguments(tool)?;
+        let after_name = String::from_utf8_lossy(&self.buffer[end..]);
+        let format = if self.tag == PYTHON_TAG {
+            LlamaToolCallFormat::detect(&after_name)
+        } else {
+            LlamaToolCallFormat::Json
+        };
+        let (args, args_end) = match format {
+            LlamaToolCallFormat::Python => {
+                let (args, consumed) = parse_python_call_arguments(&after_name)?;
+                (args, end + consumed)
+            }
+            LlamaToolCallFormat::Json => self.find_arguments(tool)?,
+        };
 
         let final_end = std::cmp::max(end, args_end);
 
@@ -237,7 +374,7 @@ impl ToolCallParser {
     }
 
     /// Find JSON arguments object in buffer.
-    fn find_arguments(&self, _tool: &Tool) -> Option<(HashMap<String, serde_json::Value>, usize)> {
+    fn find_arguments(&self, tool: &Tool) -> Option<(HashMap<String, serde_json::Value>, usize)> {
         if self.buffer.is_empty() {
             return None;
         }
@@ -283,13 +420,15 @@ impl ToolCallParser {
                     // Try to parse as JSON
                     if let Ok(data) = serde_json::from_str::<serde_json::Value>(object_str) {
                         // Extract arguments from various formats
-                        if let Some(args) = self.extract_arguments(&data) {
+                        if let Some(mut args) = self.extract_arguments(&data) {
+                            coerce_arguments(&mut args, tool.function.parameters.as_ref());
                             return Some((args, i + 1));
                         }
                         // If no structured format, use the whole object
                         if let Some(obj) = data.as_object() {
-                            let args: HashMap<String, serde_json::Value> =
+                            let mut args: HashMap<String, serde_json::Value> =
                                 obj.clone().into_iter().collect();
+                            coerce_arguments(&mut args, tool.function.parameters.as_ref());
                             return Some((args, i + 1));
                         }
                     }
@@ -370,12 +509,221 @@ impl ToolCallParser {
         String::from_utf8_lossy(&self.buffer).to_string()
     }
 
+    /// Special tokens that must reach the parser as text.
+    ///
+    /// Tokenizers mark `<|python_tag|>` and `<|eom_id|>` as special, so streaming
+    /// decoding drops them unless they are preserved explicitly.
+    pub fn special_tokens(&self) -> &'static [&'static str] {
+        if self.tag == PYTHON_TAG {
+            &[PYTHON_TAG, "<|eom_id|>", "<|eot_id|>"]
+        } else {
+            &[]
+        }
+    }
+
     /// Check if parser is in done state.
     pub fn is_finished(&self) -> bool {
         self.state == ToolsState::Done
     }
 }
 
+/// Parse `.call(key="value", n=1)` / `(key="value")` following a tool name.
+///
+/// Returns the arguments and the number of bytes consumed, or `None` while
+/// the closing parenthesis has not arrived yet.
+fn parse_python_call_arguments(s: &str) -> Option<(HashMap<String, serde_json::Value>, usize)> {
+    let leading_ws = s.len() - s.trim_start().len();
+    let rest = &s[leading_ws..];
+    let open = leading_ws + if rest.starts_with(".call(") { 5 } else { 0 };
+
+    // Find matching ')' outside of quotes
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    let mut depth = 0i32;
+    let mut close = None;
+    for (i, c) in s[open..].char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match (quote, c) {
+            (Some(_), '\\') => escaped = true,
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(' | '[' | '{') => depth += 1,
+            (None, ')' | ']' | '}') => {
+                depth -= 1;
+                if depth == 0 {
+                    close = Some(open + i);
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    let close = close?;
+    let inner = &s[open + 1..close];
+
+    let mut args = HashMap::new();
+    for part in split_top_level_commas(inner) {
+        let Some((key, value)) = part.split_once('=') else {
+            continue;
+        };
+        args.insert(key.trim().to_string(), python_literal_to_json(value.trim()));
+    }
+    Some((args, close + 1))
+}
+
+/// Split on commas that are not inside quotes or brackets.
+fn split_top_level_commas(s: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    let mut depth = 0i32;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match (quote, c) {
+            (Some(_), '\\') => escaped = true,
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(' | '[' | '{') => depth += 1,
+            (None, ')' | ']' | '}') => depth -= 1,
+            (None, ',') if depth == 0 => {
+                parts.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if !s[start..].trim().is_empty() {
+        parts.push(&s[start..]);
+    }
+    parts
+}
+
+/// Convert a Python literal (`"x"`, `'x'`, `42`, `True`, `None`, `[1, 2]`) to JSON.
+fn python_literal_to_json(value: &str) -> serde_json::Value {
+    if value.len() >= 2
+        && let (Some(first), Some(last)) = (value.chars().next(), value.chars().last())
+        && first == last
+        && (first == '"' || first == '\'')
+    {
+        return serde_json::Value::String(unescape_python_string(&value[1..value.len() - 1]));
+    }
+    match value {
+        "True" => serde_json::Value::Bool(true),
+        "False" => serde_json::Value::Bool(false),
+        "None" => serde_json::Value::Null,
+        _ => serde_json::from_str(value)
+            .unwrap_or_else(|_| serde_json::Value::String(value.to_string())),
+    }
+}
+
+/// Unescape the body of a Python string literal (`\\n`, `\\'`, `\\xNN`, `\\uNNNN`, ...).
+/// Unknown escapes are kept verbatim, as Python does.
+fn unescape_python_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some('0') => out.push('\0'),
+            Some(q @ ('\\' | '\'' | '"')) => out.push(q),
+            Some(kind @ ('x' | 'u' | 'U')) => {
+                let len = match kind {
+                    'x' => 2,
+                    'u' => 4,
+                    _ => 8,
+                };
+                let hex: String = chars.clone().take(len).collect();
+                let decoded = u32::from_str_radix(&hex, 16)
+                    .ok()
+                    .filter(|_| hex.len() == len)
+                    .and_then(char::from_u32);
+                match decoded {
+                    Some(ch) => {
+                        out.push(ch);
+                        chars.nth(len - 1);
+                    }
+                    None => {
+                        out.push('\\');
+                        out.push(kind);
+                    }
+                }
+            }
+            Some(other) => {
+                out.push('\\');
+                out.push(other);
+            }
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// Coerce string-encoded scalar arguments to the types declared in the tool's
+/// JSON Schema (`"42"` → `42` for `integer`, `"true"` → `true` for `boolean`).
+///
+/// Small models often quote every value; unknown properties and values that
+/// fail to parse are left untouched.
+pub fn coerce_arguments(
+    args: &mut HashMap<String, serde_json::Value>,
+    parameters: Option<&serde_json::Value>,
+) {
+    let Some(properties) = parameters
+        .and_then(|p| p.get("properties"))
+        .and_then(|p| p.as_object())
+    else {
+        return;
+    };
+
+    for (name, value) in args.iter_mut() {
+        let Some(raw) = value.as_str() else {
+            continue;
+        };
+        let Some(ty) = properties
+            .get(name)
+            .and_then(|schema| schema.get("type"))
+            .and_then(|t| t.as_str())
+        else {
+            continue;
+        };
+        let raw = raw.trim();
+        let coerced = match ty {
+            "integer" => raw.parse::<i64>().ok().map(serde_json::Value::from),
+            "number" => raw
+                .parse::<f64>()
+                .ok()
+                .and_then(serde_json::Number::from_f64)
+                .map(serde_json::Value::Number),
+            "boolean" => match raw.to_ascii_lowercase().as_str() {
+                "true" => Some(serde_json::Value::Bool(true)),
+                "false" => Some(serde_json::Value::Bool(false)),
+                _ => None,
+            },
+            "object" | "array" => serde_json::from_str::<serde_json::Value>(raw)
+                .ok()
+                .filter(|v| (ty == "object" && v.is_object()) || (ty == "array" && v.is_array())),
+            _ => None,
+        };
+        if let Some(coerced) = coerced {
+            *value = coerced;
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -486,4 +834,287 @@ mod tests {
         let args = &result.calls[0].function.arguments;
         assert!(args.contains_key("data"));
     }
+
+    #[test]
+    fn test_arguments_coerced_by_schema() {
+        let tools = vec![Tool {
+            function: ToolFunction {
+                name: "set_volume".to_string(),
+                description: None,
+                parameters: Some(serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "level": { "type": "integer" },
+                        "muted": { "type": "boolean" },
+                        "label": { "type": "string" }
+                    }
+                })),
+            },
+        }];
+        let mut parser = ToolCallParser::with_json_tag(tools);
+
+        let result = parser.add(
+            r#"{"name": "set_volume", "arguments": {"level": "42", "muted": "false", "label": "7"}}"#,
+        );
+        assert_eq!(result.calls.len(), 1);
+        let args = &result.calls[0].function.arguments;
+        assert_eq!(args.get("level").unwrap(), &serde_json::json!(42));
+        assert_eq!(args.get("muted").unwrap(), &serde_json::json!(false));
+        assert_eq!(args.get("label").unwrap(), &serde_json::json!("7"));
+    }
+
+    #[test]
+    fn test_tool_call_begin_format_single() {
+        let tools = vec![make_tool("get_weather")];
+        let template = "{{ '<|tool_call_begin|>' }}";
+        let mut parser = ToolCallParser::for_template(tools, Some(template));
+
+        let result = parser.add(
+            "Let me check.<|tool_call_begin|>function<|tool_sep|>get_weather\n```json\n{\"city\": \"Paris\"}\n```<|tool_call_end|>",
+        );
+        assert_eq!(result.content, "Let me check.");
+        assert_eq!(result.calls.len(), 1);
+        assert_eq!(result.calls[0].function.name, "get_weather");
+        assert_eq!(
+            result.calls[0].function.arguments.get("city").unwrap(),
+            &serde_json::json!("Paris")
+        );
+    }
+
+    #[test]
+    fn test_tool_call_begin_format_multiple_streamed() {
+        let tools = vec![make_tool("get_weather"), make_tool("get_time")];
+        let mut parser = ToolCallParser::new(tools, TOOL_CALL_BEGIN_TAG);
+
+        let mut calls = Vec::new();
+        for piece in [
+            "<|tool_call_",
+            "begin|>function<|tool_sep|>get_weather\n{\"city\": ",
+            "\"Oslo\"}<|tool_call_end|><|tool_call_begin|>function<|tool_sep|>get_time\n",
+            "{\"tz\": \"CET\"}<|tool_call_end|>",
+        ] {
+            calls.extend(parser.add(piece).calls);
+        }
+
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].function.name, "get_weather");
+        assert_eq!(calls[1].function.name, "get_time");
+        assert_eq!(calls[1].function.index, 1);
+    }
+
+    #[test]
+    fn test_tool_call_begin_emits_trailing_content() {
+        let tools = vec![make_tool("get_weather")];
+        let mut parser = ToolCallParser::new(tools, TOOL_CALL_BEGIN_TAG);
+
+        let mut content = String::new();
+        let mut calls = Vec::new();
+        for piece in [
+            "<|tool_call_begin|>function<|tool_sep|>get_weather\n{\"city\": \"Rome\"}<|tool_call",
+            "_end|> Checking",
+            " now.",
+        ] {
+            let r = parser.add(piece);
+            calls.extend(r.calls);
+            content.push_str(&r.content);
+        }
+        assert_eq!(calls.len(), 1);
+        assert_eq!(content, " Checking now.");
+    }
+
+    #[test]
+    fn test_hermes_tool_call_tag() {
+        let tools = vec![make_tool("get_weather"), make_tool("get_time")];
+        let template = "{{- '<tool_call>\\n' }}";
+        let mut parser = ToolCallParser::for_template(tools, Some(template));
+
+        let result = parser.add(
+            "I'll look it up.\n<tool_call>\n{\"name\": \"get_weather\", \"arguments\": {\"city\": \"Paris\"}}\n</tool_call>\n<tool_call>\n{\"name\": \"get_time\", \"arguments\": {}}\n</tool_call>\nDone.",
+        );
+        assert_eq!(result.calls.len(), 2);
+        assert_eq!(result.calls[0].function.name, "get_weather");
+        assert_eq!(
+            result.calls[0].function.arguments.get("city").unwrap(),
+            &serde_json::json!("Paris")
+        );
+        assert_eq!(result.calls[1].function.name, "get_time");
+        assert_eq!(result.content, "I'll look it up.\n\nDone.");
+        assert!(!result.content.contains("</tool_call>"));
+    }
+
+    #[test]
+    fn test_unknown_tool_section_kept_as_content() {
+        let tools = vec![make_tool("get_weather")];
+        let mut parser = ToolCallParser::new(tools, HERMES_TOOL_CALL_TAG);
+
+        let raw = "<tool_call>{\"name\": \"rm_rf\", \"arguments\": {}}</tool_call>";
+        let result = parser.add(&format!("{raw} ok"));
+        assert!(result.calls.is_empty());
+        assert_eq!(result.content, format!("{raw} ok"));
+    }
+
+    #[test]
+    fn test_llama_python_tag_json() {
+        let tools = vec![make_tool("get_current_conditions")];
+        let mut parser = ToolCallParser::for_template(tools, Some("{{- \"<|python_tag|>\" }}"));
+
+        let result = parser.add(
+            r#"<|python_tag|>{"name": "get_current_conditions", "parameters": {"location": "San Francisco, CA", "unit": "Fahrenheit"}}<|eom_id|>"#,
+        );
+        assert_eq!(result.calls.len(), 1);
+        let call = &result.calls[0];
+        assert_eq!(call.function.name, "get_current_conditions");
+        assert_eq!(
+            call.function.arguments.get("location").unwrap(),
+            &serde_json::json!("San Francisco, CA")
+        );
+        assert_eq!(
+            call.function.arguments.get("unit").unwrap(),
+            &serde_json::json!("Fahrenheit")
+        );
+    }
+
+    #[test]
+    fn test_llama31_template_bare_json_call() {
+        let template = crate::core::templates::get_all()
+            .into_iter()
+            .find(|t| t.name == "llama3")
+            .unwrap()
+            .template;
+        assert!(template.contains(PYTHON_TAG));
+        let prompt = crate::core::prompt::PromptBuilder::new(Some(template.to_string()))
+            .with_bos(Some("<|begin_of_text|>".to_string()))
+            .render_prompt(vec![crate::core::prompt::ChatMessage {
+                role: "user".to_string(),
+                content: "What's the weather in Paris?".to_string(),
+            }])
+            .unwrap();
+        assert!(prompt.ends_with("<|start_header_id|>assistant<|end_header_id|>\n\n"));
+
+        let tools = vec![make_tool("get_weather")];
+        let mut parser = ToolCallParser::for_template(tools, Some(template));
+        let mut calls = Vec::new();
+        for piece in [
+            "{\"name\": \"get_",
+            "weather\", \"parameters\": {\"city\": \"Paris\"}}",
+        ] {
+            calls.extend(parser.add(piece).calls);
+        }
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].function.name, "get_weather");
+        assert_eq!(
+            calls[0].function.arguments.get("city").unwrap(),
+            &serde_json::json!("Paris")
+        );
+
+        // Plain answers still pass through untouched
+        let mut parser =
+            ToolCallParser::for_template(vec![make_tool("get_weather")], Some(template));
+        let result = parser.add("It is sunny.");
+        assert!(result.calls.is_empty());
+        assert_eq!(result.content, "It is sunny.");
+    }
+
+    #[test]
+    fn test_llama_python_tag_builtin_call() {
+        let tools = vec![make_tool("brave_search"), make_tool("wolfram_alpha")];
+        let mut parser = ToolCallParser::new(tools, PYTHON_TAG);
+
+        let r1 = parser.add("<|python_tag|>brave_search.call(query=\"current weather in ");
+        assert!(r1.calls.is_empty());
+
+        let r2 = parser.add("Menlo Park, California\")<|eom_id|>");
+        assert_eq!(r2.calls.len(), 1);
+        assert!(r2.content.is_empty());
+        assert_eq!(r2.calls[0].function.name, "brave_search");
+        assert_eq!(
+            r2.calls[0].function.arguments.get("query").unwrap(),
+            &serde_json::json!("current weather in Menlo Park, California")
+        );
+    }
+
+    #[test]
+    fn test_python_tag_survives_token_stream() {
+        use crate::core::token_output_stream::TokenOutputStream;
+
+        let json = serde_json::json!({
+            "version": "1.0",
+            "truncation": null,
+            "padding": null,
+            "added_tokens": [
+                { "id": 2, "content": "<|python_tag|>", "single_word": false, "lstrip": false,
+                  "rstrip": false, "normalized": false, "special": true },
+                { "id": 3, "content": "<|eom_id|>", "single_word": false, "lstrip": false,
+                  "rstrip": false, "normalized": false, "special": true }
+            ],
+            "normalizer": null,
+            "pre_tokenizer": { "type": "WhitespaceSplit" },
+            "post_processor": null,
+            "decoder": null,
+            "model": {
+                "type": "WordLevel",
+                "vocab": {
+                    "<unk>": 0,
+                    "brave_search.call(query=\"weather\")": 1,
+                    "<|python_tag|>": 2,
+                    "<|eom_id|>": 3
+                },
+                "unk_token": "<unk>"
+            }
+        });
+        let tokenizer = tokenizers::Tokenizer::from_bytes(json.to_string().as_bytes()).unwrap();
+        let stream_text = |preserve: bool| {
+            let mut parser = ToolCallParser::new(vec![make_tool("brave_search")], PYTHON_TAG);
+            let mut tos = TokenOutputStream::new(tokenizer.clone());
+            if preserve {
+                tos.preserve_special_tokens(parser.special_tokens());
+            }
+            let mut calls = Vec::new();
+            for token in [2, 1, 3] {
+                if let Some(text) = tos.next_token(token).unwrap() {
+                    calls.extend(parser.add(&text).calls);
+                }
+            }
+            calls
+        };
+
+        // Without preservation the tags are skipped during decoding
+        assert!(stream_text(false).is_empty());
+
+        let calls = stream_text(true);
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].function.name, "brave_search");
+        assert_eq!(
+            calls[0].function.arguments.get("query").unwrap(),
+            &serde_json::json!("weather")
+        );
+    }
+
+    #[test]
+    fn test_python_call_argument_literals() {
+        let (args, consumed) =
+            parse_python_call_arguments(".call(a='x, y', n=3, flag=True, none=None, xs=[1, 2])!")
+                .unwrap();
+        assert_eq!(
+            consumed,
+            ".call(a='x, y', n=3, flag=True, none=None, xs=[1, 2])".len()
+        );
+        assert_eq!(args.get("a").unwrap(), &serde_json::json!("x, y"));
+        assert_eq!(args.get("n").unwrap(), &serde_json::json!(3));
+        assert_eq!(args.get("flag").unwrap(), &serde_json::json!(true));
+        assert_eq!(args.get("none").unwrap(), &serde_json::Value::Null);
+        assert_eq!(args.get("xs").unwrap(), &serde_json::json!([1, 2]));
+    }
+
+    #[test]
+    fn test_python_string_escapes() {
+        let (args, _) = parse_python_call_arguments(
+            r#"(q="say \"hi\"\n", p='it\'s', u="\u00e9\x41", w="C:\d")"#,
+        )
+        .unwrap();
+        assert_eq!(args.get("q").unwrap(), &serde_json::json!("say \"hi\"\n"));
+        assert_eq!(args.get("p").unwrap(), &serde_json::json!("it's"));
+        assert_eq!(args.get("u").unwrap(), &serde_json::json!("éA"));
+        assert_eq!(args.get("w").unwrap(), &serde_json::json!("C:\\d"));
+    }
 }
@@ -15,15 +15,38 @@ pub enum Locale {
     En,
     Ru,
     PtBr,
+    Zh,
 }
 
 impl Locale {
+    /// Все локали, для которых есть переводы
+    pub const SUPPORTED: [Locale; 4] = [Locale::En, Locale::Ru, Locale::PtBr, Locale::Zh];
+
     /// Получить строковое представление локали
     pub fn as_str(&self) -> &'static str {
         match self {
             Locale::En => "en",
             Locale::Ru => "ru",
             Locale::PtBr => "pt-BR",
+            Locale::Zh => "zh",
+        }
+    }
+
+    /// Сопоставить системный тег локали (`ru_RU.UTF-8`, `zh-Hans-CN`, `pt_BR`)
+    /// с поддерживаемой локалью
+    pub fn from_system_tag(tag: &str) -> Option<Locale> {
+        let tag = tag.split(['.', '@']).next().unwrap_or_default();
+        let language = tag
+            .split(['_', '-'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match language.as_str() {
+            "en" => Some(Locale::En),
+            "ru" => Some(Locale::Ru),
+            "pt" => Some(Locale::PtBr),
+            "zh" => Some(Locale::Zh),
+            _ => None,
         }
     }
 }
@@ -32,12 +55,47 @@ impl FromStr for Locale {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "ru" | "ru-RU" => Ok(Locale::Ru),
-            "pt" | "pt-BR" | "pt-PT" => Ok(Locale::PtBr),
-            _ => Ok(Locale::En),
+        Locale::from_system_tag(s).ok_or(())
+    }
+}
+
+/// Системная локаль: `LC_ALL`/`LC_MESSAGES`/`LANG` на Linux,
+/// `AppleLocale` (NSLocale) на macOS, `GetUserDefaultLocaleName` на Windows.
+pub fn system_locale_tag() -> Option<String> {
+    #[cfg(target_os = "windows")]
+    {
+        use windows_sys::Win32::Globalization::GetUserDefaultLocaleName;
+        // LOCALE_NAME_MAX_LENGTH
+        let mut buf = [0u16; 85];
+        let len = unsafe { GetUserDefaultLocaleName(buf.as_mut_ptr(), buf.len() as i32) };
+        if len > 1 {
+            return Some(String::from_utf16_lossy(&buf[..len as usize - 1]));
         }
     }
+
+    #[cfg(target_os = "macos")]
+    {
+        if let Ok(output) = std::process::Command::new("defaults")
+            .args(["read", "-g", "AppleLocale"])
+            .output()
+            && output.status.success()
+        {
+            let tag = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            if !tag.is_empty() {
+                return Some(tag);
+            }
+        }
+    }
+
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty() && value != "C" && value != "POSIX")
+}
+
+/// Поддерживаемая локаль, соответствующая системной
+pub fn detect_system_locale() -> Option<Locale> {
+    system_locale_tag().and_then(|tag| Locale::from_system_tag(&tag))
 }
 
 /// Глобальное хранилище переводов
@@ -121,6 +179,30 @@ pub fn init() {
         );
         translations.insert(Locale::PtBr, pt_br);
 
+        // Simplified Chinese translations
+        let mut zh = HashMap::new();
+        zh.insert(
+            "error.model.load_failed".to_string(),
+            "模型加载失败".to_string(),
+        );
+        zh.insert(
+            "error.model.unload_failed".to_string(),
+            "模型卸载失败".to_string(),
+        );
+        zh.insert(
+            "error.model.not_loaded".to_string(),
+            "模型未加载".to_string(),
+        );
+        zh.insert(
+            "error.settings.load_failed".to_string(),
+            "设置加载失败".to_string(),
+        );
+        zh.insert(
+            "error.settings.save_failed".to_string(),
+            "设置保存失败".to_string(),
+        );
+        translations.insert(Locale::Zh, zh);
+
         TRANSLATIONS = Some(translations);
         CURRENT_LOCALE = Locale::default();
     }
@@ -168,3 +250,18 @@ macro_rules! t {
         $crate::i18n::t($key)
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Locale;
+
+    #[test]
+    fn maps_system_tags_to_supported_locales() {
+        assert_eq!(Locale::from_system_tag("ru_RU.UTF-8"), Some(Locale::Ru));
+        assert_eq!(Locale::from_system_tag("zh-Hans-CN"), Some(Locale::Zh));
+        assert_eq!(Locale::from_system_tag("pt_BR"), Some(Locale::PtBr));
+        assert_eq!(Locale::from_system_tag("en_US@euro"), Some(Locale::En));
+        assert_eq!(Locale::from_system_tag("de_DE.UTF-8"), None);
+        assert!("fr".parse::<Locale>().is_err());
+    }
+}
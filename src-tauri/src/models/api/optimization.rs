@@ -14,6 +14,18 @@
 
 use candle::DType;
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::core::device::detect_cpu_features;
+use crate::models::common::shards::parallel_prefetch_enabled;
+
+/// Сбрасывается на время сборки модели, для которой пользовательское правило
+/// (`UserDefinedBackendConfig::use_flash_attn`) запрещает Flash Attention.
+static FLASH_ATTN_ALLOWED: AtomicBool = AtomicBool::new(true);
+
+pub fn set_flash_attn_allowed(allowed: bool) {
+    FLASH_ATTN_ALLOWED.store(allowed, Ordering::Relaxed);
+}
 
 /// Формат весов модели
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -39,6 +51,14 @@ pub struct OptimizationConfig {
 
     /// Использовать KV-кэш (всегда включён)
     use_kv_cache: bool,
+
+    /// Параллельное чтение шардов SafeTensors (model-0000N-of-NNNNN) перед mmap
+    #[serde(default = "default_parallel_load_shards")]
+    parallel_load_shards: bool,
+}
+
+fn default_parallel_load_shards() -> bool {
+    false
 }
 
 impl Default for OptimizationConfig {
@@ -48,6 +68,7 @@ impl Default for OptimizationConfig {
             weight_format: WeightFormat::Gguf,
             dtype: None,
             use_kv_cache: true,
+            parallel_load_shards: default_parallel_load_shards(),
         }
     }
 }
@@ -61,22 +82,27 @@ impl OptimizationConfig {
             weight_format: WeightFormat::Gguf,
             dtype: None,
             use_kv_cache: true,
+            parallel_load_shards: false,
         }
     }
 
     /// Создаёт конфигурацию для SafeTensors модели
+    /// Параллельный прогрев шардов берётся из настроек загрузки
     /// Flash Attention автоматически включается если:
     /// - feature "flash-attn" скомпилирован
     /// - CUDA доступен
     /// - dtype = bf16 или f16
+    /// - пользовательское правило архитектуры его не запрещает
     pub fn for_safetensors(dtype: DType) -> Self {
-        let flash_available = Self::is_flash_attn_available(dtype);
+        let flash_available =
+            FLASH_ATTN_ALLOWED.load(Ordering::Relaxed) && Self::is_flash_attn_available(dtype);
 
         Self {
             use_flash_attn: flash_available,
             weight_format: WeightFormat::SafeTensors,
             dtype: Some(dtype_to_string(dtype)),
             use_kv_cache: true,
+            parallel_load_shards: parallel_prefetch_enabled(),
         }
     }
 
@@ -107,10 +133,23 @@ impl OptimizationConfig {
         self.weight_format
     }
 
-    /// Возвращает информацию о SIMD возможностях
+    /// Возвращает true если шарды SafeTensors прогреваются параллельно
+    pub fn parallel_load_shards(&self) -> bool {
+        self.parallel_load_shards
+    }
+
+    /// Включает/выключает параллельный прогрев шардов SafeTensors
+    pub fn with_parallel_load_shards(mut self, enabled: bool) -> Self {
+        self.parallel_load_shards = enabled;
+        self
+    }
+
+    /// Возвращает SIMD возможности, с которыми собран бинарник
     pub fn simd_info() -> SimdCapabilities {
         SimdCapabilities {
             avx: candle::utils::with_avx(),
+            avx2: cfg!(target_feature = "avx2"),
+            avx512f: cfg!(target_feature = "avx512f"),
             neon: candle::utils::with_neon(),
             simd128: candle::utils::with_simd128(),
             f16c: candle::utils::with_f16c(),
@@ -135,21 +174,86 @@ impl OptimizationConfig {
 }
 
 /// Информация о доступных SIMD возможностях
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SimdCapabilities {
     pub avx: bool,
+    #[serde(default)]
+    pub avx2: bool,
+    #[serde(default)]
+    pub avx512f: bool,
     pub neon: bool,
     pub simd128: bool,
     pub f16c: bool,
 }
 
 impl SimdCapabilities {
+    /// Определяет возможности CPU во время выполнения
+    /// (NEON — через `detect_cpu_features`).
+    pub fn detect() -> Self {
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        let (avx, avx2, avx512f, f16c) = (
+            std::arch::is_x86_feature_detected!("avx"),
+            std::arch::is_x86_feature_detected!("avx2"),
+            std::arch::is_x86_feature_detected!("avx512f"),
+            std::arch::is_x86_feature_detected!("f16c"),
+        );
+        #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+        let (avx, avx2, avx512f, f16c) = (false, false, false, false);
+
+        Self {
+            avx,
+            avx2,
+            avx512f,
+            neon: detect_cpu_features().has_neon,
+            simd128: candle::utils::with_simd128(),
+            f16c,
+        }
+    }
+
+    /// Самый широкий доступный набор инструкций
+    pub fn best_path(&self) -> &'static str {
+        if self.avx512f {
+            "AVX-512"
+        } else if self.avx2 {
+            "AVX2"
+        } else if self.avx {
+            "AVX"
+        } else if self.neon {
+            "NEON"
+        } else if self.simd128 {
+            "SIMD128"
+        } else {
+            "scalar"
+        }
+    }
+
+    /// Расширения, которые CPU поддерживает, но сборка не использует
+    /// (`detected` — результат `detect()`, `self` — `simd_info()`).
+    pub fn unused_by_build(&self, detected: &SimdCapabilities) -> Vec<&'static str> {
+        [
+            ("AVX", self.avx, detected.avx),
+            ("AVX2", self.avx2, detected.avx2),
+            ("AVX-512F", self.avx512f, detected.avx512f),
+            ("F16C", self.f16c, detected.f16c),
+        ]
+        .into_iter()
+        .filter(|(_, compiled, available)| *available && !*compiled)
+        .map(|(name, _, _)| name)
+        .collect()
+    }
+
     /// Возвращает строку с описанием SIMD возможностей
     pub fn description(&self) -> String {
         let mut caps = Vec::new();
         if self.avx {
             caps.push("AVX");
         }
+        if self.avx2 {
+            caps.push("AVX2");
+        }
+        if self.avx512f {
+            caps.push("AVX-512F");
+        }
         if self.neon {
             caps.push("NEON");
         }
@@ -1,5 +1,7 @@
 //! Common utilities for model backends
 
 pub mod flash_helpers;
+pub mod shards;
 
 pub use flash_helpers::{is_flash_attention_available, scaled_dot_product_attention};
+pub use shards::var_builder_from_safetensors;
@@ -0,0 +1,95 @@
+//! SafeTensors shard loading
+//!
+//! Создание VarBuilder из одного или нескольких SafeTensors файлов.
+//! Веса всегда отображаются лениво через mmap. Для моделей, разбитых на
+//! `model-0000N-of-NNNNN.safetensors`, файлы по желанию (настройка
+//! `parallel_load_shards`, по умолчанию выключена) предварительно читаются
+//! параллельно в `INFERENCE_POOL`, чтобы страницы оказались в page cache
+//! до того, как модель начнёт обращаться к тензорам.
+
+use candle::{DType, Device};
+use candle_nn::VarBuilder;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+use crate::core::rayon_pool::inference_pool;
+use crate::generate::cancel::CANCEL_LOADING;
+
+/// Размер буфера при прогреве шарда.
+const PREFETCH_CHUNK_BYTES: usize = 8 * 1024 * 1024;
+
+/// Текущее значение `LoadTimingSettings::parallel_load_shards`.
+static PARALLEL_PREFETCH: AtomicBool = AtomicBool::new(false);
+
+pub fn set_parallel_prefetch(enabled: bool) {
+    PARALLEL_PREFETCH.store(enabled, Ordering::Relaxed);
+}
+
+/// Включён ли параллельный прогрев шардов для следующих загрузок.
+pub fn parallel_prefetch_enabled() -> bool {
+    PARALLEL_PREFETCH.load(Ordering::Relaxed)
+}
+
+/// Создаёт VarBuilder из SafeTensors файлов.
+///
+/// При `parallel_prefetch` и более чем одном шарде файлы сначала целиком читаются
+/// конкурентно, чтобы прогреть page cache: на медленных дисках это сокращает
+/// первое обращение к тензорам, но удваивает чтение, если файлы не помещаются
+/// в память. Сами тензоры в любом случае загружаются лениво через mmap.
+pub fn var_builder_from_safetensors(
+    filenames: &[PathBuf],
+    dtype: DType,
+    device: &Device,
+    parallel_prefetch: bool,
+) -> candle::Result<VarBuilder<'static>> {
+    if parallel_prefetch && filenames.len() > 1 {
+        let started = Instant::now();
+        let bytes = prefetch_shards(filenames)?;
+        log::info!(
+            "Prefetched {} SafeTensors shards ({} MB) in parallel in {}ms",
+            filenames.len(),
+            bytes / (1024 * 1024),
+            started.elapsed().as_millis()
+        );
+    }
+
+    unsafe { VarBuilder::from_mmaped_safetensors(filenames, dtype, device) }
+}
+
+/// Читает все шарды в `INFERENCE_POOL`, возвращает число прочитанных байт.
+/// Первая ошибка чтения или отмена загрузки прерывает прогрев и возвращается вызывающему.
+fn prefetch_shards(filenames: &[PathBuf]) -> candle::Result<u64> {
+    inference_pool().install(|| {
+        filenames
+            .par_iter()
+            .map(|path| {
+                prefetch_file(path).map_err(|e| {
+                    candle::Error::Msg(format!("Failed to read shard {}: {}", path.display(), e))
+                })
+            })
+            .sum::<candle::Result<u64>>()
+    })
+}
+
+fn prefetch_file(path: &Path) -> std::io::Result<u64> {
+    let mut file = File::open(path)?;
+    let mut buf = vec![0u8; PREFETCH_CHUNK_BYTES];
+    let mut total = 0u64;
+    loop {
+        if CANCEL_LOADING.load(Ordering::SeqCst) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Interrupted,
+                "cancelled",
+            ));
+        }
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            return Ok(total);
+        }
+        total += n as u64;
+    }
+}
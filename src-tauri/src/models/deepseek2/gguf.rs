@@ -6,8 +6,8 @@ use candle::quantized::gguf_file;
 use candle::{DType, Device};
 use std::fs::File;
 
-use super::DeepSeek2Backend;
 use super::quantized_model::GGUFDeepSeek2;
+use super::{DeepSeek2Backend, DeepSeekVariant};
 
 impl DeepSeek2Backend {
     /// Создаёт бекенд из GGUF Content
@@ -41,9 +41,22 @@ impl DeepSeek2Backend {
             .and_then(|v| v.to_u32().ok())
             .unwrap_or(163840) as usize;
 
+        let general_name = content
+            .metadata
+            .get("general.name")
+            .and_then(|v| v.to_string().ok())
+            .cloned();
+        let q_lora_rank = content
+            .metadata
+            .get(&format!("{arch}.attention.q_lora_rank"))
+            .and_then(|v| v.to_u32().ok())
+            .map(|v| v as usize);
+        let variant = DeepSeekVariant::detect(general_name.as_deref(), q_lora_rank);
+
         log::info!(
-            "Loading DeepSeek2 GGUF: arch={}, vocab_size={}, max_seq_len={}, dtype={:?}",
+            "Loading DeepSeek2 GGUF: arch={}, variant={:?}, vocab_size={}, max_seq_len={}, dtype={:?}",
             arch,
+            variant,
             vocab_size,
             max_seq_len,
             dtype
@@ -59,6 +72,7 @@ impl DeepSeek2Backend {
             device.clone(),
             vocab_size,
             max_seq_len,
+            variant,
         ))
     }
 
@@ -24,6 +24,50 @@ enum DeepSeek2Inner {
     Quantized(GGUFDeepSeek2),
 }
 
+/// Вариант модели семейства DeepSeek на архитектуре deepseek2
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeepSeekVariant {
+    V2,
+    V2Lite,
+    R1,
+    R1Distill,
+}
+
+impl DeepSeekVariant {
+    /// Определяет вариант по имени модели (`general.name` или имени директории).
+    ///
+    /// `q_lora_rank == None` характерен для V2-Lite (без low-rank проекции Q).
+    pub fn detect(name: Option<&str>, q_lora_rank: Option<usize>) -> Self {
+        let lower = name.unwrap_or_default().to_ascii_lowercase();
+        let is_r1 = lower
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .any(|part| part == "r1");
+        if is_r1 && lower.contains("distill") {
+            Self::R1Distill
+        } else if is_r1 {
+            Self::R1
+        } else if lower.contains("lite") || q_lora_rank.is_none() {
+            Self::V2Lite
+        } else {
+            Self::V2
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::V2 => "deepseek2",
+            Self::V2Lite => "deepseek2-lite",
+            Self::R1 => "deepseek-r1",
+            Self::R1Distill => "deepseek-r1-distill",
+        }
+    }
+
+    /// Модели R1 всегда рассуждают в блоке `<think>`
+    pub fn is_reasoning(&self) -> bool {
+        matches!(self, Self::R1 | Self::R1Distill)
+    }
+}
+
 /// DeepSeek2 бекенд
 pub struct DeepSeek2Backend {
     inner: DeepSeek2Inner,
@@ -31,6 +75,8 @@ pub struct DeepSeek2Backend {
     vocab_size: usize,
     max_seq_len: usize,
     optimization: OptimizationConfig,
+    variant: DeepSeekVariant,
+    model_type: String,
 }
 
 impl DeepSeek2Backend {
@@ -41,13 +87,17 @@ impl DeepSeek2Backend {
         vocab_size: usize,
         max_seq_len: usize,
         optimization: OptimizationConfig,
+        variant: DeepSeekVariant,
     ) -> Self {
+        let model_type = Self::build_model_type(variant, &optimization);
         Self {
             inner: DeepSeek2Inner::Full(model),
             _device: device,
             vocab_size,
             max_seq_len,
             optimization,
+            variant,
+            model_type,
         }
     }
 
@@ -57,13 +107,34 @@ impl DeepSeek2Backend {
         device: Device,
         vocab_size: usize,
         max_seq_len: usize,
+        variant: DeepSeekVariant,
     ) -> Self {
+        let optimization = OptimizationConfig::for_gguf();
+        let model_type = Self::build_model_type(variant, &optimization);
         Self {
             inner: DeepSeek2Inner::Quantized(model),
             _device: device,
             vocab_size,
             max_seq_len,
-            optimization: OptimizationConfig::for_gguf(),
+            optimization,
+            variant,
+            model_type,
+        }
+    }
+
+    /// Вариант модели (V2 / V2-Lite / R1 / R1-Distill)
+    pub fn variant(&self) -> DeepSeekVariant {
+        self.variant
+    }
+
+    fn build_model_type(variant: DeepSeekVariant, optimization: &OptimizationConfig) -> String {
+        let base = variant.as_str();
+        match optimization.weight_format() {
+            WeightFormat::Gguf => format!("{base}-gguf"),
+            WeightFormat::SafeTensors if optimization.uses_flash_attn() => {
+                format!("{base}-flash")
+            }
+            WeightFormat::SafeTensors => base.to_string(),
         }
     }
 }
@@ -89,16 +160,7 @@ impl ModelBackend for DeepSeek2Backend {
     }
 
     fn model_type(&self) -> &str {
-        match self.optimization.weight_format() {
-            WeightFormat::Gguf => "deepseek2-gguf",
-            WeightFormat::SafeTensors => {
-                if self.optimization.uses_flash_attn() {
-                    "deepseek2-flash"
-                } else {
-                    "deepseek2"
-                }
-            }
-        }
+        &self.model_type
     }
 
     fn vocab_size(&self) -> usize {
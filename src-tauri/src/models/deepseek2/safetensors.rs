@@ -2,11 +2,11 @@
 //!
 //! Загрузка DeepSeek-V2 моделей из SafeTensors формата.
 
-use super::DeepSeek2Backend;
 use super::model::{DeepSeekV2Config, ModelForCausalLM};
+use super::{DeepSeek2Backend, DeepSeekVariant};
 use crate::models::api::optimization::OptimizationConfig;
+use crate::models::common::var_builder_from_safetensors;
 use candle::{DType, Device};
-use candle_nn::VarBuilder;
 use std::path::{Path, PathBuf};
 
 impl DeepSeek2Backend {
@@ -23,18 +23,28 @@ impl DeepSeek2Backend {
         let config: DeepSeekV2Config = serde_json::from_slice(&config_data)
             .map_err(|e| candle::Error::Msg(format!("Failed to parse config.json: {}", e)))?;
 
+        // Flash Attention автоматически включается для bf16/f16 на CUDA
+        let optimization = OptimizationConfig::for_safetensors(dtype);
+
         // Создаём VarBuilder из SafeTensors
-        let vb = unsafe {
-            VarBuilder::from_mmaped_safetensors(filenames, dtype, device)
-                .map_err(|e| candle::Error::Msg(format!("Failed to load SafeTensors: {}", e)))?
-        };
+        let vb = var_builder_from_safetensors(
+            filenames,
+            dtype,
+            device,
+            optimization.parallel_load_shards(),
+        )
+        .map_err(|e| candle::Error::Msg(format!("Failed to load SafeTensors: {}", e)))?;
 
         // Создаём модель
         let inner = ModelForCausalLM::new(&config, vb)
             .map_err(|e| candle::Error::Msg(format!("Failed to build DeepSeek2 model: {}", e)))?;
 
-        // Flash Attention автоматически включается для bf16/f16 на CUDA
-        let optimization = OptimizationConfig::for_safetensors(dtype);
+        // В config.json нет имени модели, берём имя директории
+        let dir_name = config_path
+            .parent()
+            .and_then(|p| p.file_name())
+            .map(|n| n.to_string_lossy().to_string());
+        let variant = DeepSeekVariant::detect(dir_name.as_deref(), config.q_lora_rank);
 
         Ok(Self::new(
             inner,
@@ -42,6 +52,7 @@ impl DeepSeek2Backend {
             config.vocab_size,
             config.max_position_embeddings,
             optimization,
+            variant,
         ))
     }
 
@@ -3,13 +3,12 @@
 //! Загрузка Llama-подобных моделей из SafeTensors формата.
 
 use candle::{DType, Device};
-use candle_nn::VarBuilder;
 use candle_transformers::models::llama::{Cache, Llama, LlamaConfig};
 use std::path::{Path, PathBuf};
 
 use super::LlamaBackend;
 use crate::models::api::optimization::OptimizationConfig;
-use crate::models::common::is_flash_attention_available;
+use crate::models::common::{is_flash_attention_available, var_builder_from_safetensors};
 
 impl LlamaBackend {
     /// Создаёт бекенд из SafeTensors файлов
@@ -53,15 +52,18 @@ impl LlamaBackend {
             max_seq_len
         );
 
-        // Создаём VarBuilder из SafeTensors
-        let vb = unsafe {
-            VarBuilder::from_mmaped_safetensors(filenames, dtype, device)
-                .map_err(|e| format!("Failed to load SafeTensors: {}", e))?
-        };
-
         // Создаём конфигурацию оптимизаций
         let optimization = OptimizationConfig::for_safetensors(dtype);
 
+        // Создаём VarBuilder из SafeTensors
+        let vb = var_builder_from_safetensors(
+            filenames,
+            dtype,
+            device,
+            optimization.parallel_load_shards(),
+        )
+        .map_err(|e| format!("Failed to load SafeTensors: {}", e))?;
+
         // Проверяем условия для Flash Attention
         let fa_available = is_flash_attention_available();
         let fa_opt = optimization.uses_flash_attn();
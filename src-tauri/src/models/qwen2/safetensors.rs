@@ -4,12 +4,12 @@
 //! Использует candle_transformers::models::qwen2.
 
 use candle::{DType, Device};
-use candle_nn::VarBuilder;
 use candle_transformers::models::qwen2::{Config, ModelForCausalLM};
 use std::path::{Path, PathBuf};
 
 use super::Qwen2Backend;
 use crate::models::api::optimization::OptimizationConfig;
+use crate::models::common::var_builder_from_safetensors;
 
 impl Qwen2Backend {
     /// Создаёт бекенд из SafeTensors файлов
@@ -25,15 +25,18 @@ impl Qwen2Backend {
         let config: Config = serde_json::from_slice(&config_data)
             .map_err(|e| format!("Failed to parse config.json: {}", e))?;
 
-        // Создаём VarBuilder из SafeTensors
-        let vb = unsafe {
-            VarBuilder::from_mmaped_safetensors(filenames, dtype, device)
-                .map_err(|e| format!("Failed to load SafeTensors: {}", e))?
-        };
-
         // Создаём конфигурацию оптимизаций
         let optimization = OptimizationConfig::for_safetensors(dtype);
 
+        // Создаём VarBuilder из SafeTensors
+        let vb = var_builder_from_safetensors(
+            filenames,
+            dtype,
+            device,
+            optimization.parallel_load_shards(),
+        )
+        .map_err(|e| format!("Failed to load SafeTensors: {}", e))?;
+
         log::info!(
             "Loading Qwen2/2.5 SafeTensors: vocab_size={}, max_position_embeddings={}",
             config.vocab_size,
@@ -4,12 +4,12 @@
 //! Использует candle_transformers::models::qwen2_moe.
 
 use candle::{DType, Device};
-use candle_nn::VarBuilder;
 use candle_transformers::models::qwen2_moe::{Config, Model};
 use std::path::{Path, PathBuf};
 
 use super::Qwen2MoeBackend;
 use crate::models::api::optimization::OptimizationConfig;
+use crate::models::common::var_builder_from_safetensors;
 
 impl Qwen2MoeBackend {
     /// Создаёт бекенд из SafeTensors файлов
@@ -25,11 +25,17 @@ impl Qwen2MoeBackend {
         let config: Config = serde_json::from_slice(&config_data)
             .map_err(|e| format!("Failed to parse config.json: {}", e))?;
 
+        // Qwen2-MoE в candle-transformers не использует flash attention
+        let optimization = OptimizationConfig::for_safetensors(dtype);
+
         // Создаём VarBuilder из SafeTensors
-        let vb = unsafe {
-            VarBuilder::from_mmaped_safetensors(filenames, dtype, device)
-                .map_err(|e| format!("Failed to load SafeTensors: {}", e))?
-        };
+        let vb = var_builder_from_safetensors(
+            filenames,
+            dtype,
+            device,
+            optimization.parallel_load_shards(),
+        )
+        .map_err(|e| format!("Failed to load SafeTensors: {}", e))?;
 
         log::info!(
             "Loading Qwen2-MoE SafeTensors: vocab_size={}, max_position_embeddings={}, num_experts={}",
@@ -42,9 +48,6 @@ impl Qwen2MoeBackend {
         let inner = Model::new(&config, vb)
             .map_err(|e| format!("Failed to build Qwen2-MoE model: {}", e))?;
 
-        // Qwen2-MoE в candle-transformers не использует flash attention
-        let optimization = OptimizationConfig::for_safetensors(dtype);
-
         Ok(Self::new(
             inner,
             device.clone(),
@@ -4,13 +4,12 @@
 //! Основано на примере src-tauri/src/models/qwen/main.rs
 
 use candle::{DType, Device};
-use candle_nn::VarBuilder;
 use std::path::{Path, PathBuf};
 
 use super::Qwen3Backend;
 use super::model::{Config, ModelForCausalLM};
 use crate::models::api::optimization::OptimizationConfig;
-use crate::models::common::is_flash_attention_available;
+use crate::models::common::{is_flash_attention_available, var_builder_from_safetensors};
 
 impl Qwen3Backend {
     /// Создаёт бекенд из SafeTensors файлов (как в примере qwen)
@@ -27,15 +26,18 @@ impl Qwen3Backend {
         let mut config: Config = serde_json::from_slice(&config_data)
             .map_err(|e| format!("Failed to parse config.json: {}", e))?;
 
-        // Создаём VarBuilder из SafeTensors (как в примере qwen: line 334)
-        let vb = unsafe {
-            VarBuilder::from_mmaped_safetensors(filenames, dtype, device)
-                .map_err(|e| format!("Failed to load SafeTensors: {}", e))?
-        };
-
         // Создаём конфигурацию оптимизаций
         let optimization = OptimizationConfig::for_safetensors(dtype);
 
+        // Создаём VarBuilder из SafeTensors
+        let vb = var_builder_from_safetensors(
+            filenames,
+            dtype,
+            device,
+            optimization.parallel_load_shards(),
+        )
+        .map_err(|e| format!("Failed to load SafeTensors: {}", e))?;
+
         // Проверяем условия для Flash Attention и устанавливаем флаг в конфиге
         let fa_available = is_flash_attention_available();
         let fa_opt = optimization.uses_flash_attn();
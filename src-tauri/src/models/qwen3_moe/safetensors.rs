@@ -4,13 +4,13 @@
 //! Основано на примере src-tauri/src/models/qwen/main.rs (строки 344-346)
 
 use candle::{DType, Device};
-use candle_nn::VarBuilder;
 // Use local model with flash-attn support
 use super::model::{Config, ModelForCausalLM};
 use std::path::{Path, PathBuf};
 
 use super::Qwen3MoeBackend;
 use crate::models::api::optimization::OptimizationConfig;
+use crate::models::common::var_builder_from_safetensors;
 
 impl Qwen3MoeBackend {
     /// Создаёт бекенд из SafeTensors файлов (как в примере qwen: lines 344-346)
@@ -27,19 +27,22 @@ impl Qwen3MoeBackend {
         let config: Config = serde_json::from_slice(&config_data)
             .map_err(|e| format!("Failed to parse config.json: {}", e))?;
 
-        // Создаём VarBuilder из SafeTensors (как в примере qwen: line 334)
-        let vb = unsafe {
-            VarBuilder::from_mmaped_safetensors(filenames, dtype, device)
-                .map_err(|e| format!("Failed to load SafeTensors: {}", e))?
-        };
+        // Flash Attention автоматически включается для bf16/f16 на CUDA
+        let optimization = OptimizationConfig::for_safetensors(dtype);
+
+        // Создаём VarBuilder из SafeTensors
+        let vb = var_builder_from_safetensors(
+            filenames,
+            dtype,
+            device,
+            optimization.parallel_load_shards(),
+        )
+        .map_err(|e| format!("Failed to load SafeTensors: {}", e))?;
 
         // Создаём модель (как в примере qwen: line 346)
         let inner = ModelForCausalLM::new(&config, vb)
             .map_err(|e| format!("Failed to build Qwen3-MoE model: {}", e))?;
 
-        // Flash Attention автоматически включается для bf16/f16 на CUDA
-        let optimization = OptimizationConfig::for_safetensors(dtype);
-
         Ok(Self::new(
             inner,
             device.clone(),
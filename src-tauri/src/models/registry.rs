@@ -1,7 +1,9 @@
 //! Model registry - регистрация и автоопределение архитектур моделей
 
 use candle::quantized::gguf_file::Value;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::RwLock;
 
 /// Поддерживаемые архитектуры моделей
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -16,6 +18,16 @@ pub enum ArchKind {
 }
 
 impl ArchKind {
+    /// Все встроенные архитектуры
+    pub const ALL: [ArchKind; 6] = [
+        ArchKind::llama,
+        ArchKind::qwen2,
+        ArchKind::qwen2moe,
+        ArchKind::qwen3,
+        ArchKind::qwen3moe,
+        ArchKind::deepseek2,
+    ];
+
     /// Возвращает человекочитаемое название
     pub fn display_name(&self) -> &'static str {
         match self {
@@ -54,7 +66,7 @@ pub fn detect_arch(metadata: &HashMap<String, Value>) -> Option<ArchKind> {
         _ => None,
     })?;
 
-    detect_arch_from_string(arch_str)
+    detect_arch_from_string(arch_str).or_else(|| detect_custom_arch(arch_str, true))
 }
 
 /// Определяет архитектуру из config.json
@@ -62,7 +74,7 @@ pub fn detect_arch_from_config(config: &serde_json::Value) -> Option<ArchKind> {
     // Проверяем model_type
     let model_type = config.get("model_type")?.as_str()?;
 
-    detect_arch_from_string(model_type)
+    detect_arch_from_string(model_type).or_else(|| detect_custom_arch(model_type, false))
 }
 
 /// Определяет архитектуру из строки
@@ -87,6 +99,110 @@ pub fn detect_arch_from_string(s: &str) -> Option<ArchKind> {
     }
 }
 
+/// Пользовательское сопоставление: неподдерживаемая архитектура загружается
+/// встроенным бекендом (например, `qwen3_dense` → `qwen3`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserDefinedBackendConfig {
+    /// `general.architecture` (GGUF) или `model_type` (config.json), без учёта регистра
+    pub architecture_match: String,
+    /// Встроенная архитектура, которой загружается модель
+    pub model_type_override: String,
+    /// `true` — правило для GGUF, `false` — для SafeTensors
+    pub use_gguf: bool,
+    /// Разрешить Flash Attention (SafeTensors на CUDA); `false` отключает его для модели
+    #[serde(default = "default_use_flash_attn")]
+    pub use_flash_attn: bool,
+}
+
+fn default_use_flash_attn() -> bool {
+    true
+}
+
+impl UserDefinedBackendConfig {
+    /// Проверяет правило и возвращает архитектуру, на которую оно указывает.
+    pub fn target_arch(&self) -> Result<ArchKind, String> {
+        if self.architecture_match.trim().is_empty() {
+            return Err("architecture_match must not be empty".into());
+        }
+        if detect_arch_from_string(&self.architecture_match).is_some() {
+            return Err(format!(
+                "Architecture '{}' is already supported",
+                self.architecture_match
+            ));
+        }
+        detect_arch_from_string(&self.model_type_override).ok_or_else(|| {
+            format!(
+                "Unknown built-in architecture '{}'",
+                self.model_type_override
+            )
+        })
+    }
+
+    fn matches(&self, arch: &str, gguf: bool) -> bool {
+        self.use_gguf == gguf && self.architecture_match.trim().eq_ignore_ascii_case(arch)
+    }
+}
+
+/// Правила из `profile_dir/custom_backends.json`; проверяются после встроенных.
+static CUSTOM_BACKENDS: RwLock<Vec<UserDefinedBackendConfig>> = RwLock::new(Vec::new());
+
+pub fn custom_backends() -> Vec<UserDefinedBackendConfig> {
+    CUSTOM_BACKENDS
+        .read()
+        .map(|backends| backends.clone())
+        .unwrap_or_default()
+}
+
+pub fn set_custom_backends(backends: Vec<UserDefinedBackendConfig>) {
+    if let Ok(mut slot) = CUSTOM_BACKENDS.write() {
+        *slot = backends;
+    }
+}
+
+/// Правило для архитектуры, не распознанной встроенными бекендами.
+pub fn find_custom_backend(arch: &str, gguf: bool) -> Option<UserDefinedBackendConfig> {
+    CUSTOM_BACKENDS
+        .read()
+        .ok()?
+        .iter()
+        .find(|backend| backend.matches(arch, gguf))
+        .cloned()
+}
+
+fn detect_custom_arch(arch: &str, gguf: bool) -> Option<ArchKind> {
+    find_custom_backend(arch, gguf)?.target_arch().ok()
+}
+
+/// GGUF-бекенды читают ключи встроенной архитектуры (`qwen3.block_count` и т.п.).
+/// Для модели, распознанной пользовательским правилом, переименовывает
+/// `{architecture_match}.*` в `{target}.*` и подменяет `general.architecture`.
+pub fn remap_custom_gguf_metadata(metadata: &mut HashMap<String, Value>, arch: ArchKind) {
+    let source = match metadata.get("general.architecture") {
+        Some(Value::String(source)) => source.clone(),
+        _ => return,
+    };
+    let target = arch.display_name();
+    if detect_arch_from_string(&source).is_some() || find_custom_backend(&source, true).is_none() {
+        return;
+    }
+
+    let prefix = format!("{source}.");
+    let keys: Vec<String> = metadata
+        .keys()
+        .filter(|key| key.starts_with(&prefix))
+        .cloned()
+        .collect();
+    for key in keys {
+        if let Some(value) = metadata.remove(&key) {
+            metadata.insert(format!("{target}.{}", &key[prefix.len()..]), value);
+        }
+    }
+    metadata.insert(
+        "general.architecture".to_string(),
+        Value::String(target.to_string()),
+    );
+}
+
 /// Информация о модели из GGUF
 #[derive(Debug, Clone)]
 pub struct GgufModelInfo {
@@ -138,6 +254,7 @@ impl GgufModelInfo {
 }
 
 use super::ModelBackend;
+use super::api::optimization::set_flash_attn_allowed;
 use candle::Device;
 use candle::quantized::gguf_file::Content;
 use std::sync::OnceLock;
@@ -234,43 +351,59 @@ impl ModelFactory {
         let filenames: Vec<std::path::PathBuf> =
             files.iter().map(|p| p.as_ref().to_path_buf()).collect();
 
-        let _ = config; // config уже загружен, используем config_path
+        // Пользовательское правило может запретить Flash Attention для модели
+        let flash_attn_allowed = config
+            .get("model_type")
+            .and_then(|v| v.as_str())
+            .filter(|model_type| detect_arch_from_string(model_type).is_none())
+            .and_then(|model_type| find_custom_backend(model_type, false))
+            .is_none_or(|rule| rule.use_flash_attn);
+        set_flash_attn_allowed(flash_attn_allowed);
+        let built = self.build_safetensors_backend(arch, &filenames, &config_path, device, dtype);
+        set_flash_attn_allowed(true);
+        built
+    }
 
+    fn build_safetensors_backend(
+        &self,
+        arch: ArchKind,
+        filenames: &[std::path::PathBuf],
+        config_path: &std::path::Path,
+        device: &Device,
+        dtype: candle::DType,
+    ) -> Result<Box<dyn ModelBackend + Send>, String> {
         match arch {
             ArchKind::qwen3 => {
                 use super::qwen3::Qwen3Backend;
-                let model =
-                    Qwen3Backend::from_safetensors(&filenames, &config_path, device, dtype)?;
+                let model = Qwen3Backend::from_safetensors(filenames, config_path, device, dtype)?;
                 Ok(Box::new(model))
             }
             ArchKind::qwen2 => {
                 use super::qwen2::Qwen2Backend;
-                let model =
-                    Qwen2Backend::from_safetensors(&filenames, &config_path, device, dtype)?;
+                let model = Qwen2Backend::from_safetensors(filenames, config_path, device, dtype)?;
                 Ok(Box::new(model))
             }
             ArchKind::qwen3moe => {
                 use super::qwen3_moe::Qwen3MoeBackend;
                 let model =
-                    Qwen3MoeBackend::from_safetensors(&filenames, &config_path, device, dtype)?;
+                    Qwen3MoeBackend::from_safetensors(filenames, config_path, device, dtype)?;
                 Ok(Box::new(model))
             }
             ArchKind::qwen2moe => {
                 use super::qwen2_moe::Qwen2MoeBackend;
                 let model =
-                    Qwen2MoeBackend::from_safetensors(&filenames, &config_path, device, dtype)?;
+                    Qwen2MoeBackend::from_safetensors(filenames, config_path, device, dtype)?;
                 Ok(Box::new(model))
             }
             ArchKind::llama => {
                 use super::llama::LlamaBackend;
-                let model =
-                    LlamaBackend::from_safetensors(&filenames, &config_path, device, dtype)?;
+                let model = LlamaBackend::from_safetensors(filenames, config_path, device, dtype)?;
                 Ok(Box::new(model))
             }
             ArchKind::deepseek2 => {
                 use super::deepseek2::DeepSeek2Backend;
                 let model =
-                    DeepSeek2Backend::from_safetensors(&filenames, &config_path, device, dtype)
+                    DeepSeek2Backend::from_safetensors(filenames, config_path, device, dtype)
                         .map_err(|e| e.to_string())?;
                 Ok(Box::new(model))
             }
@@ -337,6 +470,72 @@ mod tests {
         assert_eq!(detect_arch_from_config(&cfg), Some(ArchKind::DeepSeek2));
     }
 
+    #[test]
+    fn custom_backends_map_unknown_architectures() {
+        let rule = UserDefinedBackendConfig {
+            architecture_match: "qwen3_dense".into(),
+            model_type_override: "qwen3".into(),
+            use_gguf: false,
+            use_flash_attn: true,
+        };
+        assert_eq!(rule.target_arch(), Ok(ArchKind::qwen3));
+        // Файлы правил без `use_flash_attn` не запрещают Flash Attention
+        let saved = serde_json::json!({
+            "architecture_match": "qwen3_dense",
+            "model_type_override": "qwen3",
+            "use_gguf": false
+        });
+        assert_eq!(
+            serde_json::from_value::<UserDefinedBackendConfig>(saved).unwrap(),
+            rule
+        );
+        set_custom_backends(vec![rule.clone()]);
+
+        let cfg = serde_json::json!({ "model_type": "Qwen3_Dense" });
+        assert_eq!(detect_arch_from_config(&cfg), Some(ArchKind::qwen3));
+        let mut metadata = HashMap::new();
+        metadata.insert(
+            "general.architecture".to_string(),
+            Value::String("qwen3_dense".into()),
+        );
+        // Правило задано только для SafeTensors
+        assert_eq!(detect_arch(&metadata), None);
+
+        let bad = UserDefinedBackendConfig {
+            architecture_match: "llama".into(),
+            model_type_override: "qwen3".into(),
+            use_gguf: true,
+            use_flash_attn: false,
+        };
+        assert!(bad.target_arch().is_err());
+
+        // GGUF-бекенд читает `qwen3.*`, поэтому ключи переименовываются
+        set_custom_backends(vec![UserDefinedBackendConfig {
+            use_gguf: true,
+            ..rule
+        }]);
+        metadata.insert("qwen3_dense.block_count".to_string(), Value::U32(28));
+        metadata.insert(
+            "tokenizer.ggml.model".to_string(),
+            Value::String("gpt2".into()),
+        );
+        let arch = detect_arch(&metadata).unwrap();
+        remap_custom_gguf_metadata(&mut metadata, arch);
+        set_custom_backends(Vec::new());
+
+        assert!(matches!(
+            metadata.get("qwen3.block_count"),
+            Some(Value::U32(28))
+        ));
+        assert!(!metadata.contains_key("qwen3_dense.block_count"));
+        assert!(metadata.contains_key("tokenizer.ggml.model"));
+        assert!(matches!(
+            metadata.get("general.architecture"),
+            Some(Value::String(arch)) if arch == "qwen3"
+        ));
+        assert_eq!(detect_arch(&metadata), Some(ArchKind::qwen3));
+    }
+
     #[test]
     fn test_supports_gguf_flags() {
         assert!(ArchKind::Glm4.supports_gguf());
@@ -37,6 +37,10 @@ fn test_generate_request_with_chat_messages() {
         tools: None,
         stop_sequences: None,
         tool_choice: None,
+        disable_template_substitution: false,
+        suppress_thinking: false,
+        thinking_budget_tokens: None,
+        fim_mode: None,
     };
 
     assert_eq!(req.prompt, "Direct prompt");
@@ -68,6 +72,10 @@ fn test_generate_request_without_chat_messages() {
         tools: None,
         stop_sequences: None,
         tool_choice: None,
+        disable_template_substitution: false,
+        suppress_thinking: false,
+        thinking_budget_tokens: None,
+        fim_mode: None,
     };
 
     assert_eq!(req.prompt, "Direct prompt");
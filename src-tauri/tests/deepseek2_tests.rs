@@ -46,3 +46,27 @@ fn test_deepseek2_arch_detection() {
         Some(ArchKind::deepseek2)
     );
 }
+
+#[test]
+fn test_deepseek_variant_detection() {
+    use oxide_lib::models::deepseek2::DeepSeekVariant;
+
+    assert_eq!(
+        DeepSeekVariant::detect(Some("DeepSeek-V2-Chat"), Some(1536)),
+        DeepSeekVariant::V2
+    );
+    assert_eq!(
+        DeepSeekVariant::detect(Some("DeepSeek-V2-Lite-Chat"), None),
+        DeepSeekVariant::V2Lite
+    );
+    assert_eq!(
+        DeepSeekVariant::detect(Some("DeepSeek-R1"), Some(1536)),
+        DeepSeekVariant::R1
+    );
+    assert_eq!(
+        DeepSeekVariant::detect(Some("DeepSeek-R1-Distill-Qwen-7B"), Some(1536)),
+        DeepSeekVariant::R1Distill
+    );
+    assert!(DeepSeekVariant::R1.is_reasoning());
+    assert!(!DeepSeekVariant::V2.is_reasoning());
+}
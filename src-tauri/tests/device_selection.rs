@@ -30,3 +30,20 @@ fn test_explicit_cpu_selection() {
         _ => panic!("Expected CPU device"),
     }
 }
+
+#[test]
+fn test_parse_nvidia_smi_query() {
+    use oxide_lib::core::device::parse_nvidia_smi_query;
+
+    let output = "Driver Version : 555.42.02\nCUDA Version : 12.5\n\
+                  Product Name : NVIDIA GeForce RTX 4090\nFB Memory Usage\n\
+                  Total : 24564 MiB\n";
+    let info = parse_nvidia_smi_query(output).unwrap();
+    assert_eq!(info.name, "NVIDIA GeForce RTX 4090");
+    assert_eq!(info.driver_version, "555.42.02");
+    assert_eq!(info.cuda_version.as_deref(), Some("12.5"));
+    assert_eq!(info.total_vram_mb, 24564);
+
+    assert!(parse_nvidia_smi_query("").is_none());
+    assert!(parse_nvidia_smi_query("Product Name : GPU\nDriver Version : 555.42.02\n").is_none());
+}
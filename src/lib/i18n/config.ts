@@ -132,6 +132,43 @@ const config: Config = {
             key: 'about',
             loader: async () => (await import('./locales/pt-BR/about.json')).default,
         },
+
+        // Simplified Chinese (zh) - общие переводы
+        {
+            locale: 'zh',
+            key: 'common',
+            loader: async () => (await import('./locales/zh/common.json')).default,
+        },
+        {
+            locale: 'zh',
+            key: 'sidebar',
+            loader: async () => (await import('./locales/zh/sidebar.json')).default,
+        },
+        {
+            locale: 'zh',
+            key: 'settings',
+            loader: async () => (await import('./locales/zh/settings.json')).default,
+        },
+        {
+            locale: 'zh',
+            key: 'models',
+            loader: async () => (await import('./locales/zh/models.json')).default,
+        },
+        {
+            locale: 'zh',
+            key: 'chat',
+            loader: async () => (await import('./locales/zh/chat.json')).default,
+        },
+        {
+            locale: 'zh',
+            key: 'errors',
+            loader: async () => (await import('./locales/zh/errors.json')).default,
+        },
+        {
+            locale: 'zh',
+            key: 'about',
+            loader: async () => (await import('./locales/zh/about.json')).default,
+        },
     ],
 
     // Настройки логирования (только ошибки в продакшене)
@@ -11,7 +11,7 @@ import { loadTranslations, locale } from './config';
 const LOCALE_STORAGE_KEY = 'oxide-locale';
 
 // Поддерживаемые локали
-export const SUPPORTED_LOCALES = ['en', 'ru', 'pt-BR'] as const;
+export const SUPPORTED_LOCALES = ['en', 'ru', 'pt-BR', 'zh'] as const;
 export type SupportedLocale = (typeof SUPPORTED_LOCALES)[number];
 
 /**
@@ -3,8 +3,43 @@ import { invoke } from '@tauri-apps/api/core';
 export interface ServerConfig {
     port: number;
     running: boolean;
+    ws_enabled: boolean;
+    max_request_body_mb: number;
+    drain_timeout_secs: number;
+    cors_allowlist: string[];
+}
+
+export interface OpenAiServerSettings {
+    ws_enabled: boolean;
+    /** Applied the next time the server starts */
+    max_request_body_mb: number;
+    /** Seconds to wait for in-flight requests when the server stops */
+    drain_timeout_secs: number;
+    /** Browser origins allowed to call the API; empty allows any origin */
+    cors_allowed_origins: string[];
+}
+
+export interface LocalRagSettings {
+    /** Seconds to reuse an `/v1/embeddings` result; 0 disables the cache */
+    cache_ttl_secs: number;
 }
 
 export async function getServerConfig(): Promise<ServerConfig> {
     return await invoke('get_server_config');
 }
+
+export async function getOpenAiServerSettings(): Promise<OpenAiServerSettings> {
+    return await invoke('get_openai_server_settings');
+}
+
+export async function setOpenAiServerSettings(settings: OpenAiServerSettings): Promise<void> {
+    await invoke('set_openai_server_settings', { settings });
+}
+
+export async function getLocalRagSettings(): Promise<LocalRagSettings> {
+    return await invoke('get_local_rag_settings');
+}
+
+export async function setLocalRagSettings(settings: LocalRagSettings): Promise<void> {
+    await invoke('set_local_rag_settings', { settings });
+}
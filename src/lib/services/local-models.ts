@@ -47,6 +47,14 @@ export class LocalModelsService {
         }
     }
 
+    /**
+     * Register a folder as a models directory (deletion is limited to these folders).
+     */
+    static async registerModelsDir(dir: string): Promise<void> {
+        const { invoke } = await import('@tauri-apps/api/core');
+        await invoke('register_models_dir', { dir });
+    }
+
     /**
      * Delete a local model file.
      */
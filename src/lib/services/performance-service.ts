@@ -10,7 +10,10 @@ import type {
     InferenceMetrics,
     PerformanceSummary,
     StartupMetrics,
+    LoadTimingReport,
+    LoadTimingSettings,
     SystemUsage,
+    PerformanceRegression,
 } from '$lib/types/performance';
 
 export class PerformanceService {
@@ -84,6 +87,24 @@ export class PerformanceService {
         }
     }
 
+    /**
+     * Get stage timings of the last model loads
+     */
+    async getLoadTimingReports(): Promise<LoadTimingReport[]> {
+        const { invoke } = await import('@tauri-apps/api/core');
+        return await invoke<LoadTimingReport[]>('get_load_timing_reports');
+    }
+
+    async getLoadTimingSettings(): Promise<LoadTimingSettings> {
+        const { invoke } = await import('@tauri-apps/api/core');
+        return await invoke<LoadTimingSettings>('get_load_timing_settings');
+    }
+
+    async setLoadTimingSettings(settings: LoadTimingSettings): Promise<void> {
+        const { invoke } = await import('@tauri-apps/api/core');
+        await invoke('set_load_timing_settings', { settings });
+    }
+
     /**
      * Clear all performance metrics
      */
@@ -102,6 +123,14 @@ export class PerformanceService {
         }
     }
 
+    /**
+     * Reset the generation speed baseline for a model (or all models)
+     */
+    async clearBaseline(modelId?: string): Promise<void> {
+        const { invoke } = await import('@tauri-apps/api/core');
+        await invoke('clear_performance_baseline', { modelId: modelId ?? null });
+    }
+
     /**
      * Get performance summary
      */
@@ -140,6 +169,7 @@ export class PerformanceService {
         onModelLoad?: (metrics: ModelLoadMetrics) => void,
         onInference?: (metrics: InferenceMetrics) => void,
         onStartup?: (metrics: StartupMetrics) => void,
+        onRegression?: (regression: PerformanceRegression) => void,
     ): Promise<void> {
 
 
@@ -171,7 +201,15 @@ export class PerformanceService {
             onStartup?.(event.payload);
         });
 
-        this.listeners = [modelLoadListener, inferenceListener, startupListener];
+        const regressionListener = await listen<PerformanceRegression>(
+            'performance_regression_detected',
+            (event) => {
+                console.warn('[Performance] Generation slowdown detected:', event.payload);
+                onRegression?.(event.payload);
+            },
+        );
+
+        this.listeners = [modelLoadListener, inferenceListener, startupListener, regressionListener];
     }
 
     /**
@@ -37,6 +37,27 @@ export type ChatSession = {
     updatedAt: number;
     modelPath?: string;
     repoId?: string;
+    tags: string[];
+};
+
+export type TagCount = {
+    tag: string;
+    count: number;
+};
+
+export type ConversationMatch = {
+    session_id: string;
+    title: string;
+    matched_message_role: string;
+    /** Excerpt around the match; matched terms are wrapped in `**` */
+    message_preview: string;
+    /** Higher is more relevant (negated FTS5 bm25) */
+    relevance_score: number;
+};
+
+export type ConversationSearchResults = {
+    matches: ConversationMatch[];
+    total: number;
 };
 
 export type ChatHistoryState = {
@@ -62,6 +83,24 @@ async function getDb() {
     return dbInstance;
 }
 
+/**
+ * Turns free text into an FTS5 query: every word is quoted so that operators
+ * and punctuation in user input cannot break the MATCH syntax.
+ */
+export function toFtsQuery(query: string): string {
+    return query
+        .split(/\s+/)
+        .filter((word) => word.length > 0)
+        .map((word) => `"${word.replace(/"/g, '""')}"`)
+        .join(' ');
+}
+
+/** Tags are stored trimmed and lowercase; empty tags are dropped. */
+export function normalizeTags(tags: string[]): string[] {
+    const normalized = tags.map((tag) => tag.trim().toLowerCase()).filter((tag) => tag.length > 0);
+    return [...new Set(normalized)];
+}
+
 function dbMessageToChatMessage(msg: DbMessage): ChatMessage {
     return {
         role: msg.role as 'user' | 'assistant',
@@ -89,6 +128,16 @@ function createChatHistoryStore() {
                     'SELECT id, title, model_path, repo_id, created_at, updated_at FROM sessions ORDER BY updated_at DESC',
                 );
 
+                const tagRows = await db.select<{ session_id: string; tag: string }[]>(
+                    'SELECT session_id, tag FROM session_tags ORDER BY tag ASC',
+                );
+                const tagsBySession = new Map<string, string[]>();
+                for (const row of tagRows) {
+                    const tags = tagsBySession.get(row.session_id) ?? [];
+                    tags.push(row.tag);
+                    tagsBySession.set(row.session_id, tags);
+                }
+
                 const sessions: ChatSession[] = rows.map((row) => ({
                     id: row.id,
                     title: row.title,
@@ -96,6 +145,7 @@ function createChatHistoryStore() {
                     repoId: row.repo_id ?? undefined,
                     createdAt: row.created_at,
                     updatedAt: row.updated_at,
+                    tags: tagsBySession.get(row.id) ?? [],
                     messages: [],
                 }));
 
@@ -135,6 +185,7 @@ function createChatHistoryStore() {
                         repoId,
                         createdAt: now,
                         updatedAt: now,
+                        tags: [],
                         messages: [],
                     },
                     ...s.sessions,
@@ -319,6 +370,8 @@ function createChatHistoryStore() {
         deleteSession: async (sessionId: string) => {
             try {
                 const db = await getDb();
+                // foreign_keys is off in this connection, so ON DELETE CASCADE never fires
+                await db.execute('DELETE FROM session_tags WHERE session_id = ?', [sessionId]);
                 await db.execute('DELETE FROM sessions WHERE id = ?', [sessionId]);
             } catch (err) {
                 console.error('Failed to delete session from DB:', err);
@@ -359,6 +412,7 @@ function createChatHistoryStore() {
         clearAll: async () => {
             try {
                 const db = await getDb();
+                await db.execute('DELETE FROM session_tags');
                 await db.execute('DELETE FROM sessions');
             } catch (err) {
                 console.error('Failed to clear chat history:', err);
@@ -367,6 +421,144 @@ function createChatHistoryStore() {
             update((s) => ({ ...s, sessions: [], currentSessionId: null }));
         },
 
+        tagConversation: async (sessionId: string, tags: string[]) => {
+            const normalized = normalizeTags(tags);
+            if (normalized.length === 0) return;
+
+            try {
+                const db = await getDb();
+                for (const tag of normalized) {
+                    await db.execute(
+                        'INSERT OR IGNORE INTO session_tags (session_id, tag) VALUES (?, ?)',
+                        [sessionId, tag],
+                    );
+                }
+            } catch (err) {
+                console.error('Failed to tag session:', err);
+                return;
+            }
+
+            update((s) => ({
+                ...s,
+                sessions: s.sessions.map((sess) =>
+                    sess.id === sessionId
+                        ? { ...sess, tags: normalizeTags([...sess.tags, ...normalized]).sort() }
+                        : sess,
+                ),
+            }));
+        },
+
+        untagConversation: async (sessionId: string, tag: string) => {
+            const [normalized] = normalizeTags([tag]);
+            if (!normalized) return;
+
+            try {
+                const db = await getDb();
+                await db.execute('DELETE FROM session_tags WHERE session_id = ? AND tag = ?', [
+                    sessionId,
+                    normalized,
+                ]);
+            } catch (err) {
+                console.error('Failed to untag session:', err);
+                return;
+            }
+
+            update((s) => ({
+                ...s,
+                sessions: s.sessions.map((sess) =>
+                    sess.id === sessionId
+                        ? { ...sess, tags: sess.tags.filter((t) => t !== normalized) }
+                        : sess,
+                ),
+            }));
+        },
+
+        listConversationsByTag: (tag: string): ChatSession[] => {
+            const [normalized] = normalizeTags([tag]);
+            const state = get({ subscribe });
+            return state.sessions.filter((sess) => normalized && sess.tags.includes(normalized));
+        },
+
+        listAllTags: async (): Promise<TagCount[]> => {
+            try {
+                const db = await getDb();
+                return await db.select<TagCount[]>(
+                    // JOIN skips tags left behind by sessions deleted before tags were cleaned up
+                    `SELECT t.tag AS tag, COUNT(*) AS count
+                     FROM session_tags t
+                     JOIN sessions s ON s.id = t.session_id
+                     GROUP BY t.tag
+                     ORDER BY count DESC, t.tag ASC`,
+                );
+            } catch (err) {
+                console.error('Failed to list tags:', err);
+                return [];
+            }
+        },
+
+        /** Existing tags starting with `prefix`, for autocomplete. */
+        searchTags: async (prefix: string, limit = 10): Promise<string[]> => {
+            const normalized = prefix.trim().toLowerCase();
+            try {
+                const db = await getDb();
+                // Escape LIKE wildcards so they match literally
+                const pattern = normalized.replace(/[\\%_]/g, (c) => `\\${c}`) + '%';
+                const rows = await db.select<{ tag: string }[]>(
+                    `SELECT DISTINCT t.tag AS tag
+                     FROM session_tags t
+                     JOIN sessions s ON s.id = t.session_id
+                     WHERE t.tag LIKE ? ESCAPE '\\'
+                     ORDER BY t.tag ASC
+                     LIMIT ?`,
+                    [pattern, limit],
+                );
+                return rows.map((row) => row.tag);
+            } catch (err) {
+                console.error('Failed to search tags:', err);
+                return [];
+            }
+        },
+
+        /** Full-text search over message content in all sessions, best matches first. */
+        searchAllConversations: async (
+            query: string,
+            limit = 20,
+        ): Promise<ConversationSearchResults> => {
+            const ftsQuery = toFtsQuery(query);
+            if (!ftsQuery) return { matches: [], total: 0 };
+
+            try {
+                const db = await getDb();
+                const matches = await db.select<ConversationMatch[]>(
+                    `SELECT m.session_id AS session_id,
+                            s.title AS title,
+                            m.role AS matched_message_role,
+                            snippet(messages_fts, 0, '**', '**', '…', 16) AS message_preview,
+                            -bm25(messages_fts) AS relevance_score
+                     FROM messages_fts
+                     JOIN messages m ON m.id = messages_fts.rowid
+                     JOIN sessions s ON s.id = m.session_id
+                     WHERE messages_fts MATCH ?
+                     ORDER BY bm25(messages_fts)
+                     LIMIT ?`,
+                    [ftsQuery, limit],
+                );
+                const [{ total }] = await db.select<{ total: number }[]>(
+                    // Same joins as above, so messages of deleted sessions are not counted
+                    `SELECT COUNT(*) AS total
+                     FROM messages_fts
+                     JOIN messages m ON m.id = messages_fts.rowid
+                     JOIN sessions s ON s.id = m.session_id
+                     WHERE messages_fts MATCH ?`,
+                    [ftsQuery],
+                );
+                return { matches, total };
+            } catch (err) {
+                console.error('Failed to search conversations:', err);
+                return { matches: [], total: 0 };
+            }
+        },
+
         exportSession: (sessionId: string) => {
             const state = get({ subscribe });
             const session = state.sessions.find((s) => s.id === sessionId);
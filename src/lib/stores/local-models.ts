@@ -18,6 +18,16 @@ import { LocalModelsService } from '$lib/services/local-models';
 const CACHE_DURATION = 5 * 60 * 1000; // 5 minutes in milliseconds
 const STORAGE_KEY = 'local_models_folder_path';
 
+/**
+ * Mirror the selected folder into the backend models directories.
+ */
+function registerModelsDir(path: string): void {
+    if (!path) return;
+    LocalModelsService.registerModelsDir(path).catch((err) => {
+        console.warn('Failed to register models directory:', err);
+    });
+}
+
 /**
  * Store for selected folder path
  */
@@ -26,6 +36,7 @@ function createFolderPathStore() {
         typeof localStorage !== 'undefined' ? localStorage.getItem(STORAGE_KEY) || '' : '';
 
     const { subscribe, set } = writable<string>(savedPath);
+    registerModelsDir(savedPath);
 
     return {
         subscribe,
@@ -34,6 +45,7 @@ function createFolderPathStore() {
             if (typeof localStorage !== 'undefined') {
                 localStorage.setItem(STORAGE_KEY, path);
             }
+            registerModelsDir(path);
         },
         clear: () => {
             set('');
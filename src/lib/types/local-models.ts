@@ -79,6 +79,18 @@ export interface ModelInfo {
     validation_status: ValidationStatus;
     created_at: string;
     metadata: GGUFMetadata;
+    capabilities?: ModelCapabilities;
+}
+
+/**
+ * Capabilities inferred from the model name, metadata and sibling files.
+ */
+export interface ModelCapabilities {
+    supports_thinking: boolean;
+    supports_vision: boolean;
+    supports_code: boolean;
+    supports_function_calling: boolean;
+    embedding_only: boolean;
 }
 
 /**
@@ -192,6 +204,12 @@ export type DownloadStatus =
     | 'error'
     | 'cancelled';
 
+export type DownloadPriority = 'high' | 'normal' | 'low';
+
+export interface DownloadSettings {
+    max_concurrent_downloads: number;
+}
+
 export interface DownloadJob {
     id: string;
     repo_id: string;
@@ -210,6 +228,8 @@ export interface DownloadJob {
     sha256?: string;
     group_id?: string;
     display_name?: string;
+    commit_sha?: string;
+    priority: DownloadPriority;
 }
 
 export interface DownloadHistoryEntry {
@@ -51,6 +51,19 @@ export interface StartupStage {
     duration_ms: number;
 }
 
+export interface LoadTimingReport {
+    model_id: string;
+    total_ms: number;
+    /** [stage, duration_ms] pairs in completion order */
+    stages: [string, number][];
+}
+
+export interface LoadTimingSettings {
+    stage_warn_threshold_ms: number;
+    /** Read multi-file SafeTensors shards in parallel before mapping them */
+    parallel_load_shards: boolean;
+}
+
 export interface PerformanceSummary {
     current_memory_mb: number;
     last_model_load?: ModelLoadMetrics;
@@ -67,3 +80,11 @@ export interface SystemUsage {
     gpu_memory_mb?: number;
     timestamp: string;
 }
+
+export interface PerformanceRegression {
+    model_id: string;
+    expected_tps: number;
+    actual_tps: number;
+    degradation_percent: number;
+    detected_at: string;
+}